@@ -5,6 +5,7 @@
 
 import { Some, None } from 'ts-results';
 import { EscrowState, type EscrowResponse } from '../types/index.js';
+import { ENTRY_POINTS, ESCROW_STATES } from '../generated/escrow-codes.js';
 import { createRequire } from 'module';
 
 // ============================================
@@ -362,7 +363,7 @@ export class CasperService {
       );
     } else {
       deploy = this.contract.callEntrypoint(
-        'init',
+        ENTRY_POINTS.INIT,
         args,
         senderKey,
         this.networkName,
@@ -389,7 +390,7 @@ export class CasperService {
     const args = RuntimeArgs.fromMap({});
 
     const deploy = contract.callEntrypoint(
-      'accept',
+      ENTRY_POINTS.ACCEPT,
       args,
       senderKey,
       this.networkName,
@@ -418,7 +419,7 @@ export class CasperService {
     });
 
     const deploy = contract.callEntrypoint(
-      'fund',
+      ENTRY_POINTS.FUND,
       args,
       senderKey,
       this.networkName,
@@ -444,7 +445,7 @@ export class CasperService {
     const args = RuntimeArgs.fromMap({});
 
     const deploy = contract.callEntrypoint(
-      'release',
+      ENTRY_POINTS.RELEASE,
       args,
       senderKey,
       this.networkName,
@@ -584,12 +585,12 @@ export class CasperService {
    */
   private parseEscrowState(value: any): EscrowState {
     const stateMap: Record<number, EscrowState> = {
-      0: EscrowState.Draft,
-      1: EscrowState.Accepted,
-      2: EscrowState.Funded,
-      3: EscrowState.Released,
-      4: EscrowState.Cancelled,
-      5: EscrowState.Disputed,
+      [ESCROW_STATES.Draft]: EscrowState.Draft,
+      [ESCROW_STATES.Accepted]: EscrowState.Accepted,
+      [ESCROW_STATES.Funded]: EscrowState.Funded,
+      [ESCROW_STATES.Released]: EscrowState.Released,
+      [ESCROW_STATES.Cancelled]: EscrowState.Cancelled,
+      [ESCROW_STATES.Disputed]: EscrowState.Disputed,
     };
 
     const stateNum = typeof value === 'number' ? value : Number(value);
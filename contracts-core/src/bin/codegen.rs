@@ -0,0 +1,157 @@
+//! Renders the escrow code registry for off-chain clients
+//!
+//! Writes state codes, error codes, entry point names and the native summary
+//! layout as JSON and TypeScript into `api/` and `dashboard/`. Run after
+//! changing the registry:
+//!
+//! ```sh
+//! cargo run --manifest-path contracts-core/Cargo.toml --bin codegen
+//! ```
+
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use escrow_core::{entry_points, Error, State};
+
+const HEADER: &str = "Generated by contracts-core/src/bin/codegen.rs from escrow-core. Do not edit.";
+
+type Render = fn() -> String;
+
+/// Output files relative to the repository root, with their renderer
+const OUTPUTS: [(&str, Render); 3] = [
+    ("api/src/generated/escrow-codes.json", render_json),
+    ("api/src/generated/escrow-codes.ts", render_ts),
+    ("dashboard/src/lib/casper/escrow-codes.ts", render_ts),
+];
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// Quote a string as a JSON string literal, which is also valid TypeScript
+fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn render_json() -> String {
+    let states: Vec<String> = State::ALL
+        .iter()
+        .map(|state| format!("    {{ \"name\": {}, \"code\": {} }}", quote(state.name()), *state as u8))
+        .collect();
+    let errors: Vec<String> = Error::ALL
+        .iter()
+        .map(|error| {
+            format!(
+                "    {{ \"name\": {}, \"code\": {}, \"description\": {} }}",
+                quote(error.name()),
+                error.code(),
+                quote(error.description())
+            )
+        })
+        .collect();
+    let entry_points: Vec<String> = entry_points::ALL
+        .iter()
+        .map(|(name, value)| format!("    {{ \"name\": {}, \"value\": {} }}", quote(name), quote(value)))
+        .collect();
+    let summary_layout: Vec<String> = entry_points::NATIVE_SUMMARY_LAYOUT
+        .iter()
+        .map(|(field, cl_type)| format!("    {{ \"field\": {}, \"cl_type\": {} }}", quote(field), quote(cl_type)))
+        .collect();
+
+    format!(
+        "{{\n  \"_comment\": {},\n  \"states\": [\n{}\n  ],\n  \"errors\": [\n{}\n  ],\n  \"entry_points\": [\n{}\n  ],\n  \"native_summary_layout\": [\n{}\n  ]\n}}\n",
+        quote(HEADER),
+        states.join(",\n"),
+        errors.join(",\n"),
+        entry_points.join(",\n"),
+        summary_layout.join(",\n")
+    )
+}
+
+fn render_ts() -> String {
+    let mut ts = format!("// {}\n\n", HEADER);
+
+    ts.push_str("/** Escrow state codes, as returned by `get_state` */\nexport const ESCROW_STATES = {\n");
+    for state in State::ALL {
+        writeln!(ts, "  {}: {},", state.name(), state as u8).unwrap();
+    }
+    ts.push_str("} as const;\n\n");
+    ts.push_str("export type EscrowStateName = keyof typeof ESCROW_STATES;\n");
+    ts.push_str("export type EscrowStateCode = (typeof ESCROW_STATES)[EscrowStateName];\n\n");
+
+    ts.push_str("/** Contract error codes, reported as `User error: <code>` */\nexport const ESCROW_ERRORS = {\n");
+    for error in Error::ALL {
+        writeln!(ts, "  {}: {},", error.name(), error.code()).unwrap();
+    }
+    ts.push_str("} as const;\n\n");
+    ts.push_str("export type EscrowErrorName = keyof typeof ESCROW_ERRORS;\n");
+    ts.push_str("export type EscrowErrorCode = (typeof ESCROW_ERRORS)[EscrowErrorName];\n\n");
+
+    ts.push_str("export const ESCROW_ERROR_DESCRIPTIONS: Record<EscrowErrorCode, string> = {\n");
+    for error in Error::ALL {
+        writeln!(ts, "  {}: {},", error.code(), quote(error.description())).unwrap();
+    }
+    ts.push_str("};\n\n");
+
+    ts.push_str("/** Entry point names of the Odra and native contracts */\nexport const ENTRY_POINTS = {\n");
+    for (name, value) in entry_points::ALL {
+        writeln!(ts, "  {}: {},", name, quote(value)).unwrap();
+    }
+    ts.push_str("} as const;\n\n");
+    ts.push_str("export type EntryPointName = (typeof ENTRY_POINTS)[keyof typeof ENTRY_POINTS];\n\n");
+
+    ts.push_str(
+        "/**\n * Fields of the native `get_summary` value with their CL types. The value is\n * typed `Any` and holds each field's bytes in this order, with no length prefix.\n */\n",
+    );
+    ts.push_str("export const NATIVE_SUMMARY_LAYOUT = [\n");
+    for (field, cl_type) in entry_points::NATIVE_SUMMARY_LAYOUT {
+        writeln!(ts, "  {{ field: {}, clType: {} }},", quote(field), quote(cl_type)).unwrap();
+    }
+    ts.push_str("] as const;\n");
+
+    ts
+}
+
+fn main() {
+    let root = repo_root();
+    for (path, render) in OUTPUTS {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, render()).unwrap();
+        println!("wrote {}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_files_are_current() {
+        let root = repo_root();
+        for (path, render) in OUTPUTS {
+            let written = fs::read_to_string(root.join(path)).unwrap_or_default();
+            assert!(
+                written == render(),
+                "{} is out of date; run the codegen binary",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_quote_escapes() {
+        assert_eq!(quote("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+    }
+}
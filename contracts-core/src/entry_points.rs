@@ -0,0 +1,77 @@
+//! Entry point names of both contract flavours
+
+macro_rules! entry_points {
+    ($($(#[doc = $doc:literal])+ $name:ident = $value:literal,)+) => {
+        $($(#[doc = $doc])+ pub const $name: &str = $value;)+
+
+        /// Every entry point, as (constant name, entry point name)
+        pub const ALL: &[(&str, &str)] = &[$((stringify!($name), $value)),+];
+    };
+}
+
+entry_points! {
+    /// Odra constructor
+    INIT = "init",
+    /// Native: create an escrow in the installed contract
+    CREATE_ESCROW = "create_escrow",
+    /// Payer accepts the escrow terms
+    ACCEPT = "accept",
+    /// Payer deposits the escrow amount
+    FUND = "fund",
+    /// Odra: record a release approval
+    APPROVE = "approve",
+    /// Odra: reveal a release secret
+    REVEAL_SECRET = "reveal_secret",
+    /// Pay out to the issuer
+    RELEASE = "release",
+    /// Cancel before funding
+    CANCEL = "cancel",
+    /// Raise a dispute over funded escrow
+    DISPUTE = "dispute",
+    /// Arbiter settles a dispute
+    RESOLVE_DISPUTE = "resolve_dispute",
+    /// Current state
+    GET_STATE = "get_state",
+    /// Amount held in escrow
+    GET_BALANCE = "get_balance",
+    /// Complete escrow snapshot
+    GET_SUMMARY = "get_summary",
+    /// Odra: invoice details
+    GET_INVOICE = "get_invoice",
+    /// Odra: release rule with defaults applied
+    GET_RELEASE_CONDITIONS = "get_release_conditions",
+    /// Odra: release conditions still unmet
+    GET_UNMET_CONDITIONS = "get_unmet_conditions",
+    /// Odra: number of recorded transitions
+    GET_HISTORY_LEN = "get_history_len",
+    /// Odra: page of recorded transitions
+    GET_HISTORY = "get_history",
+    /// Native: number of escrows created so far
+    GET_ESCROW_COUNT = "get_escrow_count",
+    /// Native: recorded balance and actual escrow purse balance
+    GET_PURSE_BALANCES = "get_purse_balances",
+    /// Native: return purse funds beyond the recorded balance, once issuer and payer agree
+    SWEEP = "sweep",
+}
+
+/// Fields of the native `get_summary` value, as (field, CL type). The value
+/// is exposed as `CLType::Any` and holds the fields' bytes in this order.
+pub const NATIVE_SUMMARY_LAYOUT: &[(&str, &str)] = &[
+    ("escrow_id", "U64"),
+    ("state", "U8"),
+    ("issuer", "Key"),
+    ("payer", "Key"),
+    ("arbiter", "Option<Key>"),
+    ("amount", "U512"),
+    ("balance", "U512"),
+    ("description", "String"),
+    ("due_date", "Option<U64>"),
+    ("created_at", "U64"),
+    ("accepted_at", "Option<U64>"),
+    ("funded_at", "Option<U64>"),
+    ("released_at", "Option<U64>"),
+    ("dispute", "Option<(Key, String, U64)>"),
+    ("refunded", "Bool"),
+    ("issuer_purse", "Option<URef>"),
+    ("payer_purse", "Option<URef>"),
+];
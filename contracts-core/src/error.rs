@@ -0,0 +1,110 @@
+//! Error codes shared by both contract flavours
+
+macro_rules! errors {
+    ($($(#[doc = $doc:literal])+ $name:ident = $code:literal,)+) => {
+        /// Escrow errors. The discriminant is the user error code reported on
+        /// chain, so existing values must never change.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[repr(u16)]
+        pub enum Error {
+            $($(#[doc = $doc])+ $name = $code,)+
+        }
+
+        impl Error {
+            /// Every error, in code order
+            pub const ALL: &'static [Error] = &[$(Error::$name),+];
+
+            /// Variant name, as used in generated client bindings
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Error::$name => stringify!($name),)+
+                }
+            }
+
+            /// One-line description, taken from the variant's doc comment
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Error::$name => concat!($($doc),+).trim(),)+
+                }
+            }
+        }
+    };
+}
+
+errors! {
+    /// Caller is not authorized to perform this action
+    Unauthorized = 1,
+    /// Invalid state for this operation
+    InvalidState = 2,
+    /// Insufficient funds provided
+    InsufficientFunds = 3,
+    /// Escrow not found
+    NotFound = 4,
+    /// Release conditions are not yet satisfied
+    ConditionsNotMet = 5,
+    /// Revealed secret does not match any release condition
+    InvalidSecret = 6,
+    /// Escrow amount must be greater than zero
+    ZeroAmount = 7,
+    /// Payer must differ from the issuer
+    PayerIsIssuer = 8,
+    /// Arbiter must not be the issuer or the payer
+    ArbiterIsParty = 9,
+    /// Due date must lie in the future
+    DueDateInPast = 10,
+    /// Escrow id must not be empty
+    EmptyId = 11,
+    /// Escrow id exceeds the maximum length
+    IdTooLong = 12,
+    /// Description must not be empty
+    EmptyDescription = 13,
+    /// Description exceeds the maximum length
+    DescriptionTooLong = 14,
+    /// Release condition is malformed (empty group, bad threshold, duplicate approvers)
+    InvalidReleaseCondition = 15,
+    /// Moving funds out of or into the escrow purse failed
+    TransferFailed = 16,
+    /// Escrow purse holds no funds beyond the recorded balance
+    NothingToSweep = 17,
+    /// A contract party must pass a purse it can receive payouts into
+    PayoutPurseRequired = 18,
+    /// Party must be an account or a contract package
+    InvalidParty = 19,
+    /// Accounts are paid into their main purse and take no payout purse
+    UnexpectedPayoutPurse = 20,
+}
+
+impl Error {
+    /// User error code reported when a contract reverts with this error
+    pub const fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// Decode a user error code
+    pub fn from_code(code: u16) -> Option<Error> {
+        Error::ALL.iter().copied().find(|error| error.code() == code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_unique_and_ordered() {
+        for (index, error) in Error::ALL.iter().enumerate() {
+            assert_eq!(error.code() as usize, index + 1);
+            assert_eq!(Error::from_code(error.code()), Some(*error));
+        }
+        assert_eq!(Error::from_code(0), None);
+    }
+
+    #[test]
+    fn test_names_and_descriptions() {
+        assert_eq!(Error::Unauthorized.name(), "Unauthorized");
+        assert_eq!(
+            Error::Unauthorized.description(),
+            "Caller is not authorized to perform this action"
+        );
+    }
+}
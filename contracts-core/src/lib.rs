@@ -0,0 +1,23 @@
+//! Escrow rules shared by the Odra and native contracts
+//!
+//! Holds the state machine, caller roles, error codes, entry point names and
+//! input limits, with no storage or runtime dependencies. Each contract reads
+//! its own storage, asks this crate whether an action is allowed, and writes
+//! the resulting state.
+//!
+//! This is also the canonical registry of codes for off-chain clients: the
+//! `codegen` binary renders it as JSON and TypeScript for `api/` and `dashboard/`.
+
+#![no_std]
+
+pub mod entry_points;
+mod error;
+mod machine;
+mod validation;
+
+pub use error::Error;
+pub use machine::{check_funding, transition, Action, Role, State};
+pub use validation::{
+    validate_description, validate_due_date, validate_id, validate_parties, MAX_DESCRIPTION_LENGTH,
+    MAX_ID_LENGTH,
+};
@@ -0,0 +1,236 @@
+//! Escrow state machine
+
+use crate::Error;
+
+/// Lifecycle state of an escrow. The discriminant is the stored `u8` value
+/// of the native contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum State {
+    /// Created by the issuer, awaiting the payer
+    Draft = 0,
+    /// Payer has accepted the escrow terms
+    Accepted = 1,
+    /// Funds have been deposited into escrow
+    Funded = 2,
+    /// Funds have been paid out
+    Released = 3,
+    /// Escrow was cancelled before funding
+    Cancelled = 4,
+    /// A party raised a dispute; only the arbiter can move it on
+    Disputed = 5,
+}
+
+impl State {
+    /// Every state, in discriminant order
+    pub const ALL: [State; 6] = [
+        State::Draft,
+        State::Accepted,
+        State::Funded,
+        State::Released,
+        State::Cancelled,
+        State::Disputed,
+    ];
+
+    /// Decode a stored state value
+    pub fn from_u8(value: u8) -> Option<State> {
+        State::ALL.get(value as usize).copied()
+    }
+
+    /// Variant name, as used in generated client bindings
+    pub const fn name(self) -> &'static str {
+        match self {
+            State::Draft => "Draft",
+            State::Accepted => "Accepted",
+            State::Funded => "Funded",
+            State::Released => "Released",
+            State::Cancelled => "Cancelled",
+            State::Disputed => "Disputed",
+        }
+    }
+
+    /// Whether no further action can change this state
+    pub fn is_final(self) -> bool {
+        matches!(self, State::Released | State::Cancelled)
+    }
+}
+
+/// Role of the caller relative to one escrow
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Invoice issuer, who receives the funds
+    Issuer,
+    /// Invoice payer, who deposits the funds
+    Payer,
+    /// Dispute resolver named at creation
+    Arbiter,
+    /// Anyone else
+    Other,
+}
+
+impl Role {
+    /// Every role
+    pub const ALL: [Role; 4] = [Role::Issuer, Role::Payer, Role::Arbiter, Role::Other];
+
+    /// Classify `caller` against the parties of an escrow
+    pub fn of<A: PartialEq>(caller: &A, issuer: &A, payer: &A, arbiter: Option<&A>) -> Role {
+        if caller == issuer {
+            Role::Issuer
+        } else if caller == payer {
+            Role::Payer
+        } else if arbiter == Some(caller) {
+            Role::Arbiter
+        } else {
+            Role::Other
+        }
+    }
+}
+
+/// Operation a caller attempts on an escrow
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Accept the escrow terms
+    Accept,
+    /// Deposit the escrow amount
+    Fund,
+    /// Record a release approval; any caller may approve
+    Approve,
+    /// Reveal a release secret; any caller may reveal
+    RevealSecret,
+    /// Pay out to the issuer. Release conditions are checked by the contract.
+    Release,
+    /// Cancel before funding
+    Cancel,
+    /// Raise a dispute over funded escrow
+    Dispute,
+    /// Settle a dispute in favour of either party
+    ResolveDispute,
+}
+
+impl Action {
+    /// Every action
+    pub const ALL: [Action; 8] = [
+        Action::Accept,
+        Action::Fund,
+        Action::Approve,
+        Action::RevealSecret,
+        Action::Release,
+        Action::Cancel,
+        Action::Dispute,
+        Action::ResolveDispute,
+    ];
+}
+
+/// Return the state `action` by `role` leads to from state `from`.
+/// The state is checked before the role, so a wrong-state call reports
+/// `InvalidState` whoever makes it.
+pub fn transition(from: State, action: Action, role: Role) -> Result<State, Error> {
+    use Action::*;
+    use Role::*;
+    use State::*;
+
+    let (allowed, to) = match (action, from) {
+        (Accept, Draft) => (role == Payer, Accepted),
+        (Fund, Accepted) => (role == Payer, Funded),
+        (Approve | RevealSecret, Funded) => (true, Funded),
+        (Release, Funded) => (matches!(role, Issuer | Payer), Released),
+        // Only the issuer can withdraw a draft; both parties can back out before funding
+        (Cancel, Draft) => (role == Issuer, Cancelled),
+        (Cancel, Accepted) => (matches!(role, Issuer | Payer), Cancelled),
+        (Dispute, Funded) => (matches!(role, Issuer | Payer), Disputed),
+        (ResolveDispute, Disputed) => (role == Arbiter, Released),
+        _ => return Err(Error::InvalidState),
+    };
+
+    if allowed {
+        Ok(to)
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+/// Check that a deposit covers the required escrow amount
+pub fn check_funding<A: PartialOrd>(required: &A, amount: &A) -> Result<(), Error> {
+    if amount < required {
+        return Err(Error::InsufficientFunds);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Allowed transitions, written out independently of `transition`
+    const TABLE: &[(State, Action, &[Role], State)] = &[
+        (State::Draft, Action::Accept, &[Role::Payer], State::Accepted),
+        (State::Draft, Action::Cancel, &[Role::Issuer], State::Cancelled),
+        (State::Accepted, Action::Fund, &[Role::Payer], State::Funded),
+        (State::Accepted, Action::Cancel, &[Role::Issuer, Role::Payer], State::Cancelled),
+        (State::Funded, Action::Approve, &Role::ALL, State::Funded),
+        (State::Funded, Action::RevealSecret, &Role::ALL, State::Funded),
+        (State::Funded, Action::Release, &[Role::Issuer, Role::Payer], State::Released),
+        (State::Funded, Action::Dispute, &[Role::Issuer, Role::Payer], State::Disputed),
+        (State::Disputed, Action::ResolveDispute, &[Role::Arbiter], State::Released),
+    ];
+
+    #[test]
+    fn test_transition_table_is_exhaustive() {
+        for from in State::ALL {
+            for action in Action::ALL {
+                let entry = TABLE.iter().find(|(f, a, _, _)| *f == from && *a == action);
+                for role in Role::ALL {
+                    let expected = match entry {
+                        None => Err(Error::InvalidState),
+                        Some((_, _, roles, to)) if roles.contains(&role) => Ok(*to),
+                        Some(_) => Err(Error::Unauthorized),
+                    };
+                    assert_eq!(
+                        transition(from, action, role),
+                        expected,
+                        "{:?} --{:?} by {:?}-->",
+                        from,
+                        action,
+                        role
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_final_states_accept_no_action() {
+        for from in State::ALL.into_iter().filter(|s| s.is_final()) {
+            for action in Action::ALL {
+                for role in Role::ALL {
+                    assert_eq!(transition(from, action, role), Err(Error::InvalidState));
+                }
+            }
+        }
+        assert!(!State::Disputed.is_final());
+    }
+
+    #[test]
+    fn test_state_round_trips_through_u8() {
+        for state in State::ALL {
+            assert_eq!(State::from_u8(state as u8), Some(state));
+        }
+        assert_eq!(State::from_u8(State::ALL.len() as u8), None);
+    }
+
+    #[test]
+    fn test_role_of_caller() {
+        assert_eq!(Role::of(&1, &1, &2, Some(&3)), Role::Issuer);
+        assert_eq!(Role::of(&2, &1, &2, Some(&3)), Role::Payer);
+        assert_eq!(Role::of(&3, &1, &2, Some(&3)), Role::Arbiter);
+        assert_eq!(Role::of(&3, &1, &2, None), Role::Other);
+        assert_eq!(Role::of(&4, &1, &2, Some(&3)), Role::Other);
+    }
+
+    #[test]
+    fn test_check_funding() {
+        assert_eq!(check_funding(&1000u64, &999), Err(Error::InsufficientFunds));
+        assert_eq!(check_funding(&1000u64, &1000), Ok(()));
+        assert_eq!(check_funding(&1000u64, &1001), Ok(()));
+    }
+}
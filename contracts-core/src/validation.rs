@@ -0,0 +1,72 @@
+//! Input checks applied when an escrow is created
+
+use crate::Error;
+
+/// Maximum length of an escrow identifier, in bytes
+pub const MAX_ID_LENGTH: usize = 64;
+/// Maximum length of an escrow description, in bytes
+pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+
+/// Check that an escrow id is present and within bounds
+pub fn validate_id(id: &str) -> Result<(), Error> {
+    if id.is_empty() {
+        return Err(Error::EmptyId);
+    }
+    if id.len() > MAX_ID_LENGTH {
+        return Err(Error::IdTooLong);
+    }
+    Ok(())
+}
+
+/// Check that a description is present and within bounds
+pub fn validate_description(description: &str) -> Result<(), Error> {
+    if description.is_empty() {
+        return Err(Error::EmptyDescription);
+    }
+    if description.len() > MAX_DESCRIPTION_LENGTH {
+        return Err(Error::DescriptionTooLong);
+    }
+    Ok(())
+}
+
+/// Check that the payer and the optional arbiter are distinct from the other parties
+pub fn validate_parties<A: PartialEq>(issuer: &A, payer: &A, arbiter: Option<&A>) -> Result<(), Error> {
+    if payer == issuer {
+        return Err(Error::PayerIsIssuer);
+    }
+    if let Some(arbiter) = arbiter {
+        if arbiter == issuer || arbiter == payer {
+            return Err(Error::ArbiterIsParty);
+        }
+    }
+    Ok(())
+}
+
+/// Check that an optional due date lies after block time `now`
+pub fn validate_due_date(due_date: Option<u64>, now: u64) -> Result<(), Error> {
+    match due_date {
+        Some(due_date) if due_date <= now => Err(Error::DueDateInPast),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_parties() {
+        assert_eq!(validate_parties(&1, &2, None), Ok(()));
+        assert_eq!(validate_parties(&1, &2, Some(&3)), Ok(()));
+        assert_eq!(validate_parties(&1, &1, None), Err(Error::PayerIsIssuer));
+        assert_eq!(validate_parties(&1, &2, Some(&1)), Err(Error::ArbiterIsParty));
+        assert_eq!(validate_parties(&1, &2, Some(&2)), Err(Error::ArbiterIsParty));
+    }
+
+    #[test]
+    fn test_validate_due_date() {
+        assert_eq!(validate_due_date(None, 100), Ok(()));
+        assert_eq!(validate_due_date(Some(101), 100), Ok(()));
+        assert_eq!(validate_due_date(Some(100), 100), Err(Error::DueDateInPast));
+    }
+}
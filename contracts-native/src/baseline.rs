@@ -1,5 +1,8 @@
+//! First released escrow installer, kept unchanged so the tests can upgrade
+//! a contract installed from it. Built for Casper 2.0 only; not deployed.
+//!
 //! Native Escrow Contract for Casper 2.0
-//! This is a simplified escrow that works on Casper testnet
+//! Simplified escrow for B2B payments
 
 #![no_std]
 #![no_main]
@@ -13,9 +16,11 @@ use casper_contract::{
 };
 use casper_types::{
     api_error::ApiError,
-    contracts::{EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, NamedKeys, Parameters},
-    CLType, CLValue, Key, Parameter, RuntimeArgs, URef, U512,
-    account::AccountHash,
+    addressable_entity::{
+        EntityEntryPoint, EntryPointAccess, EntryPointPayment, EntryPointType, EntryPoints, Parameter,
+    },
+    CLType, CLValue, URef, U512, NamedKeys,
+    account::AccountHash, CLTyped, bytesrepr::FromBytes,
 };
 
 // Contract keys
@@ -38,6 +43,7 @@ const EP_FUND: &str = "fund";
 const EP_RELEASE: &str = "release";
 const EP_CANCEL: &str = "cancel";
 const EP_GET_STATE: &str = "get_state";
+const EP_GET_BALANCE: &str = "get_balance";
 
 // States: 0=Draft, 1=Accepted, 2=Funded, 3=Released, 4=Cancelled
 const STATE_DRAFT: u8 = 0;
@@ -62,30 +68,25 @@ impl From<EscrowError> for ApiError {
 }
 
 // Helper to get stored value
-fn get_key<T: casper_types::bytesrepr::FromBytes + CLTyped>(name: &str) -> T {
-    let uref: URef = runtime::get_key(name)
+fn get_uref(name: &str) -> URef {
+    runtime::get_key(name)
         .unwrap_or_revert_with(ApiError::MissingKey)
         .into_uref()
-        .unwrap_or_revert_with(ApiError::UnexpectedKeyVariant);
+        .unwrap_or_revert_with(ApiError::UnexpectedKeyVariant)
+}
+
+fn get_key<T: FromBytes + CLTyped>(name: &str) -> T {
+    let uref = get_uref(name);
     storage::read(uref)
         .unwrap_or_revert()
         .unwrap_or_revert()
 }
 
-// Helper to set stored value
 fn set_key<T: casper_types::bytesrepr::ToBytes + CLTyped>(name: &str, value: T) {
-    let uref: URef = runtime::get_key(name)
-        .unwrap_or_revert_with(ApiError::MissingKey)
-        .into_uref()
-        .unwrap_or_revert_with(ApiError::UnexpectedKeyVariant);
+    let uref = get_uref(name);
     storage::write(uref, value);
 }
 
-// Get caller as AccountHash
-fn get_caller() -> AccountHash {
-    runtime::get_caller()
-}
-
 /// Accept the escrow terms (called by payer)
 #[no_mangle]
 pub extern "C" fn accept() {
@@ -95,7 +96,7 @@ pub extern "C" fn accept() {
     }
 
     let payer: AccountHash = get_key(PAYER_KEY);
-    if get_caller() != payer {
+    if runtime::get_caller() != payer {
         runtime::revert(EscrowError::Unauthorized);
     }
 
@@ -111,7 +112,7 @@ pub extern "C" fn fund() {
     }
 
     let payer: AccountHash = get_key(PAYER_KEY);
-    if get_caller() != payer {
+    if runtime::get_caller() != payer {
         runtime::revert(EscrowError::Unauthorized);
     }
 
@@ -122,9 +123,11 @@ pub extern "C" fn fund() {
         runtime::revert(EscrowError::InsufficientFunds);
     }
 
-    // Get escrow purse and transfer funds
-    let escrow_purse: URef = get_key(PURSE_KEY);
-    let source_purse: URef = runtime::get_named_arg("source_purse");
+    // Get escrow purse and transfer funds from caller's main purse
+    let escrow_purse: URef = get_uref(PURSE_KEY);
+
+    // Get caller's main purse
+    let source_purse: URef = runtime::get_named_arg("source");
 
     system::transfer_from_purse_to_purse(source_purse, escrow_purse, amount, None)
         .unwrap_or_revert_with(EscrowError::TransferFailed);
@@ -142,13 +145,13 @@ pub extern "C" fn release() {
     }
 
     let payer: AccountHash = get_key(PAYER_KEY);
-    if get_caller() != payer {
+    if runtime::get_caller() != payer {
         runtime::revert(EscrowError::Unauthorized);
     }
 
     let issuer: AccountHash = get_key(ISSUER_KEY);
     let balance: U512 = get_key(BALANCE_KEY);
-    let escrow_purse: URef = get_key(PURSE_KEY);
+    let escrow_purse: URef = get_uref(PURSE_KEY);
 
     // Transfer to issuer
     system::transfer_from_purse_to_account(escrow_purse, issuer, balance, None)
@@ -162,7 +165,7 @@ pub extern "C" fn release() {
 #[no_mangle]
 pub extern "C" fn cancel() {
     let state: u8 = get_key(STATE_KEY);
-    let caller = get_caller();
+    let caller = runtime::get_caller();
     let issuer: AccountHash = get_key(ISSUER_KEY);
     let payer: AccountHash = get_key(PAYER_KEY);
 
@@ -190,13 +193,24 @@ pub extern "C" fn get_state() {
     runtime::ret(CLValue::from_t(state).unwrap_or_revert());
 }
 
+/// Get current balance (view function)
+#[no_mangle]
+pub extern "C" fn get_balance() {
+    let balance: U512 = get_key(BALANCE_KEY);
+    runtime::ret(CLValue::from_t(balance).unwrap_or_revert());
+}
+
 /// Contract installation entry point
 #[no_mangle]
 pub extern "C" fn call() {
-    // Get initialization arguments
-    let issuer: AccountHash = runtime::get_named_arg("issuer");
-    let payer: AccountHash = runtime::get_named_arg("payer");
-    let amount: U512 = runtime::get_named_arg("amount");
+    // For demo: use caller as both issuer and payer
+    let caller = runtime::get_caller();
+    let issuer = caller;
+    let payer = caller;
+
+    // Use U64 for amount (simpler serialization), convert to U512 for storage
+    let amount_u64: u64 = runtime::get_named_arg("amount");
+    let amount = U512::from(amount_u64);
     let description: String = runtime::get_named_arg("description");
 
     // Create escrow purse to hold funds
@@ -212,63 +226,75 @@ pub extern "C" fn call() {
     named_keys.insert(BALANCE_KEY.to_string(), storage::new_uref(U512::zero()).into());
     named_keys.insert(PURSE_KEY.to_string(), escrow_purse.into());
 
-    // Define entry points
+    // Define entry points (Casper 2.0 API)
     let mut entry_points = EntryPoints::new();
 
-    entry_points.add_entry_point(EntryPoint::new(
+    entry_points.add_entry_point(EntityEntryPoint::new(
         EP_ACCEPT,
         Vec::new(),
         CLType::Unit,
         EntryPointAccess::Public,
-        EntryPointType::Contract,
+        EntryPointType::Called,
+        EntryPointPayment::Caller,
     ));
 
-    entry_points.add_entry_point(EntryPoint::new(
+    entry_points.add_entry_point(EntityEntryPoint::new(
         EP_FUND,
         vec![
             Parameter::new("amount", CLType::U512),
-            Parameter::new("source_purse", CLType::URef),
+            Parameter::new("source", CLType::URef),
         ],
         CLType::Unit,
         EntryPointAccess::Public,
-        EntryPointType::Contract,
+        EntryPointType::Called,
+        EntryPointPayment::Caller,
     ));
 
-    entry_points.add_entry_point(EntryPoint::new(
+    entry_points.add_entry_point(EntityEntryPoint::new(
         EP_RELEASE,
         Vec::new(),
         CLType::Unit,
         EntryPointAccess::Public,
-        EntryPointType::Contract,
+        EntryPointType::Called,
+        EntryPointPayment::Caller,
     ));
 
-    entry_points.add_entry_point(EntryPoint::new(
+    entry_points.add_entry_point(EntityEntryPoint::new(
         EP_CANCEL,
         Vec::new(),
         CLType::Unit,
         EntryPointAccess::Public,
-        EntryPointType::Contract,
+        EntryPointType::Called,
+        EntryPointPayment::Caller,
     ));
 
-    entry_points.add_entry_point(EntryPoint::new(
+    entry_points.add_entry_point(EntityEntryPoint::new(
         EP_GET_STATE,
         Vec::new(),
         CLType::U8,
         EntryPointAccess::Public,
-        EntryPointType::Contract,
+        EntryPointType::Called,
+        EntryPointPayment::Caller,
+    ));
+
+    entry_points.add_entry_point(EntityEntryPoint::new(
+        EP_GET_BALANCE,
+        Vec::new(),
+        CLType::U512,
+        EntryPointAccess::Public,
+        EntryPointType::Called,
+        EntryPointPayment::Caller,
     ));
 
-    // Install contract
+    // Install contract (Casper 2.0 API with message_topics)
     let (contract_hash, _contract_version) = storage::new_contract(
         entry_points,
         Some(named_keys),
         Some(CONTRACT_PACKAGE_NAME.to_string()),
         Some(CONTRACT_ACCESS_UREF.to_string()),
+        None, // message_topics - not used in this contract
     );
 
     // Store contract hash for easy access
     runtime::put_key(CONTRACT_KEY, contract_hash.into());
 }
-
-// Required for CLTyped trait
-use casper_types::CLTyped;
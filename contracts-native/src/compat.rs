@@ -0,0 +1,157 @@
+//! Differences between the Casper 1.x and 2.0 contract APIs
+//!
+//! The rest of the crate is written against the API both generations share
+//! and calls through here for everything else. Each binary aliases the 1.x
+//! crates to their usual names at its root when `casper-1x` is enabled.
+
+#[cfg(all(feature = "casper-1x", feature = "casper-2x"))]
+compile_error!("features `casper-1x` and `casper-2x` are mutually exclusive");
+#[cfg(not(any(feature = "casper-1x", feature = "casper-2x")))]
+compile_error!("enable one of the `casper-1x` or `casper-2x` features");
+
+use alloc::vec::Vec;
+use casper_contract::contract_api::{runtime, storage};
+use casper_types::{
+    bytesrepr::FromBytes,
+    contracts::{ContractHash, ContractPackageHash, NamedKeys},
+    CLType, EntryPoints, Key, Parameter,
+};
+
+#[cfg(feature = "casper-2x")]
+use casper_types::addressable_entity::{
+    EntityEntryPoint as EntryPoint, EntryPointAccess, EntryPointPayment, EntryPointType,
+};
+#[cfg(feature = "casper-1x")]
+use casper_types::{EntryPoint, EntryPointAccess, EntryPointType};
+
+/// Public entry point called in the contract's own context
+#[cfg(feature = "casper-2x")]
+pub fn entry_point(name: &str, params: Vec<Parameter>, ret: CLType) -> EntryPoint {
+    EntryPoint::new(
+        name,
+        params,
+        ret,
+        EntryPointAccess::Public,
+        EntryPointType::Called,
+        EntryPointPayment::Caller,
+    )
+}
+
+/// Public entry point called in the contract's own context
+#[cfg(feature = "casper-1x")]
+pub fn entry_point(name: &str, params: Vec<Parameter>, ret: CLType) -> EntryPoint {
+    EntryPoint::new(name, params, ret, EntryPointAccess::Public, EntryPointType::Contract)
+}
+
+/// Install a contract into a new package, returning its hash and version
+pub fn new_contract(
+    entry_points: EntryPoints,
+    named_keys: NamedKeys,
+    package_name: &str,
+    access_uref_name: &str,
+) -> (ContractHash, u32) {
+    let named_keys = Some(named_keys);
+    let package_name = Some(package_name.into());
+    let access_uref_name = Some(access_uref_name.into());
+
+    #[cfg(feature = "casper-2x")]
+    return storage::new_contract(
+        entry_points,
+        named_keys,
+        package_name,
+        access_uref_name,
+        None, // message_topics - not used in this contract
+    );
+    #[cfg(feature = "casper-1x")]
+    return storage::new_contract(entry_points, named_keys, package_name, access_uref_name);
+}
+
+/// Add a contract version to an existing package, returning its hash and version
+pub fn add_contract_version(
+    package_hash: ContractPackageHash,
+    entry_points: EntryPoints,
+) -> (ContractHash, u32) {
+    // Named keys of the previous version carry over to the new one
+    #[cfg(feature = "casper-2x")]
+    return storage::add_contract_version(
+        package_hash,
+        entry_points,
+        NamedKeys::new(),
+        alloc::collections::BTreeMap::new(),
+    );
+    #[cfg(feature = "casper-1x")]
+    return storage::add_contract_version(package_hash, entry_points, NamedKeys::new());
+}
+
+/// Read an optional named argument of the current call.
+/// An argument that is present but does not decode as `T` reverts with
+/// `ApiError::InvalidArgument`; the 2.0 `runtime::try_get_named_arg` would
+/// silently treat it as missing.
+pub fn try_get_named_arg<T: FromBytes>(name: &str) -> Option<T> {
+    use casper_contract::ext_ffi;
+    use casper_types::{api_error, ApiError};
+
+    let mut size = 0usize;
+    let ret = unsafe { ext_ffi::casper_get_named_arg_size(name.as_ptr(), name.len(), &mut size) };
+    match api_error::result_from(ret) {
+        Ok(()) => Some(runtime::get_named_arg(name)),
+        Err(ApiError::MissingArgument) => None,
+        Err(error) => runtime::revert(error),
+    }
+}
+
+/// Immediate caller of the current entry point: the calling account, or the
+/// package of a calling contract. This is how Odra's `Address` serializes.
+#[cfg(feature = "casper-2x")]
+pub fn immediate_caller() -> Key {
+    use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+    use casper_types::{account::AccountHash, system::CallerInfo, ApiError, CLTyped, PackageHash};
+
+    // Field indices of `CallerInfo`
+    const ACCOUNT: u8 = 0;
+    const PACKAGE: u8 = 1;
+    const CONTRACT_PACKAGE: u8 = 2;
+
+    fn field<T: CLTyped + FromBytes>(caller: &CallerInfo, index: u8) -> Option<T> {
+        caller.get_field_by_index(index)?.clone().into_t::<Option<T>>().ok().flatten()
+    }
+
+    let caller = runtime::get_immediate_caller().unwrap_or_revert();
+    if let Some(account) = field::<AccountHash>(&caller, ACCOUNT) {
+        return Key::Account(account);
+    }
+    if let Some(package) = field::<ContractPackageHash>(&caller, CONTRACT_PACKAGE) {
+        return Key::Hash(package.value());
+    }
+    if let Some(package) = field::<PackageHash>(&caller, PACKAGE) {
+        return Key::Hash(package.value());
+    }
+    runtime::revert(ApiError::InvalidCallerInfoRequest)
+}
+
+/// Immediate caller of the current entry point: the calling account, or the
+/// package of a calling contract. This is how Odra's `Address` serializes.
+#[cfg(feature = "casper-1x")]
+pub fn immediate_caller() -> Key {
+    use casper_types::{system::CallStackElement, ApiError};
+
+    // The last element is the current contract, so it always has a caller
+    match runtime::get_call_stack().iter().rev().nth(1) {
+        Some(
+            CallStackElement::Session { account_hash }
+            | CallStackElement::StoredSession { account_hash, .. },
+        ) => Key::Account(*account_hash),
+        Some(CallStackElement::StoredContract { contract_package_hash, .. }) => {
+            Key::Hash(contract_package_hash.value())
+        }
+        None => runtime::revert(ApiError::Unhandled),
+    }
+}
+
+/// Address of a `Key::Hash`, as stored for contract and package hashes
+pub fn hash_addr(key: Key) -> Option<[u8; 32]> {
+    #[cfg(feature = "casper-2x")]
+    return key.into_hash_addr();
+    #[cfg(feature = "casper-1x")]
+    return key.into_hash();
+}
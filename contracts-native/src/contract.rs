@@ -0,0 +1,815 @@
+//! Escrow contract code shared by the installer and the upgrade session
+//!
+//! A single installed contract holds many escrows. Per-escrow fields live in
+//! dictionaries keyed by escrow id; each escrow has its own purse.
+
+use alloc::{format, string::{String, ToString}, vec, vec::Vec};
+use casper_contract::{
+    contract_api::{runtime, storage, system},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    api_error::ApiError,
+    contracts::NamedKeys,
+    CLType, CLValue, EntryPoints, Key, Parameter, URef, U512,
+    account::AccountHash, CLTyped,
+    bytesrepr::{self, FromBytes, ToBytes},
+};
+
+use escrow_core::{entry_points, Action, Error, Role, State};
+
+use crate::compat::{self, entry_point};
+use crate::events::{
+    self,
+    DisputeRaised, DisputeResolved, EscrowAccepted, EscrowCancelled, EscrowCreated,
+    FundsDeposited, FundsReleased, FundsSwept,
+};
+
+// Contract keys, stored under the installing account
+pub const CONTRACT_PACKAGE_NAME: &str = "escrow_package";
+pub const CONTRACT_ACCESS_UREF: &str = "escrow_access_uref";
+pub const CONTRACT_KEY: &str = "escrow_contract";
+pub const CONTRACT_VERSION_KEY: &str = "escrow_contract_version";
+
+// Storage layout of the contract's named keys. Contracts installed by the
+// first release have no such key and are treated as layout 0: a single escrow
+// in plain named keys, with its funds in `escrow_purse`.
+const LAYOUT_VERSION_KEY: &str = "layout_version";
+const LAYOUT_VERSION: u32 = 1;
+
+// Number of escrows created so far; also the next escrow id
+const ESCROW_COUNT_KEY: &str = "escrow_count";
+
+// Per-escrow purses are named keys `escrow_purse_<id>`. A URef read back from
+// a dictionary is not in the contract's access rights, so purses can't live there.
+const PURSE_KEY_PREFIX: &str = "escrow_purse_";
+
+// Purse of the single escrow held by a first-release contract
+const BASELINE_PURSE_KEY: &str = "escrow_purse";
+
+// Contract parties receive payouts into a purse they register, stored under
+// named keys `payout_purse_<id>_<issuer|payer>` with add access only.
+// Accounts are paid into their main purse.
+const PAYOUT_PURSE_KEY_PREFIX: &str = "payout_purse_";
+
+// Dictionaries, keyed by escrow id
+const STATE_KEY: &str = "state";
+const ISSUER_KEY: &str = "issuer";
+const PAYER_KEY: &str = "payer";
+const ARBITER_KEY: &str = "arbiter";
+const DISPUTE_KEY: &str = "dispute";
+const DUE_DATE_KEY: &str = "due_date";
+const AMOUNT_KEY: &str = "amount";
+const DESCRIPTION_KEY: &str = "description";
+const BALANCE_KEY: &str = "balance";
+const CREATED_AT_KEY: &str = "created_at";
+const ACCEPTED_AT_KEY: &str = "accepted_at";
+const FUNDED_AT_KEY: &str = "funded_at";
+const RELEASED_AT_KEY: &str = "released_at";
+const SWEEP_PROPOSAL_KEY: &str = "sweep_proposal";
+const REFUNDED_KEY: &str = "refunded";
+
+const DICTIONARIES: [&str; 15] = [
+    STATE_KEY,
+    ISSUER_KEY,
+    PAYER_KEY,
+    ARBITER_KEY,
+    DISPUTE_KEY,
+    DUE_DATE_KEY,
+    AMOUNT_KEY,
+    DESCRIPTION_KEY,
+    BALANCE_KEY,
+    CREATED_AT_KEY,
+    ACCEPTED_AT_KEY,
+    FUNDED_AT_KEY,
+    RELEASED_AT_KEY,
+    SWEEP_PROPOSAL_KEY,
+    REFUNDED_KEY,
+];
+
+// Argument names
+const ARG_ESCROW_ID: &str = "escrow_id";
+
+// Native escrows are always denominated in motes
+const CURRENCY_SYMBOL: &str = "CSPR";
+const CURRENCY_DECIMALS: u8 = 9;
+
+// Errors are reported as `ApiError::User` with the shared escrow-core codes
+fn api_error(error: Error) -> ApiError {
+    ApiError::User(error.code())
+}
+
+fn revert(error: Error) -> ! {
+    runtime::revert(api_error(error))
+}
+
+// Helper to get stored value
+fn get_uref(name: &str) -> URef {
+    runtime::get_key(name)
+        .unwrap_or_revert_with(ApiError::MissingKey)
+        .into_uref()
+        .unwrap_or_revert_with(ApiError::UnexpectedKeyVariant)
+}
+
+fn get_key<T: FromBytes + CLTyped>(name: &str) -> T {
+    let uref = get_uref(name);
+    storage::read(uref)
+        .unwrap_or_revert()
+        .unwrap_or_revert()
+}
+
+fn set_key<T: ToBytes + CLTyped>(name: &str, value: T) {
+    let uref = get_uref(name);
+    storage::write(uref, value);
+}
+
+// Helpers to read and write one escrow's field in its dictionary
+fn get_field<T: FromBytes + CLTyped>(dictionary: &str, escrow_id: u64) -> T {
+    storage::dictionary_get(get_uref(dictionary), &escrow_id.to_string())
+        .unwrap_or_revert()
+        .unwrap_or_revert_with(api_error(Error::NotFound))
+}
+
+// Read a field that is only written once needed
+fn try_get_field<T: FromBytes + CLTyped>(dictionary: &str, escrow_id: u64) -> Option<T> {
+    storage::dictionary_get(get_uref(dictionary), &escrow_id.to_string()).unwrap_or_revert()
+}
+
+fn set_field<T: ToBytes + CLTyped>(dictionary: &str, escrow_id: u64, value: T) {
+    storage::dictionary_put(get_uref(dictionary), &escrow_id.to_string(), value);
+}
+
+fn purse_key(escrow_id: u64) -> String {
+    format!("{}{}", PURSE_KEY_PREFIX, escrow_id)
+}
+
+fn purse_balance(purse: URef) -> U512 {
+    system::get_purse_balance(purse).unwrap_or_revert()
+}
+
+fn payout_purse_key(escrow_id: u64, role: &str) -> String {
+    format!("{}{}_{}", PAYOUT_PURSE_KEY_PREFIX, escrow_id, role)
+}
+
+/// Record the payout purse a contract party passes as `arg`.
+/// Accounts need none; for a contract the purse must accept deposits.
+fn register_payout_purse(escrow_id: u64, party: Key, role: &str, arg: &str) {
+    if let Key::Account(_) = party {
+        return;
+    }
+    let purse = compat::try_get_named_arg::<URef>(arg)
+        .filter(|purse| purse.is_addable())
+        .unwrap_or_else(|| revert(Error::PayoutPurseRequired));
+    runtime::put_key(&payout_purse_key(escrow_id, role), purse.into_add().into());
+}
+
+/// Registered payout purse of `role`, without access rights, for display
+fn payout_purse(escrow_id: u64, role: &str) -> Option<URef> {
+    runtime::get_key(&payout_purse_key(escrow_id, role))
+        .and_then(Key::into_uref)
+        .map(|purse| purse.remove_access_rights())
+}
+
+/// Whether `party` can be paid: accounts always, contracts once they have
+/// registered a payout purse
+fn can_pay(escrow_id: u64, party: Key, role: &str) -> bool {
+    matches!(party, Key::Account(_)) || runtime::has_key(&payout_purse_key(escrow_id, role))
+}
+
+/// Pay `amount` from the escrow purse to the party holding `role`
+fn pay(escrow_id: u64, party: Key, role: &str, amount: U512) {
+    let escrow_purse = get_uref(&purse_key(escrow_id));
+    let result = match party {
+        Key::Account(account) => {
+            system::transfer_from_purse_to_account(escrow_purse, account, amount, None).map(|_| ())
+        }
+        _ => {
+            let payout_purse = runtime::get_key(&payout_purse_key(escrow_id, role))
+                .and_then(Key::into_uref)
+                .unwrap_or_else(|| revert(Error::PayoutPurseRequired));
+            system::transfer_from_purse_to_purse(escrow_purse, payout_purse, amount, None)
+        }
+    };
+    result.unwrap_or_revert_with(api_error(Error::TransferFailed));
+}
+
+// Escrow id argument of the current call
+fn escrow_id_arg() -> u64 {
+    runtime::get_named_arg(ARG_ESCROW_ID)
+}
+
+// Current block time in milliseconds
+fn now() -> u64 {
+    runtime::get_blocktime().into()
+}
+
+/// Check `action` by the caller against the shared transition table,
+/// returning the state it leads to
+fn advance(escrow_id: u64, action: Action) -> State {
+    let state = State::from_u8(get_field(STATE_KEY, escrow_id))
+        .unwrap_or_revert_with(ApiError::Formatting);
+    let issuer: Key = get_field(ISSUER_KEY, escrow_id);
+    let payer: Key = get_field(PAYER_KEY, escrow_id);
+    let arbiter: Option<Key> = get_field(ARBITER_KEY, escrow_id);
+    let role = Role::of(&compat::immediate_caller(), &issuer, &payer, arbiter.as_ref());
+    escrow_core::transition(state, action, role).unwrap_or_else(|error| revert(error))
+}
+
+/// Bring storage written by an older contract version up to the current layout.
+/// Runs at the start of every entry point; after the first call on a new
+/// version it only reads the layout version.
+fn migrate_layout() {
+    let layout: u32 = match runtime::get_key(LAYOUT_VERSION_KEY) {
+        Some(key) => storage::read(key.into_uref().unwrap_or_revert())
+            .unwrap_or_revert()
+            .unwrap_or_revert(),
+        None => 0,
+    };
+    if layout >= LAYOUT_VERSION {
+        return;
+    }
+
+    // The first release's named keys share names with the dictionaries, so
+    // they are taken out before the dictionaries are created
+    let escrow = take_baseline_escrow();
+    for name in DICTIONARIES {
+        storage::new_dictionary(name).unwrap_or_revert();
+    }
+    events::migrate();
+    store_baseline_escrow(escrow);
+    runtime::put_key(LAYOUT_VERSION_KEY, storage::new_uref(LAYOUT_VERSION).into());
+}
+
+/// The single escrow of a first-release contract
+struct BaselineEscrow {
+    state: u8,
+    issuer: AccountHash,
+    payer: AccountHash,
+    amount: U512,
+    description: String,
+    balance: U512,
+}
+
+/// Read the first release's escrow out of its named keys and remove them.
+/// Its purse becomes the purse of escrow 0.
+fn take_baseline_escrow() -> BaselineEscrow {
+    let escrow = BaselineEscrow {
+        state: get_key(STATE_KEY),
+        issuer: get_key(ISSUER_KEY),
+        payer: get_key(PAYER_KEY),
+        amount: get_key(AMOUNT_KEY),
+        description: get_key(DESCRIPTION_KEY),
+        balance: get_key(BALANCE_KEY),
+    };
+    for name in [STATE_KEY, ISSUER_KEY, PAYER_KEY, AMOUNT_KEY, DESCRIPTION_KEY, BALANCE_KEY] {
+        runtime::remove_key(name);
+    }
+
+    let purse = get_uref(BASELINE_PURSE_KEY);
+    runtime::remove_key(BASELINE_PURSE_KEY);
+    runtime::put_key(&purse_key(0), purse.into());
+    escrow
+}
+
+/// Write the first release's escrow as escrow 0 of the current layout.
+/// Its states share their codes with `State`; it kept no timestamps.
+fn store_baseline_escrow(escrow: BaselineEscrow) {
+    let BaselineEscrow { state, issuer, payer, amount, description, balance } = escrow;
+    set_field(STATE_KEY, 0, state);
+    set_field(ISSUER_KEY, 0, Key::from(issuer));
+    set_field(PAYER_KEY, 0, Key::from(payer));
+    set_field(ARBITER_KEY, 0, None::<Key>);
+    set_field(DISPUTE_KEY, 0, None::<Dispute>);
+    set_field(AMOUNT_KEY, 0, amount);
+    set_field(DESCRIPTION_KEY, 0, description);
+    set_field(DUE_DATE_KEY, 0, None::<u64>);
+    set_field(BALANCE_KEY, 0, balance);
+    set_field(CREATED_AT_KEY, 0, 0u64);
+    set_field(ACCEPTED_AT_KEY, 0, None::<u64>);
+    set_field(FUNDED_AT_KEY, 0, None::<u64>);
+    set_field(RELEASED_AT_KEY, 0, None::<u64>);
+    set_field(SWEEP_PROPOSAL_KEY, 0, None::<SweepProposal>);
+    set_field(REFUNDED_KEY, 0, false);
+    runtime::put_key(ESCROW_COUNT_KEY, storage::new_uref(1u64).into());
+}
+
+/// Dispute details: raised by, reason, block time
+type Dispute = (Key, String, u64);
+
+/// Pending sweep: proposed by, recipient
+type SweepProposal = (Key, Key);
+
+/// Complete escrow snapshot returned by `get_summary`.
+/// Serialized field by field in declaration order, exposed as `CLType::Any`.
+/// The order is `escrow_core::entry_points::NATIVE_SUMMARY_LAYOUT`, which the
+/// generated bindings publish and termina-client decodes.
+struct EscrowSummary {
+    escrow_id: u64,
+    state: u8,
+    issuer: Key,
+    payer: Key,
+    arbiter: Option<Key>,
+    amount: U512,
+    balance: U512,
+    description: String,
+    due_date: Option<u64>,
+    created_at: u64,
+    accepted_at: Option<u64>,
+    funded_at: Option<u64>,
+    released_at: Option<u64>,
+    dispute: Option<Dispute>,
+    refunded: bool,
+    issuer_purse: Option<URef>,
+    payer_purse: Option<URef>,
+}
+
+impl ToBytes for EscrowSummary {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        buffer.extend(self.escrow_id.to_bytes()?);
+        buffer.extend(self.state.to_bytes()?);
+        buffer.extend(self.issuer.to_bytes()?);
+        buffer.extend(self.payer.to_bytes()?);
+        buffer.extend(self.arbiter.to_bytes()?);
+        buffer.extend(self.amount.to_bytes()?);
+        buffer.extend(self.balance.to_bytes()?);
+        buffer.extend(self.description.to_bytes()?);
+        buffer.extend(self.due_date.to_bytes()?);
+        buffer.extend(self.created_at.to_bytes()?);
+        buffer.extend(self.accepted_at.to_bytes()?);
+        buffer.extend(self.funded_at.to_bytes()?);
+        buffer.extend(self.released_at.to_bytes()?);
+        buffer.extend(self.dispute.to_bytes()?);
+        buffer.extend(self.refunded.to_bytes()?);
+        buffer.extend(self.issuer_purse.to_bytes()?);
+        buffer.extend(self.payer_purse.to_bytes()?);
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.escrow_id.serialized_length()
+            + self.state.serialized_length()
+            + self.issuer.serialized_length()
+            + self.payer.serialized_length()
+            + self.arbiter.serialized_length()
+            + self.amount.serialized_length()
+            + self.balance.serialized_length()
+            + self.description.serialized_length()
+            + self.due_date.serialized_length()
+            + self.created_at.serialized_length()
+            + self.accepted_at.serialized_length()
+            + self.funded_at.serialized_length()
+            + self.released_at.serialized_length()
+            + self.dispute.serialized_length()
+            + self.refunded.serialized_length()
+            + self.issuer_purse.serialized_length()
+            + self.payer_purse.serialized_length()
+    }
+}
+
+impl CLTyped for EscrowSummary {
+    fn cl_type() -> CLType {
+        CLType::Any
+    }
+}
+
+/// Create a new escrow and return its id.
+/// Parties are accounts or contract packages, as `Key`.
+/// Optional args: `issuer` (defaults to the immediate caller), `arbiter`,
+/// `due_date`, and `issuer_purse`, required when the issuer is a contract
+/// and rejected for an account. Any caller may name an account as issuer,
+/// which is paid into its main purse. A contract issuer decides where its
+/// payouts go, so only the issuer itself may create such an escrow.
+#[no_mangle]
+pub extern "C" fn create_escrow() {
+    migrate_layout();
+    let caller = compat::immediate_caller();
+    let issuer: Key = compat::try_get_named_arg("issuer").unwrap_or(caller);
+    let payer: Key = runtime::get_named_arg("payer");
+    let arbiter: Option<Key> = compat::try_get_named_arg("arbiter");
+    let amount: U512 = runtime::get_named_arg("amount");
+    let description: String = runtime::get_named_arg("description");
+    // Block time in milliseconds after which payment is due
+    let due_date: Option<u64> = compat::try_get_named_arg("due_date");
+
+    // Validate inputs before creating any state
+    if [Some(&issuer), Some(&payer), arbiter.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|party| !matches!(party, Key::Account(_) | Key::Hash(_)))
+    {
+        revert(Error::InvalidParty);
+    }
+    let issuer_purse: Option<URef> = compat::try_get_named_arg("issuer_purse");
+    if matches!(issuer, Key::Account(_)) && issuer_purse.is_some() {
+        revert(Error::UnexpectedPayoutPurse);
+    }
+    if !matches!(issuer, Key::Account(_)) && issuer != caller {
+        revert(Error::Unauthorized);
+    }
+    if amount.is_zero() {
+        revert(Error::ZeroAmount);
+    }
+    escrow_core::validate_description(&description)
+        .and_then(|()| escrow_core::validate_parties(&issuer, &payer, arbiter.as_ref()))
+        .and_then(|()| escrow_core::validate_due_date(due_date, now()))
+        .unwrap_or_else(|error| revert(error));
+
+    let escrow_id: u64 = get_key(ESCROW_COUNT_KEY);
+    set_key(ESCROW_COUNT_KEY, escrow_id + 1);
+
+    // Create escrow purse to hold funds
+    runtime::put_key(&purse_key(escrow_id), system::create_purse().into());
+    register_payout_purse(escrow_id, issuer, ISSUER_KEY, "issuer_purse");
+
+    set_field(STATE_KEY, escrow_id, State::Draft as u8);
+    set_field(ISSUER_KEY, escrow_id, issuer);
+    set_field(PAYER_KEY, escrow_id, payer);
+    set_field(ARBITER_KEY, escrow_id, arbiter);
+    set_field(DISPUTE_KEY, escrow_id, None::<Dispute>);
+    set_field(AMOUNT_KEY, escrow_id, amount);
+    set_field(DESCRIPTION_KEY, escrow_id, description);
+    set_field(DUE_DATE_KEY, escrow_id, due_date);
+    set_field(BALANCE_KEY, escrow_id, U512::zero());
+    set_field(CREATED_AT_KEY, escrow_id, now());
+    set_field(ACCEPTED_AT_KEY, escrow_id, None::<u64>);
+    set_field(FUNDED_AT_KEY, escrow_id, None::<u64>);
+    set_field(RELEASED_AT_KEY, escrow_id, None::<u64>);
+    set_field(REFUNDED_KEY, escrow_id, false);
+
+    casper_event_standard::emit(EscrowCreated {
+        escrow_id: escrow_id.to_string(),
+        issuer,
+        payer,
+        amount,
+        currency_symbol: CURRENCY_SYMBOL.to_string(),
+        currency_decimals: CURRENCY_DECIMALS,
+    });
+
+    runtime::ret(CLValue::from_t(escrow_id).unwrap_or_revert());
+}
+
+/// Accept the escrow terms (called by payer).
+/// A contract payer passes the `payer_purse` it receives refunds into.
+#[no_mangle]
+pub extern "C" fn accept() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let next = advance(escrow_id, Action::Accept);
+    let payer: Key = get_field(PAYER_KEY, escrow_id);
+    register_payout_purse(escrow_id, payer, PAYER_KEY, "payer_purse");
+
+    set_field(STATE_KEY, escrow_id, next as u8);
+    set_field(ACCEPTED_AT_KEY, escrow_id, Some(now()));
+
+    casper_event_standard::emit(EscrowAccepted {
+        escrow_id: escrow_id.to_string(),
+        payer,
+    });
+}
+
+/// Fund the escrow (called by payer) from the `source` purse.
+/// Moves `amount`, or the whole `source` balance if omitted. The recorded
+/// balance is what the escrow purse actually received.
+#[no_mangle]
+pub extern "C" fn fund() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let next = advance(escrow_id, Action::Fund);
+    let payer: Key = get_field(PAYER_KEY, escrow_id);
+
+    let required_amount: U512 = get_field(AMOUNT_KEY, escrow_id);
+    let escrow_purse: URef = get_uref(&purse_key(escrow_id));
+    let source_purse: URef = runtime::get_named_arg("source");
+    let amount: U512 = compat::try_get_named_arg("amount")
+        .unwrap_or_else(|| purse_balance(source_purse));
+
+    // Stray funds already in the purse don't count towards the deposit
+    let before = purse_balance(escrow_purse);
+    system::transfer_from_purse_to_purse(source_purse, escrow_purse, amount, None)
+        .unwrap_or_revert_with(api_error(Error::TransferFailed));
+    let amount = purse_balance(escrow_purse).saturating_sub(before);
+
+    escrow_core::check_funding(&required_amount, &amount).unwrap_or_else(|error| revert(error));
+
+    set_field(BALANCE_KEY, escrow_id, amount);
+    set_field(STATE_KEY, escrow_id, next as u8);
+    set_field(FUNDED_AT_KEY, escrow_id, Some(now()));
+
+    casper_event_standard::emit(FundsDeposited {
+        escrow_id: escrow_id.to_string(),
+        payer,
+        amount,
+    });
+}
+
+/// Release funds to issuer (called by payer).
+/// Native escrows have no release conditions beyond the payer's approval.
+/// The shared table also lets the issuer release, so other callers are
+/// refused here with `Unauthorized`, as by the first release.
+#[no_mangle]
+pub extern "C" fn release() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let next = advance(escrow_id, Action::Release);
+
+    let payer: Key = get_field(PAYER_KEY, escrow_id);
+    if compat::immediate_caller() != payer {
+        revert(Error::Unauthorized);
+    }
+
+    let issuer: Key = get_field(ISSUER_KEY, escrow_id);
+    let balance: U512 = get_field(BALANCE_KEY, escrow_id);
+    pay(escrow_id, issuer, ISSUER_KEY, balance);
+
+    set_field(BALANCE_KEY, escrow_id, U512::zero());
+    set_field(STATE_KEY, escrow_id, next as u8);
+    set_field(RELEASED_AT_KEY, escrow_id, Some(now()));
+
+    casper_event_standard::emit(FundsReleased {
+        escrow_id: escrow_id.to_string(),
+        receiver: issuer,
+        amount: balance,
+    });
+}
+
+/// Cancel escrow (only in Draft or Accepted state).
+/// Anything in the escrow purse goes back to the payer, so a cancelled
+/// escrow never holds funds. A contract payer that cancels before accepting
+/// has no payout purse yet; its funds stay behind for a sweep.
+#[no_mangle]
+pub extern "C" fn cancel() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let next = advance(escrow_id, Action::Cancel);
+    let caller = compat::immediate_caller();
+
+    let payer: Key = get_field(PAYER_KEY, escrow_id);
+    let refund = if can_pay(escrow_id, payer, PAYER_KEY) {
+        purse_balance(get_uref(&purse_key(escrow_id)))
+    } else {
+        U512::zero()
+    };
+    if !refund.is_zero() {
+        pay(escrow_id, payer, PAYER_KEY, refund);
+        set_field(REFUNDED_KEY, escrow_id, true);
+    }
+
+    set_field(BALANCE_KEY, escrow_id, U512::zero());
+    set_field(STATE_KEY, escrow_id, next as u8);
+
+    casper_event_standard::emit(EscrowCancelled {
+        escrow_id: escrow_id.to_string(),
+        cancelled_by: caller,
+    });
+    if !refund.is_zero() {
+        casper_event_standard::emit(FundsReleased {
+            escrow_id: escrow_id.to_string(),
+            receiver: payer,
+            amount: refund,
+        });
+    }
+}
+
+/// Raise a dispute (only when funded, by issuer or payer)
+#[no_mangle]
+pub extern "C" fn dispute() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let next = advance(escrow_id, Action::Dispute);
+    let caller = compat::immediate_caller();
+
+    let reason: String = runtime::get_named_arg("reason");
+
+    set_field(DISPUTE_KEY, escrow_id, Some((caller, reason.clone(), now())));
+    set_field(STATE_KEY, escrow_id, next as u8);
+
+    casper_event_standard::emit(DisputeRaised {
+        escrow_id: escrow_id.to_string(),
+        raised_by: caller,
+        reason,
+    });
+}
+
+/// Resolve a dispute (only by arbiter), paying out to the winning party
+#[no_mangle]
+pub extern "C" fn resolve_dispute() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let next = advance(escrow_id, Action::ResolveDispute);
+    let caller = compat::immediate_caller();
+
+    let release_to_receiver: bool = runtime::get_named_arg("release_to_receiver");
+    let role = if release_to_receiver { ISSUER_KEY } else { PAYER_KEY };
+    let receiver: Key = get_field(role, escrow_id);
+    let balance: U512 = get_field(BALANCE_KEY, escrow_id);
+    pay(escrow_id, receiver, role, balance);
+
+    set_field(BALANCE_KEY, escrow_id, U512::zero());
+    set_field(STATE_KEY, escrow_id, next as u8);
+    set_field(RELEASED_AT_KEY, escrow_id, Some(now()));
+    set_field(REFUNDED_KEY, escrow_id, !release_to_receiver);
+
+    casper_event_standard::emit(DisputeResolved {
+        escrow_id: escrow_id.to_string(),
+        resolved_by: caller,
+        release_to_receiver,
+    });
+    casper_event_standard::emit(FundsReleased {
+        escrow_id: escrow_id.to_string(),
+        receiver,
+        amount: balance,
+    });
+}
+
+/// Return escrow purse funds beyond the recorded balance to `recipient`.
+/// Only the issuer and the payer may sweep, and both must name the same
+/// recipient: the first call records a proposal, a matching call by the
+/// other party carries it out. Any account can receive a sweep; a contract
+/// must be a party and is paid into its payout purse.
+#[no_mangle]
+pub extern "C" fn sweep() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let recipient: Key = runtime::get_named_arg("recipient");
+
+    let caller = compat::immediate_caller();
+    let issuer: Key = get_field(ISSUER_KEY, escrow_id);
+    let payer: Key = get_field(PAYER_KEY, escrow_id);
+    if !matches!(Role::of(&caller, &issuer, &payer, None), Role::Issuer | Role::Payer) {
+        revert(Error::Unauthorized);
+    }
+    // A contract is paid into the payout purse of the role it holds;
+    // accounts are paid directly, whatever the role
+    let role = match recipient {
+        Key::Account(_) => ISSUER_KEY,
+        Key::Hash(_) if recipient == issuer => ISSUER_KEY,
+        Key::Hash(_) if recipient == payer => PAYER_KEY,
+        Key::Hash(_) => revert(Error::PayoutPurseRequired),
+        _ => revert(Error::InvalidParty),
+    };
+
+    let balance: U512 = get_field(BALANCE_KEY, escrow_id);
+    let escrow_purse: URef = get_uref(&purse_key(escrow_id));
+    let stray = purse_balance(escrow_purse).saturating_sub(balance);
+    if stray.is_zero() {
+        revert(Error::NothingToSweep);
+    }
+
+    let proposal: Option<SweepProposal> = try_get_field(SWEEP_PROPOSAL_KEY, escrow_id).flatten();
+    match proposal {
+        Some((proposed_by, proposed_to)) if proposed_by != caller && proposed_to == recipient => {
+            pay(escrow_id, recipient, role, stray);
+            set_field(SWEEP_PROPOSAL_KEY, escrow_id, None::<SweepProposal>);
+
+            casper_event_standard::emit(FundsSwept {
+                escrow_id: escrow_id.to_string(),
+                recipient,
+                amount: stray,
+            });
+        }
+        _ => set_field(SWEEP_PROPOSAL_KEY, escrow_id, Some((caller, recipient))),
+    }
+}
+
+/// Get current state (view function)
+#[no_mangle]
+pub extern "C" fn get_state() {
+    migrate_layout();
+    let state: u8 = get_field(STATE_KEY, escrow_id_arg());
+    runtime::ret(CLValue::from_t(state).unwrap_or_revert());
+}
+
+/// Get current balance (view function)
+#[no_mangle]
+pub extern "C" fn get_balance() {
+    migrate_layout();
+    let balance: U512 = get_field(BALANCE_KEY, escrow_id_arg());
+    runtime::ret(CLValue::from_t(balance).unwrap_or_revert());
+}
+
+/// Get the recorded balance and the actual escrow purse balance (view function).
+/// The purse holds more than recorded when funds were sent to it directly.
+#[no_mangle]
+pub extern "C" fn get_purse_balances() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let recorded: U512 = get_field(BALANCE_KEY, escrow_id);
+    let actual = purse_balance(get_uref(&purse_key(escrow_id)));
+    runtime::ret(CLValue::from_t((recorded, actual)).unwrap_or_revert());
+}
+
+/// Get a complete escrow snapshot (view function)
+#[no_mangle]
+pub extern "C" fn get_summary() {
+    migrate_layout();
+    let escrow_id = escrow_id_arg();
+    let summary = EscrowSummary {
+        escrow_id,
+        state: get_field(STATE_KEY, escrow_id),
+        issuer: get_field(ISSUER_KEY, escrow_id),
+        payer: get_field(PAYER_KEY, escrow_id),
+        arbiter: get_field(ARBITER_KEY, escrow_id),
+        amount: get_field(AMOUNT_KEY, escrow_id),
+        balance: get_field(BALANCE_KEY, escrow_id),
+        description: get_field(DESCRIPTION_KEY, escrow_id),
+        due_date: get_field(DUE_DATE_KEY, escrow_id),
+        created_at: get_field(CREATED_AT_KEY, escrow_id),
+        accepted_at: get_field(ACCEPTED_AT_KEY, escrow_id),
+        funded_at: get_field(FUNDED_AT_KEY, escrow_id),
+        released_at: get_field(RELEASED_AT_KEY, escrow_id),
+        dispute: get_field(DISPUTE_KEY, escrow_id),
+        refunded: get_field(REFUNDED_KEY, escrow_id),
+        issuer_purse: payout_purse(escrow_id, ISSUER_KEY),
+        payer_purse: payout_purse(escrow_id, PAYER_KEY),
+    };
+    runtime::ret(CLValue::from_t(summary).unwrap_or_revert());
+}
+
+/// Get the number of escrows created so far (view function)
+#[no_mangle]
+pub extern "C" fn get_escrow_count() {
+    migrate_layout();
+    let count: u64 = get_key(ESCROW_COUNT_KEY);
+    runtime::ret(CLValue::from_t(count).unwrap_or_revert());
+}
+
+fn escrow_id_param() -> Parameter {
+    Parameter::new(ARG_ESCROW_ID, CLType::U64)
+}
+
+/// Entry points of the current contract version
+pub fn entry_points() -> EntryPoints {
+    let mut points = EntryPoints::new();
+
+    points.add_entry_point(entry_point(
+        entry_points::CREATE_ESCROW,
+        vec![
+            Parameter::new("payer", Key::cl_type()),
+            Parameter::new("amount", CLType::U512),
+            Parameter::new("description", CLType::String),
+        ],
+        CLType::U64,
+    ));
+
+    points.add_entry_point(entry_point(entry_points::ACCEPT, vec![escrow_id_param()], CLType::Unit));
+
+    points.add_entry_point(entry_point(
+        entry_points::FUND,
+        vec![escrow_id_param(), Parameter::new("source", CLType::URef)],
+        CLType::Unit,
+    ));
+
+    points.add_entry_point(entry_point(entry_points::RELEASE, vec![escrow_id_param()], CLType::Unit));
+
+    points.add_entry_point(entry_point(entry_points::CANCEL, vec![escrow_id_param()], CLType::Unit));
+
+    points.add_entry_point(entry_point(
+        entry_points::DISPUTE,
+        vec![escrow_id_param(), Parameter::new("reason", CLType::String)],
+        CLType::Unit,
+    ));
+
+    points.add_entry_point(entry_point(
+        entry_points::RESOLVE_DISPUTE,
+        vec![escrow_id_param(), Parameter::new("release_to_receiver", CLType::Bool)],
+        CLType::Unit,
+    ));
+
+    points.add_entry_point(entry_point(
+        entry_points::SWEEP,
+        vec![escrow_id_param(), Parameter::new("recipient", Key::cl_type())],
+        CLType::Unit,
+    ));
+
+    points.add_entry_point(entry_point(entry_points::GET_STATE, vec![escrow_id_param()], CLType::U8));
+
+    points.add_entry_point(entry_point(entry_points::GET_BALANCE, vec![escrow_id_param()], CLType::U512));
+
+    points.add_entry_point(entry_point(
+        entry_points::GET_PURSE_BALANCES,
+        vec![escrow_id_param()],
+        <(U512, U512)>::cl_type(),
+    ));
+
+    points.add_entry_point(entry_point(entry_points::GET_SUMMARY, vec![escrow_id_param()], CLType::Any));
+
+    points.add_entry_point(entry_point(entry_points::GET_ESCROW_COUNT, Vec::new(), CLType::U64));
+
+    points
+}
+
+/// Named keys of a freshly installed contract, at the current layout version.
+/// Dictionaries are created under the installing account and moved here.
+pub fn named_keys() -> NamedKeys {
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(ESCROW_COUNT_KEY.to_string(), storage::new_uref(0u64).into());
+    named_keys.insert(LAYOUT_VERSION_KEY.to_string(), storage::new_uref(LAYOUT_VERSION).into());
+    for name in DICTIONARIES {
+        let dictionary = storage::new_dictionary(name).unwrap_or_revert();
+        runtime::remove_key(name);
+        named_keys.insert(name.to_string(), dictionary.into());
+    }
+    events::init(&mut named_keys);
+    named_keys
+}
@@ -0,0 +1,75 @@
+//! Session code for creating and funding a native escrow in one deploy
+//! Creates an escrow with the caller as payer, accepts it on the caller's
+//! behalf and funds it from the caller's main purse. Any failed step reverts
+//! the whole deploy, so no escrow is left created but unfunded.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+#[cfg(feature = "casper-1x")]
+extern crate casper_contract_1x as casper_contract;
+#[cfg(feature = "casper-1x")]
+extern crate casper_types_1x as casper_types;
+
+// Only `try_get_named_arg` is used here
+#[allow(dead_code)]
+mod compat;
+
+use alloc::string::String;
+use casper_contract::{
+    contract_api::{account, runtime, system},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{contracts::ContractHash, ApiError, Key, RuntimeArgs, U512};
+use escrow_core::{entry_points, Error};
+
+const ARG_ESCROW_CONTRACT: &str = "escrow_contract";
+const ARG_ESCROW_ID: &str = "escrow_id";
+const ARG_AMOUNT: &str = "amount";
+
+// A failed transfer reverts with the escrow-core code clients already decode
+fn api_error(error: Error) -> ApiError {
+    ApiError::User(error.code())
+}
+
+/// Args: `escrow_contract`, `issuer` (Key), `amount` (U512), `description`,
+/// and the optional `arbiter` and `due_date` of `create_escrow`.
+/// A contract issuer can't be used, as its payout purse isn't the caller's to pass.
+#[no_mangle]
+pub extern "C" fn call() {
+    let escrow_contract: ContractHash = runtime::get_named_arg(ARG_ESCROW_CONTRACT);
+    let issuer: Key = runtime::get_named_arg("issuer");
+    let amount: U512 = runtime::get_named_arg(ARG_AMOUNT);
+    let description: String = runtime::get_named_arg("description");
+
+    let mut args = RuntimeArgs::new();
+    args.insert("issuer", issuer).unwrap_or_revert();
+    args.insert("payer", Key::Account(runtime::get_caller())).unwrap_or_revert();
+    args.insert(ARG_AMOUNT, amount).unwrap_or_revert();
+    args.insert("description", description).unwrap_or_revert();
+    if let Some(arbiter) = compat::try_get_named_arg::<Key>("arbiter") {
+        args.insert("arbiter", arbiter).unwrap_or_revert();
+    }
+    if let Some(due_date) = compat::try_get_named_arg::<u64>("due_date") {
+        args.insert("due_date", due_date).unwrap_or_revert();
+    }
+    let escrow_id: u64 =
+        runtime::call_contract(escrow_contract, entry_points::CREATE_ESCROW, args);
+
+    // This session runs as the payer, so the escrow sees the payer accepting
+    let mut args = RuntimeArgs::new();
+    args.insert(ARG_ESCROW_ID, escrow_id).unwrap_or_revert();
+    runtime::call_contract::<()>(escrow_contract, entry_points::ACCEPT, args);
+
+    // Only the temporary purse is exposed to the contract, never the main purse
+    let source = system::create_purse();
+    system::transfer_from_purse_to_purse(account::get_main_purse(), source, amount, None)
+        .unwrap_or_revert_with(api_error(Error::TransferFailed));
+
+    let mut args = RuntimeArgs::new();
+    args.insert(ARG_ESCROW_ID, escrow_id).unwrap_or_revert();
+    args.insert(ARG_AMOUNT, amount).unwrap_or_revert();
+    args.insert("source", source).unwrap_or_revert();
+    runtime::call_contract::<()>(escrow_contract, entry_points::FUND, args);
+}
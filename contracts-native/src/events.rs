@@ -0,0 +1,116 @@
+//! Casper Event Standard (CES) events emitted by the native escrow
+//!
+//! Names and fields mirror `contracts/src/events.rs`, so one indexer can
+//! consume both contracts; `FundsSwept` is native only. Parties are emitted
+//! as `Key`, matching the serialization of Odra's `Address`.
+
+use alloc::string::{String, ToString};
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_event_standard::{Event, Schemas, CES_VERSION, CES_VERSION_KEY, EVENTS_DICT, EVENTS_LENGTH, EVENTS_SCHEMA};
+use casper_types::{contracts::NamedKeys, Key, U512};
+
+/// Emitted when a new escrow is created
+#[derive(Event)]
+pub struct EscrowCreated {
+    pub escrow_id: String,
+    pub issuer: Key,
+    pub payer: Key,
+    pub amount: U512,
+    pub currency_symbol: String,
+    pub currency_decimals: u8,
+}
+
+/// Emitted when an escrow is accepted by the payer
+#[derive(Event)]
+pub struct EscrowAccepted {
+    pub escrow_id: String,
+    pub payer: Key,
+}
+
+/// Emitted when funds are deposited into escrow
+#[derive(Event)]
+pub struct FundsDeposited {
+    pub escrow_id: String,
+    pub payer: Key,
+    pub amount: U512,
+}
+
+/// Emitted when funds are released to the receiver
+#[derive(Event)]
+pub struct FundsReleased {
+    pub escrow_id: String,
+    pub receiver: Key,
+    pub amount: U512,
+}
+
+/// Emitted when an escrow is cancelled
+#[derive(Event)]
+pub struct EscrowCancelled {
+    pub escrow_id: String,
+    pub cancelled_by: Key,
+}
+
+/// Emitted when a dispute is raised
+#[derive(Event)]
+pub struct DisputeRaised {
+    pub escrow_id: String,
+    pub raised_by: Key,
+    pub reason: String,
+}
+
+/// Emitted when a dispute is resolved
+#[derive(Event)]
+pub struct DisputeResolved {
+    pub escrow_id: String,
+    pub resolved_by: Key,
+    pub release_to_receiver: bool,
+}
+
+/// Emitted when funds beyond the recorded balance leave the escrow purse
+#[derive(Event)]
+pub struct FundsSwept {
+    pub escrow_id: String,
+    pub recipient: Key,
+    pub amount: U512,
+}
+
+fn schemas() -> Schemas {
+    Schemas::new()
+        .with::<EscrowCreated>()
+        .with::<EscrowAccepted>()
+        .with::<FundsDeposited>()
+        .with::<FundsReleased>()
+        .with::<EscrowCancelled>()
+        .with::<DisputeRaised>()
+        .with::<DisputeResolved>()
+        .with::<FundsSwept>()
+}
+
+/// Create the CES named keys for a contract that is about to be installed.
+///
+/// `casper_event_standard::init` writes to the current context, which during
+/// installation is the deploying account, so the keys are moved into the
+/// contract's named keys instead.
+pub fn init(named_keys: &mut NamedKeys) {
+    let events = storage::new_dictionary(EVENTS_DICT).unwrap_or_revert();
+    runtime::remove_key(EVENTS_DICT);
+
+    named_keys.insert(EVENTS_DICT.to_string(), events.into());
+    named_keys.insert(EVENTS_LENGTH.to_string(), storage::new_uref(0u32).into());
+    named_keys.insert(EVENTS_SCHEMA.to_string(), storage::new_uref(schemas()).into());
+    named_keys.insert(CES_VERSION_KEY.to_string(), storage::new_uref(CES_VERSION).into());
+}
+
+/// Bring the CES keys of an installed contract up to date, from its own context.
+///
+/// Contracts installed before events were added get the full set of keys;
+/// others get the current schema, so events added since can be emitted.
+pub fn migrate() {
+    match runtime::get_key(EVENTS_SCHEMA) {
+        Some(key) => storage::write(key.into_uref().unwrap_or_revert(), schemas()),
+        None => casper_event_standard::init(schemas()),
+    }
+}
@@ -0,0 +1,47 @@
+//! Session code for funding a native escrow
+//! Moves `amount` from the caller's main purse into a temporary purse
+//! and hands that purse to the escrow's `fund` entry point
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+#[cfg(feature = "casper-1x")]
+extern crate casper_contract_1x as casper_contract;
+#[cfg(feature = "casper-1x")]
+extern crate casper_types_1x as casper_types;
+
+use casper_contract::{
+    contract_api::{account, runtime, system},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{contracts::ContractHash, ApiError, RuntimeArgs, U512};
+use escrow_core::{entry_points, Error};
+
+const ARG_ESCROW_CONTRACT: &str = "escrow_contract";
+const ARG_ESCROW_ID: &str = "escrow_id";
+const ARG_AMOUNT: &str = "amount";
+
+// Errors are reported as `ApiError::User` with the shared escrow-core codes
+fn api_error(error: Error) -> ApiError {
+    ApiError::User(error.code())
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let escrow_contract: ContractHash = runtime::get_named_arg(ARG_ESCROW_CONTRACT);
+    let escrow_id: u64 = runtime::get_named_arg(ARG_ESCROW_ID);
+    let amount: U512 = runtime::get_named_arg(ARG_AMOUNT);
+
+    // Only the temporary purse is exposed to the contract, never the main purse
+    let source = system::create_purse();
+    system::transfer_from_purse_to_purse(account::get_main_purse(), source, amount, None)
+        .unwrap_or_revert_with(api_error(Error::TransferFailed));
+
+    let mut args = RuntimeArgs::new();
+    args.insert(ARG_ESCROW_ID, escrow_id).unwrap_or_revert();
+    args.insert(ARG_AMOUNT, amount).unwrap_or_revert();
+    args.insert("source", source).unwrap_or_revert();
+
+    runtime::call_contract::<()>(escrow_contract, entry_points::FUND, args);
+}
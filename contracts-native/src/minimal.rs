@@ -4,6 +4,10 @@
 #![no_main]
 
 extern crate alloc;
+#[cfg(feature = "casper-1x")]
+extern crate casper_contract_1x as casper_contract;
+#[cfg(feature = "casper-1x")]
+extern crate casper_types_1x as casper_types;
 
 use alloc::string::ToString;
 use casper_contract::contract_api::{runtime, storage};
@@ -0,0 +1,73 @@
+//! Test contract that takes part in native escrows as a contract party
+//!
+//! Installs a contract holding its own purse under the `purse` named key.
+//! `call_escrow` forwards an escrow entry point call with the given serialized
+//! arguments, so the escrow sees this contract as the immediate caller.
+//! Arguments may name the purse, which the contract can pass on.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+#[cfg(feature = "casper-1x")]
+extern crate casper_contract_1x as casper_contract;
+#[cfg(feature = "casper-1x")]
+extern crate casper_types_1x as casper_types;
+
+// Only the install helpers of `compat` are used here
+#[allow(dead_code)]
+mod compat;
+
+use alloc::{string::{String, ToString}, vec};
+use casper_contract::{
+    contract_api::{runtime, system},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    bytesrepr::{Bytes, FromBytes},
+    contracts::{ContractHash, NamedKeys},
+    CLType, CLTyped, CLValue, EntryPoints, Parameter, RuntimeArgs,
+};
+use escrow_core::entry_points;
+
+const PACKAGE_NAME: &str = "party_proxy_package";
+const ACCESS_UREF: &str = "party_proxy_access_uref";
+const CONTRACT_KEY: &str = "party_proxy";
+const PURSE_KEY: &str = "purse";
+
+/// Call `entry_point` on `escrow_contract` with `args`, a serialized
+/// `RuntimeArgs`. Returns the escrow id for `create_escrow`.
+#[no_mangle]
+pub extern "C" fn call_escrow() {
+    let escrow_contract: ContractHash = runtime::get_named_arg("escrow_contract");
+    let entry_point: String = runtime::get_named_arg("entry_point");
+    let args: Bytes = runtime::get_named_arg("args");
+    let (args, _) = RuntimeArgs::from_bytes(&args).unwrap_or_revert();
+
+    if entry_point == entry_points::CREATE_ESCROW {
+        let escrow_id = runtime::call_contract::<u64>(escrow_contract, &entry_point, args);
+        runtime::ret(CLValue::from_t(escrow_id).unwrap_or_revert());
+    }
+    runtime::call_contract::<()>(escrow_contract, &entry_point, args);
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(compat::entry_point(
+        "call_escrow",
+        vec![
+            Parameter::new("escrow_contract", ContractHash::cl_type()),
+            Parameter::new("entry_point", CLType::String),
+            Parameter::new("args", Bytes::cl_type()),
+        ],
+        CLType::Any,
+    ));
+
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(PURSE_KEY.to_string(), system::create_purse().into());
+
+    let (contract_hash, _) =
+        compat::new_contract(entry_points, named_keys, PACKAGE_NAME, ACCESS_UREF);
+    runtime::put_key(CONTRACT_KEY, contract_hash.into());
+}
@@ -0,0 +1,70 @@
+//! Session code for upgrading an installed native escrow
+//! Adds the contract in `contract.rs` as a new version of the caller's
+//! `escrow_package`. Named keys, dictionaries and purses carry over from the
+//! previous version; older storage layouts are migrated on the first call.
+//!
+//! Optional args: `disable_previous` (bool, default `true`) disables the
+//! replaced version. Each version keeps its own copy of the named keys, so a
+//! replaced version left enabled can still reach the purses it knew; only
+//! pass `false` when it is known not to move funds.
+
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+#[cfg(feature = "casper-1x")]
+extern crate casper_contract_1x as casper_contract;
+#[cfg(feature = "casper-1x")]
+extern crate casper_event_standard_1x as casper_event_standard;
+#[cfg(feature = "casper-1x")]
+extern crate casper_types_1x as casper_types;
+
+// Installation helpers in these modules are only used by `main.rs`
+#[allow(dead_code)]
+mod compat;
+#[allow(dead_code)]
+mod contract;
+#[allow(dead_code)]
+mod events;
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    contracts::{ContractHash, ContractPackageHash},
+    ApiError,
+};
+
+use contract::{CONTRACT_ACCESS_UREF, CONTRACT_KEY, CONTRACT_PACKAGE_NAME, CONTRACT_VERSION_KEY};
+
+const ARG_DISABLE_PREVIOUS: &str = "disable_previous";
+
+/// Address stored under a `Key::Hash` named key of the caller
+fn hash_key(name: &str) -> [u8; 32] {
+    let key = runtime::get_key(name).unwrap_or_revert_with(ApiError::MissingKey);
+    compat::hash_addr(key).unwrap_or_revert_with(ApiError::UnexpectedKeyVariant)
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let package_hash = ContractPackageHash::new(hash_key(CONTRACT_PACKAGE_NAME));
+    let previous_hash = ContractHash::new(hash_key(CONTRACT_KEY));
+
+    // Only the holder of the package's access URef may add versions
+    if !runtime::has_key(CONTRACT_ACCESS_UREF) {
+        runtime::revert(ApiError::MissingKey);
+    }
+
+    let (contract_hash, contract_version) =
+        compat::add_contract_version(package_hash, contract::entry_points());
+
+    let disable_previous: bool =
+        compat::try_get_named_arg(ARG_DISABLE_PREVIOUS).unwrap_or(true);
+    if disable_previous {
+        storage::disable_contract_version(package_hash, previous_hash).unwrap_or_revert();
+    }
+
+    runtime::put_key(CONTRACT_KEY, contract_hash.into());
+    runtime::put_key(CONTRACT_VERSION_KEY, storage::new_uref(contract_version).into());
+}
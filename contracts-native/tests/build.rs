@@ -0,0 +1,36 @@
+//! Builds the contract and session wasm the tests install, with `make build`
+//! in contracts-native, so a plain `cargo test` runs against current sources
+
+use std::{env, path::PathBuf, process::Command};
+
+fn main() {
+    let contract_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("..");
+    for path in ["src", "Cargo.toml", "Cargo.lock", "Makefile"] {
+        println!(
+            "cargo:rerun-if-changed={}",
+            contract_dir.join(path).display()
+        );
+    }
+
+    let mut make = Command::new("make");
+    make.arg("build").current_dir(&contract_dir);
+    // Settings cargo passes to build scripts are for this host build, not for
+    // the wasm one
+    for (name, _) in env::vars_os() {
+        let name = name.to_string_lossy();
+        let for_host = (name.starts_with("CARGO_") && name != "CARGO_HOME")
+            || (name.starts_with("RUST") && !name.starts_with("RUSTUP_"));
+        if for_host {
+            make.env_remove(&*name);
+        }
+    }
+
+    let status = make
+        .status()
+        .unwrap_or_else(|error| panic!("could not run make in contracts-native: {}", error));
+    assert!(
+        status.success(),
+        "`make build` in contracts-native failed; it needs the toolchain from the README's \
+         \"Compiling the Smart Contract\" section"
+    );
+}
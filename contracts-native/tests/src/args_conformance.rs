@@ -0,0 +1,323 @@
+//! Argument serialization conformance
+//!
+//! Pins the CLValue type each installer, entry point and session argument
+//! is decoded as. Clients must send exactly these types: the contract API
+//! only sees argument bytes, so a mismatched type reverts with
+//! `ApiError::InvalidArgument` rather than being converted. Optional
+//! arguments are omitted when absent, never wrapped in `Option`.
+//!
+//! Types with the same encoding, such as `String` and `List<U8>` or `Bool`
+//! and `U8`, can't be told apart and aren't covered.
+
+use casper_types::{account::AccountHash, runtime_args, ApiError, CLTyped, Key, RuntimeArgs, U512};
+use escrow_core::{entry_points, Error, State};
+
+use crate::fixture::{accounts, assert_reverted, id_args, Fixture, AMOUNT};
+
+const DESCRIPTION: &str = "Factura nº 7 — 1.500 € / 発注書";
+const DUE_DATE: u64 = 1_900_000_000_000;
+
+/// Installer args that create a first escrow, with every optional party set
+fn installer_args() -> RuntimeArgs {
+    let [_, payer, arbiter, stranger] = accounts();
+    runtime_args! {
+        "payer" => Key::from(payer),
+        "amount" => AMOUNT,
+        "description" => DESCRIPTION.to_string(),
+        "issuer" => Key::from(stranger),
+        "arbiter" => Key::from(arbiter),
+        "due_date" => DUE_DATE,
+    }
+}
+
+/// `args` with `name` replaced by `value`
+fn with_arg<T: CLTyped + casper_types::bytesrepr::ToBytes>(
+    args: &RuntimeArgs,
+    name: &str,
+    value: T,
+) -> RuntimeArgs {
+    let mut replaced = RuntimeArgs::new();
+    for arg in args.named_args().filter(|arg| arg.name() != name) {
+        replaced.insert_cl_value(arg.name(), arg.cl_value().clone());
+    }
+    replaced.insert(name, value).unwrap();
+    replaced
+}
+
+/// `args` without `name`
+fn without_arg(args: &RuntimeArgs, name: &str) -> RuntimeArgs {
+    let mut removed = RuntimeArgs::new();
+    for arg in args.named_args().filter(|arg| arg.name() != name) {
+        removed.insert_cl_value(arg.name(), arg.cl_value().clone());
+    }
+    removed
+}
+
+fn create_args() -> RuntimeArgs {
+    let [_, payer, arbiter, _] = accounts();
+    runtime_args! {
+        "payer" => Key::from(payer),
+        "amount" => U512::from(AMOUNT),
+        "description" => DESCRIPTION.to_string(),
+        "arbiter" => Key::from(arbiter),
+        "due_date" => DUE_DATE,
+    }
+}
+
+fn assert_escrow_fields(fixture: &Fixture, escrow_id: u64, issuer: AccountHash) {
+    assert_eq!(fixture.state(escrow_id), State::Draft);
+    assert_eq!(fixture.field::<Key>("issuer", escrow_id), Key::from(issuer));
+    assert_eq!(
+        fixture.field::<Key>("payer", escrow_id),
+        Key::from(fixture.payer)
+    );
+    assert_eq!(
+        fixture.field::<Option<Key>>("arbiter", escrow_id),
+        Some(Key::from(fixture.arbiter))
+    );
+    assert_eq!(
+        fixture.field::<U512>("amount", escrow_id),
+        U512::from(AMOUNT)
+    );
+    assert_eq!(
+        fixture.field::<String>("description", escrow_id),
+        DESCRIPTION
+    );
+    assert_eq!(
+        fixture.field::<Option<u64>>("due_date", escrow_id),
+        Some(DUE_DATE)
+    );
+}
+
+#[test]
+fn test_installer_decodes_first_escrow_args() {
+    let fixture = Fixture::install(installer_args()).unwrap();
+    let [_, _, _, stranger] = accounts();
+
+    assert_eq!(fixture.escrow_count(), 1);
+    assert_escrow_fields(&fixture, 0, stranger);
+}
+
+#[test]
+fn test_installer_issuer_defaults_to_deployer() {
+    let fixture = Fixture::install(without_arg(&installer_args(), "issuer")).unwrap();
+    assert_escrow_fields(&fixture, 0, fixture.issuer);
+}
+
+#[test]
+fn test_installer_rejects_mistyped_args() {
+    let args = installer_args();
+    let [_, payer, arbiter, _] = accounts();
+    let cases = [
+        // The installer takes the amount as u64, unlike `create_escrow`
+        ("amount", with_arg(&args, "amount", U512::from(AMOUNT))),
+        ("amount", with_arg(&args, "amount", AMOUNT as u32)),
+        ("payer", with_arg(&args, "payer", payer)),
+        (
+            "description",
+            with_arg(&args, "description", Some(DESCRIPTION.to_string())),
+        ),
+        (
+            "arbiter",
+            with_arg(&args, "arbiter", Some(Key::from(arbiter))),
+        ),
+        ("arbiter", with_arg(&args, "arbiter", arbiter)),
+        ("due_date", with_arg(&args, "due_date", Some(DUE_DATE))),
+    ];
+    for (name, args) in cases {
+        match Fixture::install(args) {
+            Ok(_) => panic!("installer accepted a mistyped `{}`", name),
+            Err(error) => assert_reverted(Some(error), ApiError::InvalidArgument),
+        }
+    }
+}
+
+#[test]
+fn test_installer_requires_first_escrow_args() {
+    for name in ["amount", "description"] {
+        match Fixture::install(without_arg(&installer_args(), name)) {
+            Ok(_) => panic!("installer accepted a missing `{}`", name),
+            Err(error) => assert_reverted(Some(error), ApiError::MissingArgument),
+        }
+    }
+}
+
+#[test]
+fn test_create_escrow_decodes_args() {
+    let mut fixture = Fixture::new();
+    fixture.call_ok(fixture.issuer, entry_points::CREATE_ESCROW, create_args());
+    assert_escrow_fields(&fixture, 0, fixture.issuer);
+}
+
+#[test]
+fn test_create_escrow_rejects_mistyped_args() {
+    let mut fixture = Fixture::new();
+    let args = create_args();
+    let (payer, arbiter) = (fixture.payer, fixture.arbiter);
+    let cases = [
+        // `create_escrow` takes the amount as U512, unlike the installer
+        with_arg(&args, "amount", AMOUNT),
+        with_arg(&args, "amount", AMOUNT.to_string()),
+        with_arg(&args, "payer", payer),
+        with_arg(&args, "payer", payer.to_formatted_string()),
+        with_arg(&args, "issuer", fixture.issuer),
+        with_arg(&args, "arbiter", Some(Key::from(arbiter))),
+        with_arg(&args, "due_date", Some(DUE_DATE)),
+        with_arg(&args, "due_date", U512::from(DUE_DATE)),
+    ];
+    for args in cases {
+        fixture
+            .call(fixture.issuer, entry_points::CREATE_ESCROW, args)
+            .expect_failure();
+        assert_reverted(fixture.builder.get_error(), ApiError::InvalidArgument);
+    }
+    assert_eq!(fixture.escrow_count(), 0);
+}
+
+#[test]
+fn test_escrow_id_is_u64() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let payer = fixture.payer;
+
+    let cases = [
+        runtime_args! { "escrow_id" => id as u32 },
+        runtime_args! { "escrow_id" => U512::from(id) },
+        runtime_args! { "escrow_id" => id.to_string() },
+    ];
+    for args in cases {
+        fixture
+            .call(payer, entry_points::ACCEPT, args)
+            .expect_failure();
+        assert_reverted(fixture.builder.get_error(), ApiError::InvalidArgument);
+    }
+
+    fixture
+        .call(payer, entry_points::ACCEPT, RuntimeArgs::new())
+        .expect_failure();
+    assert_reverted(fixture.builder.get_error(), ApiError::MissingArgument);
+
+    fixture.call_ok(payer, entry_points::ACCEPT, id_args(id));
+    assert_eq!(fixture.state(id), State::Accepted);
+}
+
+#[test]
+fn test_fund_source_is_uref() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let payer = fixture.payer;
+    fixture.call_ok(payer, entry_points::ACCEPT, id_args(id));
+
+    let source = fixture.main_purse(payer);
+    let cases = [
+        runtime_args! { "escrow_id" => id, "amount" => U512::from(AMOUNT), "source" => Key::from(source) },
+        runtime_args! { "escrow_id" => id, "amount" => AMOUNT, "source" => source },
+    ];
+    for args in cases {
+        fixture
+            .call(payer, entry_points::FUND, args)
+            .expect_failure();
+        assert_reverted(fixture.builder.get_error(), ApiError::InvalidArgument);
+    }
+
+    // A well-typed URef decodes; the main purse itself is refused by the
+    // mint, which is why funding goes through `fund_session.wasm`
+    let args =
+        runtime_args! { "escrow_id" => id, "amount" => U512::from(AMOUNT), "source" => source };
+    fixture.call_err(payer, entry_points::FUND, args, Error::TransferFailed);
+    assert_eq!(fixture.state(id), State::Accepted);
+}
+
+#[test]
+fn test_dispute_args_round_trip() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    fixture.accept_and_fund(id);
+    let (payer, arbiter) = (fixture.payer, fixture.arbiter);
+
+    let args = runtime_args! { "escrow_id" => id, "reason" => Some(DESCRIPTION.to_string()) };
+    fixture
+        .call(payer, entry_points::DISPUTE, args)
+        .expect_failure();
+    assert_reverted(fixture.builder.get_error(), ApiError::InvalidArgument);
+
+    let args = runtime_args! { "escrow_id" => id, "reason" => DESCRIPTION.to_string() };
+    fixture.call_ok(payer, entry_points::DISPUTE, args);
+    let (raised_by, reason, _): (Key, String, u64) =
+        fixture.field::<Option<_>>("dispute", id).unwrap();
+    assert_eq!(
+        (raised_by, reason.as_str()),
+        (Key::from(payer), DESCRIPTION)
+    );
+
+    let args = runtime_args! { "escrow_id" => id, "release_to_receiver" => "true".to_string() };
+    fixture
+        .call(arbiter, entry_points::RESOLVE_DISPUTE, args)
+        .expect_failure();
+    assert_reverted(fixture.builder.get_error(), ApiError::InvalidArgument);
+
+    let args = runtime_args! { "escrow_id" => id, "release_to_receiver" => false };
+    fixture.call_ok(arbiter, entry_points::RESOLVE_DISPUTE, args);
+    assert_eq!(fixture.state(id), State::Released);
+}
+
+#[test]
+fn test_fund_session_args() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let payer = fixture.payer;
+    fixture.call_ok(payer, entry_points::ACCEPT, id_args(id));
+
+    let args = runtime_args! {
+        "escrow_contract" => fixture.contract_hash,
+        "escrow_id" => id,
+        "amount" => U512::from(AMOUNT),
+    };
+    let cases = [
+        with_arg(&args, "escrow_contract", Key::from(fixture.contract_hash)),
+        with_arg(&args, "escrow_id", id as u32),
+        with_arg(&args, "amount", AMOUNT),
+    ];
+    for args in cases {
+        fixture.run_fund_session(payer, args).expect_failure();
+        assert_reverted(fixture.builder.get_error(), ApiError::InvalidArgument);
+    }
+
+    fixture
+        .run_fund_session(payer, args)
+        .expect_success()
+        .commit();
+    assert_eq!(fixture.state(id), State::Funded);
+}
+
+#[test]
+fn test_create_and_fund_session_args() {
+    let mut fixture = Fixture::new();
+    let (issuer, payer) = (fixture.issuer, fixture.payer);
+
+    let args = runtime_args! {
+        "escrow_contract" => fixture.contract_hash,
+        "issuer" => Key::from(issuer),
+        "amount" => U512::from(AMOUNT),
+        "description" => DESCRIPTION.to_string(),
+        "due_date" => DUE_DATE,
+    };
+    let cases = [
+        with_arg(&args, "escrow_contract", Key::from(fixture.contract_hash)),
+        with_arg(&args, "issuer", issuer),
+        with_arg(&args, "amount", AMOUNT),
+        with_arg(&args, "arbiter", fixture.arbiter),
+        with_arg(&args, "due_date", Some(DUE_DATE)),
+    ];
+    for args in cases {
+        fixture.run_create_and_fund(payer, args).expect_failure();
+        assert_reverted(fixture.builder.get_error(), ApiError::InvalidArgument);
+    }
+
+    fixture
+        .run_create_and_fund(payer, args)
+        .expect_success()
+        .commit();
+    assert_eq!(fixture.state(0), State::Funded);
+    assert_eq!(fixture.field::<Option<u64>>("due_date", 0), Some(DUE_DATE));
+}
@@ -0,0 +1,414 @@
+//! Installed escrow contract in an in-process engine, shared by the test modules
+
+use std::path::PathBuf;
+
+use casper_engine_test_support::{
+    ExecuteRequestBuilder, LmdbWasmTestBuilder, TransferRequestBuilder, DEFAULT_ACCOUNT_ADDR,
+    LOCAL_GENESIS_REQUEST,
+};
+use casper_execution_engine::{engine_state, execution::ExecError};
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{Bytes, FromBytes, ToBytes},
+    contracts::ContractHash,
+    runtime_args, AddressableEntityHash, ApiError, CLTyped, CLValue, Key, PublicKey, RuntimeArgs,
+    SecretKey, URef, U512,
+};
+use escrow_core::{entry_points, Error, State};
+
+const ESCROW_WASM: &str = "escrow.wasm";
+const FUND_SESSION_WASM: &str = "fund_session.wasm";
+const CREATE_AND_FUND_WASM: &str = "create_and_fund.wasm";
+const PARTY_PROXY_WASM: &str = "party_proxy.wasm";
+const UPGRADE_WASM: &str = "upgrade.wasm";
+const BASELINE_WASM: &str = "escrow_baseline.wasm";
+
+/// Motes given to each test account, enough to pay for many calls
+const ACCOUNT_BALANCE: u64 = 1_000_000_000_000_000;
+/// Escrow amount used by most tests
+pub const AMOUNT: u64 = 500_000_000_000;
+
+/// Path of a binary built by `make build` in contracts-native
+/// in `contracts-native/`
+fn wasm_path(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../target/wasm32-unknown-unknown/release")
+        .join(name);
+    assert!(
+        path.exists(),
+        "{} not found; `make build` in contracts-native should have built it",
+        path.display()
+    );
+    path.to_str().unwrap().to_string()
+}
+
+fn account(seed: u8) -> AccountHash {
+    let secret_key = SecretKey::ed25519_from_bytes([seed; 32]).unwrap();
+    PublicKey::from(&secret_key).to_account_hash()
+}
+
+/// Issuer, payer, arbiter and stranger accounts, in that order.
+/// The issuer installs the contract.
+pub fn accounts() -> [AccountHash; 4] {
+    [account(1), account(2), account(3), account(4)]
+}
+
+/// Contract from `party_proxy.wasm` that takes part in escrows as a party
+pub struct ProxyParty {
+    pub contract_hash: ContractHash,
+    /// Package of the proxy, which is how the escrow identifies it
+    pub key: Key,
+    /// Purse the proxy funds escrows from and receives payouts into
+    pub purse: URef,
+}
+
+/// Installed escrow contract and the accounts that use it
+pub struct Fixture {
+    pub builder: LmdbWasmTestBuilder,
+    pub contract_hash: ContractHash,
+    pub issuer: AccountHash,
+    pub payer: AccountHash,
+    pub arbiter: AccountHash,
+    pub stranger: AccountHash,
+}
+
+impl Fixture {
+    /// Install the contract without creating an escrow
+    pub fn new() -> Self {
+        Self::install(RuntimeArgs::new())
+            .unwrap_or_else(|error| panic!("install failed: {:?}", error))
+    }
+
+    /// Install the contract with installer `args`, returning the engine error
+    /// if the installer reverts
+    pub fn install(args: RuntimeArgs) -> Result<Self, engine_state::Error> {
+        Self::install_wasm(ESCROW_WASM, args)
+    }
+
+    /// Install the first released contract, which holds a single escrow
+    /// with the installer as both issuer and payer
+    pub fn install_baseline(args: RuntimeArgs) -> Self {
+        Self::install_wasm(BASELINE_WASM, args)
+            .unwrap_or_else(|error| panic!("baseline install failed: {:?}", error))
+    }
+
+    fn install_wasm(wasm: &str, args: RuntimeArgs) -> Result<Self, engine_state::Error> {
+        let mut builder = LmdbWasmTestBuilder::default();
+        builder.run_genesis(LOCAL_GENESIS_REQUEST.clone());
+
+        let [issuer, payer, arbiter, stranger] = accounts();
+        for target in [issuer, payer, arbiter, stranger] {
+            let transfer = TransferRequestBuilder::new(ACCOUNT_BALANCE, target)
+                .with_initiator(*DEFAULT_ACCOUNT_ADDR)
+                .build();
+            builder.transfer_and_commit(transfer).expect_success();
+        }
+
+        let install = ExecuteRequestBuilder::standard(issuer, &wasm_path(wasm), args).build();
+        if let Some(error) = builder.exec(install).get_error() {
+            return Err(error);
+        }
+        builder.commit();
+
+        let contract_hash = installed_contract(&builder, issuer);
+        Ok(Fixture {
+            builder,
+            contract_hash,
+            issuer,
+            payer,
+            arbiter,
+            stranger,
+        })
+    }
+
+    /// Add the current contract as a new version of the issuer's package
+    /// with `upgrade.wasm` and its `args`, and call that version from then on
+    pub fn upgrade(&mut self, args: RuntimeArgs) {
+        let request =
+            ExecuteRequestBuilder::standard(self.issuer, &wasm_path(UPGRADE_WASM), args).build();
+        self.builder.exec(request).expect_success().commit();
+        self.contract_hash = installed_contract(&self.builder, self.issuer);
+    }
+
+    /// Call an entry point as `sender`, without committing
+    pub fn call(
+        &mut self,
+        sender: AccountHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> &mut LmdbWasmTestBuilder {
+        self.call_version(self.contract_hash, sender, entry_point, args)
+    }
+
+    /// Call an entry point of a given contract version as `sender`,
+    /// without committing
+    pub fn call_version(
+        &mut self,
+        contract_hash: ContractHash,
+        sender: AccountHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> &mut LmdbWasmTestBuilder {
+        let hash = AddressableEntityHash::new(contract_hash.value());
+        let request =
+            ExecuteRequestBuilder::contract_call_by_hash(sender, hash, entry_point, args).build();
+        self.builder.exec(request)
+    }
+
+    /// Call an entry point as `sender` and commit, which must succeed
+    pub fn call_ok(&mut self, sender: AccountHash, entry_point: &str, args: RuntimeArgs) {
+        self.call(sender, entry_point, args)
+            .expect_success()
+            .commit();
+    }
+
+    /// Call an entry point as `sender`, which must revert with `error`
+    pub fn call_err(
+        &mut self,
+        sender: AccountHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+        error: Error,
+    ) {
+        self.call(sender, entry_point, args).expect_failure();
+        assert_user_error(&self.builder, error);
+    }
+
+    /// Create an escrow from the issuer to the payer, with the arbiter named
+    pub fn create(&mut self, amount: u64) -> u64 {
+        let id = self.escrow_count();
+        let args = runtime_args! {
+            "payer" => Key::from(self.payer),
+            "arbiter" => Key::from(self.arbiter),
+            "amount" => U512::from(amount),
+            "description" => "Invoice INV-001".to_string(),
+        };
+        self.call_ok(self.issuer, entry_points::CREATE_ESCROW, args);
+        id
+    }
+
+    /// Run `fund_session.wasm` as `sender`, without committing
+    pub fn fund(
+        &mut self,
+        sender: AccountHash,
+        escrow_id: u64,
+        amount: u64,
+    ) -> &mut LmdbWasmTestBuilder {
+        let args = runtime_args! {
+            "escrow_contract" => self.contract_hash,
+            "escrow_id" => escrow_id,
+            "amount" => U512::from(amount),
+        };
+        self.run_fund_session(sender, args)
+    }
+
+    /// Run `fund_session.wasm` as `sender` with raw `args`, without committing
+    pub fn run_fund_session(
+        &mut self,
+        sender: AccountHash,
+        args: RuntimeArgs,
+    ) -> &mut LmdbWasmTestBuilder {
+        let request =
+            ExecuteRequestBuilder::standard(sender, &wasm_path(FUND_SESSION_WASM), args).build();
+        self.builder.exec(request)
+    }
+
+    /// Run `create_and_fund.wasm` as `sender` with `args`, without committing
+    pub fn run_create_and_fund(
+        &mut self,
+        sender: AccountHash,
+        args: RuntimeArgs,
+    ) -> &mut LmdbWasmTestBuilder {
+        let request =
+            ExecuteRequestBuilder::standard(sender, &wasm_path(CREATE_AND_FUND_WASM), args).build();
+        self.builder.exec(request)
+    }
+
+    /// Install a party proxy contract as `installer`
+    pub fn install_proxy(&mut self, installer: AccountHash) -> ProxyParty {
+        let install = ExecuteRequestBuilder::standard(
+            installer,
+            &wasm_path(PARTY_PROXY_WASM),
+            RuntimeArgs::new(),
+        )
+        .build();
+        self.builder.exec(install).expect_success().commit();
+
+        let contract_hash = self
+            .builder
+            .get_named_keys_by_account_hash(installer)
+            .get("party_proxy")
+            .and_then(|key| key.into_hash_addr())
+            .map(ContractHash::new)
+            .expect("installer should store the proxy hash");
+        let contract = self.builder.get_contract(contract_hash).unwrap();
+        let key = Key::Hash(contract.contract_package_hash().value());
+        let purse = contract
+            .named_keys()
+            .get("purse")
+            .unwrap()
+            .into_uref()
+            .unwrap();
+        ProxyParty {
+            contract_hash,
+            key,
+            purse,
+        }
+    }
+
+    /// Call an escrow entry point through `proxy`, sent by `sender`,
+    /// without committing
+    pub fn call_via(
+        &mut self,
+        sender: AccountHash,
+        proxy: &ProxyParty,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> &mut LmdbWasmTestBuilder {
+        let args = runtime_args! {
+            "escrow_contract" => self.contract_hash,
+            "entry_point" => entry_point.to_string(),
+            "args" => Bytes::from(args.to_bytes().unwrap()),
+        };
+        let hash = AddressableEntityHash::new(proxy.contract_hash.value());
+        let request =
+            ExecuteRequestBuilder::contract_call_by_hash(sender, hash, "call_escrow", args).build();
+        self.builder.exec(request)
+    }
+
+    /// Transfer `amount` from `sender` into `purse`
+    pub fn top_up(&mut self, sender: AccountHash, purse: URef, amount: u64) {
+        let transfer = TransferRequestBuilder::new(amount, purse)
+            .with_initiator(sender)
+            .build();
+        self.builder.transfer_and_commit(transfer).expect_success();
+    }
+
+    /// Accept and fund an escrow as the payer
+    pub fn accept_and_fund(&mut self, escrow_id: u64) {
+        self.call_ok(
+            self.payer,
+            entry_points::ACCEPT,
+            runtime_args! { "escrow_id" => escrow_id },
+        );
+        self.fund(self.payer, escrow_id, AMOUNT)
+            .expect_success()
+            .commit();
+    }
+
+    /// Call a view entry point as `sender` and decode its return value
+    pub fn view<T: CLTyped + FromBytes>(
+        &mut self,
+        sender: AccountHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> T {
+        self.view_value(sender, entry_point, args).into_t().unwrap()
+    }
+
+    /// Call a view entry point as `sender` and return its value undecoded
+    pub fn view_value(
+        &mut self,
+        sender: AccountHash,
+        entry_point: &str,
+        args: RuntimeArgs,
+    ) -> CLValue {
+        self.call(sender, entry_point, args).expect_success();
+        let result = self.builder.get_last_exec_result().unwrap();
+        result.ret().expect("view should return a value").clone()
+    }
+
+    /// Transfer `amount` from `sender` straight into an escrow purse,
+    /// bypassing the contract
+    pub fn deposit_stray(&mut self, sender: AccountHash, escrow_id: u64, amount: u64) {
+        self.top_up(sender, self.escrow_purse(escrow_id), amount);
+    }
+
+    pub fn contract_key(&self, name: &str) -> Key {
+        let contract = self
+            .builder
+            .get_contract(self.contract_hash)
+            .expect("contract should exist");
+        *contract
+            .named_keys()
+            .get(name)
+            .unwrap_or_else(|| panic!("missing named key {}", name))
+    }
+
+    pub fn escrow_count(&self) -> u64 {
+        let key = self.contract_key("escrow_count");
+        self.builder
+            .query(None, key, &[])
+            .unwrap()
+            .as_cl_value()
+            .unwrap()
+            .clone()
+            .into_t()
+            .unwrap()
+    }
+
+    pub fn field<T: CLTyped + FromBytes>(&self, dictionary: &str, escrow_id: u64) -> T {
+        let seed = self.contract_key(dictionary).into_uref().unwrap();
+        self.builder
+            .query_dictionary_item(None, seed, &escrow_id.to_string())
+            .unwrap()
+            .as_cl_value()
+            .unwrap()
+            .clone()
+            .into_t()
+            .unwrap()
+    }
+
+    pub fn state(&self, escrow_id: u64) -> State {
+        State::from_u8(self.field("state", escrow_id)).unwrap()
+    }
+
+    pub fn escrow_purse(&self, escrow_id: u64) -> URef {
+        self.contract_key(&format!("escrow_purse_{}", escrow_id))
+            .into_uref()
+            .unwrap()
+    }
+
+    pub fn escrow_purse_balance(&self, escrow_id: u64) -> U512 {
+        self.builder.get_purse_balance(self.escrow_purse(escrow_id))
+    }
+
+    pub fn main_purse(&self, account: AccountHash) -> URef {
+        self.builder
+            .get_expected_addressable_entity_by_account_hash(account)
+            .main_purse()
+    }
+
+    pub fn balance_of(&self, account: AccountHash) -> U512 {
+        self.builder.get_purse_balance(self.main_purse(account))
+    }
+}
+
+/// Escrow contract hash stored under `installer` by the installer or upgrade session
+fn installed_contract(builder: &LmdbWasmTestBuilder, installer: AccountHash) -> ContractHash {
+    builder
+        .get_named_keys_by_account_hash(installer)
+        .get("escrow_contract")
+        .and_then(|key| key.into_hash_addr())
+        .map(ContractHash::new)
+        .expect("installer should store the contract hash")
+}
+
+/// Assert that an execution reverted with `expected`
+pub fn assert_reverted(error: Option<engine_state::Error>, expected: ApiError) {
+    match error {
+        Some(engine_state::Error::Exec(ExecError::Revert(api_error))) => {
+            assert_eq!(api_error, expected)
+        }
+        other => panic!("expected revert with {:?}, got {:?}", expected, other),
+    }
+}
+
+/// Assert that the last execution reverted with escrow error `expected`
+pub fn assert_user_error(builder: &LmdbWasmTestBuilder, expected: Error) {
+    assert_reverted(builder.get_error(), ApiError::User(expected.code()));
+}
+
+pub fn id_args(escrow_id: u64) -> RuntimeArgs {
+    runtime_args! { "escrow_id" => escrow_id }
+}
@@ -0,0 +1,879 @@
+//! Native escrow lifecycle tests
+//!
+//! Each test installs `escrow.wasm` into a fresh in-process engine, funds
+//! issuer, payer, arbiter and stranger accounts from the genesis account,
+//! and drives the contract through stored-contract calls. Funding goes
+//! through `fund_session.wasm`, as it does on a real network.
+
+use casper_engine_test_support::ExecuteRequestBuilder;
+use casper_types::{
+    account::AccountHash, runtime_args, AddressableEntityHash, CLType, Key, RuntimeArgs, U512,
+};
+use escrow_core::{entry_points, Error, State};
+use termina_client::native;
+
+use crate::fixture::{assert_user_error, id_args, Fixture, AMOUNT};
+
+#[test]
+fn test_full_lifecycle_moves_funds_to_issuer() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    assert_eq!(id, 0);
+    assert_eq!(fixture.state(id), State::Draft);
+    assert_eq!(fixture.escrow_count(), 1);
+
+    fixture.call_ok(fixture.payer, entry_points::ACCEPT, id_args(id));
+    assert_eq!(fixture.state(id), State::Accepted);
+
+    fixture
+        .fund(fixture.payer, id, AMOUNT)
+        .expect_success()
+        .commit();
+    assert_eq!(fixture.state(id), State::Funded);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::from(AMOUNT));
+    assert_eq!(fixture.field::<U512>("balance", id), U512::from(AMOUNT));
+
+    // The payer pays for the release call, so the issuer's gain is exact
+    let issuer_before = fixture.balance_of(fixture.issuer);
+    fixture.call_ok(fixture.payer, entry_points::RELEASE, id_args(id));
+    assert_eq!(fixture.state(id), State::Released);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+    assert_eq!(fixture.field::<U512>("balance", id), U512::zero());
+    assert_eq!(fixture.balance_of(fixture.issuer), issuer_before + AMOUNT);
+}
+
+#[test]
+fn test_dispute_resolved_for_payer_refunds_payer() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    fixture.accept_and_fund(id);
+
+    let args = runtime_args! { "escrow_id" => id, "reason" => "Goods not delivered".to_string() };
+    fixture.call_ok(fixture.issuer, entry_points::DISPUTE, args);
+    assert_eq!(fixture.state(id), State::Disputed);
+
+    let payer_before = fixture.balance_of(fixture.payer);
+    let args = runtime_args! { "escrow_id" => id, "release_to_receiver" => false };
+    fixture.call_ok(fixture.arbiter, entry_points::RESOLVE_DISPUTE, args);
+    assert_eq!(fixture.state(id), State::Released);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+    assert_eq!(fixture.balance_of(fixture.payer), payer_before + AMOUNT);
+}
+
+#[test]
+fn test_unauthorized_callers_are_rejected() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let (issuer, payer, arbiter, stranger) = (
+        fixture.issuer,
+        fixture.payer,
+        fixture.arbiter,
+        fixture.stranger,
+    );
+
+    fixture.call_err(
+        stranger,
+        entry_points::ACCEPT,
+        id_args(id),
+        Error::Unauthorized,
+    );
+    fixture.call_err(
+        issuer,
+        entry_points::ACCEPT,
+        id_args(id),
+        Error::Unauthorized,
+    );
+    // Only the issuer can withdraw a draft
+    fixture.call_err(
+        payer,
+        entry_points::CANCEL,
+        id_args(id),
+        Error::Unauthorized,
+    );
+
+    fixture.call_ok(payer, entry_points::ACCEPT, id_args(id));
+    fixture.fund(stranger, id, AMOUNT).expect_failure();
+    assert_user_error(&fixture.builder, Error::Unauthorized);
+
+    fixture.fund(payer, id, AMOUNT).expect_success().commit();
+    fixture.call_err(
+        stranger,
+        entry_points::RELEASE,
+        id_args(id),
+        Error::Unauthorized,
+    );
+    fixture.call_err(
+        arbiter,
+        entry_points::RELEASE,
+        id_args(id),
+        Error::Unauthorized,
+    );
+    // Only the payer releases, as in the first release
+    fixture.call_err(
+        issuer,
+        entry_points::RELEASE,
+        id_args(id),
+        Error::Unauthorized,
+    );
+
+    let args = runtime_args! { "escrow_id" => id, "reason" => "Late".to_string() };
+    fixture.call_err(
+        stranger,
+        entry_points::DISPUTE,
+        args.clone(),
+        Error::Unauthorized,
+    );
+    fixture.call_ok(payer, entry_points::DISPUTE, args);
+
+    let args = runtime_args! { "escrow_id" => id, "release_to_receiver" => true };
+    fixture.call_err(
+        payer,
+        entry_points::RESOLVE_DISPUTE,
+        args.clone(),
+        Error::Unauthorized,
+    );
+    fixture.call_err(
+        stranger,
+        entry_points::RESOLVE_DISPUTE,
+        args,
+        Error::Unauthorized,
+    );
+
+    // Nothing left the escrow purse
+    assert_eq!(fixture.state(id), State::Disputed);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::from(AMOUNT));
+}
+
+#[test]
+fn test_wrong_state_calls_are_rejected() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let (issuer, payer) = (fixture.issuer, fixture.payer);
+
+    fixture.call_err(
+        payer,
+        entry_points::RELEASE,
+        id_args(id),
+        Error::InvalidState,
+    );
+    fixture.fund(payer, id, AMOUNT).expect_failure();
+    assert_user_error(&fixture.builder, Error::InvalidState);
+
+    fixture.call_ok(payer, entry_points::ACCEPT, id_args(id));
+    fixture.call_err(
+        payer,
+        entry_points::ACCEPT,
+        id_args(id),
+        Error::InvalidState,
+    );
+
+    fixture.fund(payer, id, AMOUNT).expect_success().commit();
+    fixture.call_err(
+        issuer,
+        entry_points::CANCEL,
+        id_args(id),
+        Error::InvalidState,
+    );
+    let args = runtime_args! { "escrow_id" => id, "release_to_receiver" => true };
+    fixture.call_err(
+        fixture.arbiter,
+        entry_points::RESOLVE_DISPUTE,
+        args,
+        Error::InvalidState,
+    );
+
+    fixture.call_ok(payer, entry_points::RELEASE, id_args(id));
+    fixture.call_err(
+        payer,
+        entry_points::RELEASE,
+        id_args(id),
+        Error::InvalidState,
+    );
+    fixture.call_err(
+        issuer,
+        entry_points::CANCEL,
+        id_args(id),
+        Error::InvalidState,
+    );
+
+    let cancelled = fixture.create(AMOUNT);
+    fixture.call_ok(issuer, entry_points::CANCEL, id_args(cancelled));
+    assert_eq!(fixture.state(cancelled), State::Cancelled);
+    fixture.call_err(
+        payer,
+        entry_points::ACCEPT,
+        id_args(cancelled),
+        Error::InvalidState,
+    );
+}
+
+#[test]
+fn test_unknown_escrow_is_not_found() {
+    let mut fixture = Fixture::new();
+    fixture.create(AMOUNT);
+    fixture.call_err(
+        fixture.payer,
+        entry_points::ACCEPT,
+        id_args(7),
+        Error::NotFound,
+    );
+    fixture.call_err(
+        fixture.payer,
+        entry_points::GET_STATE,
+        id_args(7),
+        Error::NotFound,
+    );
+}
+
+#[test]
+fn test_underfunding_is_rejected() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    fixture.call_ok(fixture.payer, entry_points::ACCEPT, id_args(id));
+
+    fixture.fund(fixture.payer, id, AMOUNT - 1).expect_failure();
+    assert_user_error(&fixture.builder, Error::InsufficientFunds);
+    assert_eq!(fixture.state(id), State::Accepted);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+}
+
+#[test]
+fn test_transfer_failure_reverts_fund() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    fixture.call_ok(fixture.payer, entry_points::ACCEPT, id_args(id));
+
+    // The mint only debits a main purse from its own account's session, so
+    // passing it straight to the contract makes the transfer fail
+    let source = fixture.main_purse(fixture.payer);
+    let args = runtime_args! {
+        "escrow_id" => id,
+        "amount" => U512::from(AMOUNT),
+        "source" => source,
+    };
+    fixture.call_err(
+        fixture.payer,
+        entry_points::FUND,
+        args,
+        Error::TransferFailed,
+    );
+    assert_eq!(fixture.state(id), State::Accepted);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+}
+
+#[test]
+fn test_create_validates_terms() {
+    let mut fixture = Fixture::new();
+    let (issuer, payer, arbiter) = (fixture.issuer, fixture.payer, fixture.arbiter);
+    let create =
+        |payer: AccountHash, arbiter: Option<AccountHash>, amount: u64, description: &str| {
+            let mut args = runtime_args! {
+                "payer" => Key::from(payer),
+                "amount" => U512::from(amount),
+                "description" => description.to_string(),
+            };
+            if let Some(arbiter) = arbiter {
+                args.insert("arbiter", Key::from(arbiter)).unwrap();
+            }
+            args
+        };
+
+    let cases = [
+        (create(payer, None, 0, "Invoice"), Error::ZeroAmount),
+        (
+            create(issuer, None, AMOUNT, "Invoice"),
+            Error::PayerIsIssuer,
+        ),
+        (
+            create(payer, Some(payer), AMOUNT, "Invoice"),
+            Error::ArbiterIsParty,
+        ),
+        (
+            create(payer, Some(arbiter), AMOUNT, ""),
+            Error::EmptyDescription,
+        ),
+        (
+            create(
+                payer,
+                None,
+                AMOUNT,
+                &"x".repeat(escrow_core::MAX_DESCRIPTION_LENGTH + 1),
+            ),
+            Error::DescriptionTooLong,
+        ),
+    ];
+    for (args, error) in cases {
+        fixture.call_err(issuer, entry_points::CREATE_ESCROW, args, error);
+    }
+
+    // Parties are accounts or contract packages
+    let mut args = create(payer, None, AMOUNT, "Invoice");
+    args.insert("arbiter", Key::Dictionary([5; 32])).unwrap();
+    fixture.call_err(
+        issuer,
+        entry_points::CREATE_ESCROW,
+        args,
+        Error::InvalidParty,
+    );
+
+    let mut args = create(payer, None, AMOUNT, "Invoice");
+    args.insert("due_date", 1_000u64).unwrap();
+    let hash = AddressableEntityHash::new(fixture.contract_hash.value());
+    let request = ExecuteRequestBuilder::contract_call_by_hash(
+        issuer,
+        hash,
+        entry_points::CREATE_ESCROW,
+        args,
+    )
+    .with_block_time(2_000u64)
+    .build();
+    fixture.builder.exec(request).expect_failure();
+    assert_user_error(&fixture.builder, Error::DueDateInPast);
+
+    assert_eq!(fixture.escrow_count(), 0);
+}
+
+#[test]
+fn test_stray_deposit_is_reported_and_swept_on_agreement() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    fixture.accept_and_fund(id);
+    let (issuer, payer, stranger) = (fixture.issuer, fixture.payer, fixture.stranger);
+
+    const STRAY: u64 = 7_000_000_000;
+    fixture.deposit_stray(stranger, id, STRAY);
+    let balances: (U512, U512) =
+        fixture.view(stranger, entry_points::GET_PURSE_BALANCES, id_args(id));
+    assert_eq!(balances, (U512::from(AMOUNT), U512::from(AMOUNT + STRAY)));
+
+    let to_stranger = runtime_args! { "escrow_id" => id, "recipient" => Key::from(stranger) };
+    let to_issuer = runtime_args! { "escrow_id" => id, "recipient" => Key::from(issuer) };
+    fixture.call_err(
+        stranger,
+        entry_points::SWEEP,
+        to_stranger.clone(),
+        Error::Unauthorized,
+    );
+    fixture.call_err(
+        fixture.arbiter,
+        entry_points::SWEEP,
+        to_stranger.clone(),
+        Error::Unauthorized,
+    );
+
+    // A contract recipient must be a party with a payout purse
+    let to_contract = runtime_args! { "escrow_id" => id, "recipient" => Key::Hash([5; 32]) };
+    fixture.call_err(
+        issuer,
+        entry_points::SWEEP,
+        to_contract,
+        Error::PayoutPurseRequired,
+    );
+    let to_dictionary =
+        runtime_args! { "escrow_id" => id, "recipient" => Key::Dictionary([5; 32]) };
+    fixture.call_err(
+        issuer,
+        entry_points::SWEEP,
+        to_dictionary,
+        Error::InvalidParty,
+    );
+
+    // A proposal alone, repeated or contradicted, moves nothing
+    fixture.call_ok(issuer, entry_points::SWEEP, to_stranger.clone());
+    fixture.call_ok(issuer, entry_points::SWEEP, to_stranger.clone());
+    fixture.call_ok(payer, entry_points::SWEEP, to_issuer);
+    fixture.call_ok(issuer, entry_points::SWEEP, to_stranger.clone());
+    assert_eq!(fixture.escrow_purse_balance(id), U512::from(AMOUNT + STRAY));
+
+    let stranger_before = fixture.balance_of(stranger);
+    fixture.call_ok(payer, entry_points::SWEEP, to_stranger.clone());
+    assert_eq!(fixture.balance_of(stranger), stranger_before + STRAY);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::from(AMOUNT));
+    assert_eq!(fixture.state(id), State::Funded);
+
+    fixture.call_err(
+        issuer,
+        entry_points::SWEEP,
+        to_stranger,
+        Error::NothingToSweep,
+    );
+
+    // The escrowed amount itself is untouched by the sweep
+    let issuer_before = fixture.balance_of(issuer);
+    fixture.call_ok(payer, entry_points::RELEASE, id_args(id));
+    assert_eq!(fixture.balance_of(issuer), issuer_before + AMOUNT);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+}
+
+#[test]
+fn test_fund_records_only_what_the_purse_received() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let payer = fixture.payer;
+    fixture.call_ok(payer, entry_points::ACCEPT, id_args(id));
+
+    // Funds already in the purse don't count towards the deposit
+    fixture.deposit_stray(fixture.stranger, id, AMOUNT - 1);
+    fixture.fund(payer, id, 1).expect_failure();
+    assert_user_error(&fixture.builder, Error::InsufficientFunds);
+
+    fixture.fund(payer, id, AMOUNT).expect_success().commit();
+    assert_eq!(fixture.field::<U512>("balance", id), U512::from(AMOUNT));
+    let balances: (U512, U512) = fixture.view(payer, entry_points::GET_PURSE_BALANCES, id_args(id));
+    assert_eq!(balances, (U512::from(AMOUNT), U512::from(2 * AMOUNT - 1)));
+
+    // Only the recorded balance is released; the stray funds stay sweepable
+    fixture.call_ok(payer, entry_points::RELEASE, id_args(id));
+    assert_eq!(fixture.escrow_purse_balance(id), U512::from(AMOUNT - 1));
+}
+
+#[test]
+fn test_cancel_refunds_purse_to_payer() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let (issuer, payer) = (fixture.issuer, fixture.payer);
+    fixture.call_ok(payer, entry_points::ACCEPT, id_args(id));
+    fixture.deposit_stray(fixture.stranger, id, AMOUNT);
+    assert!(!fixture.field::<bool>("refunded", id));
+
+    // The issuer pays for the cancel call, so the payer's gain is exact
+    let payer_before = fixture.balance_of(payer);
+    fixture.call_ok(issuer, entry_points::CANCEL, id_args(id));
+    assert_eq!(fixture.state(id), State::Cancelled);
+    assert_eq!(fixture.balance_of(payer), payer_before + AMOUNT);
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+    assert!(fixture.field::<bool>("refunded", id));
+}
+
+#[test]
+fn test_recorded_balance_is_paid_out_in_every_terminal_state() {
+    type Path = fn(&mut Fixture, u64);
+    fn dispute(fixture: &mut Fixture, id: u64, release_to_receiver: bool) {
+        fixture.accept_and_fund(id);
+        let args = runtime_args! { "escrow_id" => id, "reason" => "Late".to_string() };
+        fixture.call_ok(fixture.issuer, entry_points::DISPUTE, args);
+        let args =
+            runtime_args! { "escrow_id" => id, "release_to_receiver" => release_to_receiver };
+        fixture.call_ok(fixture.arbiter, entry_points::RESOLVE_DISPUTE, args);
+    }
+
+    // Each path from a new escrow to a final state, with the expected
+    // `refunded` flag and whether stray funds are refunded along the way
+    let paths: [(&str, Path, State, bool, bool); 5] = [
+        (
+            "release",
+            |fixture, id| {
+                fixture.accept_and_fund(id);
+                fixture.call_ok(fixture.payer, entry_points::RELEASE, id_args(id));
+            },
+            State::Released,
+            false,
+            false,
+        ),
+        (
+            "dispute for issuer",
+            |fixture, id| dispute(fixture, id, true),
+            State::Released,
+            false,
+            false,
+        ),
+        (
+            "dispute for payer",
+            |fixture, id| dispute(fixture, id, false),
+            State::Released,
+            true,
+            false,
+        ),
+        (
+            "cancel draft",
+            |fixture, id| {
+                fixture.call_ok(fixture.issuer, entry_points::CANCEL, id_args(id));
+            },
+            State::Cancelled,
+            false,
+            true,
+        ),
+        (
+            "cancel accepted",
+            |fixture, id| {
+                fixture.call_ok(fixture.payer, entry_points::ACCEPT, id_args(id));
+                fixture.call_ok(fixture.payer, entry_points::CANCEL, id_args(id));
+            },
+            State::Cancelled,
+            false,
+            true,
+        ),
+    ];
+
+    let stray = U512::from(AMOUNT / 10);
+    for (name, path, state, refunded, refunds_stray) in paths {
+        for with_stray in [false, true] {
+            let name = format!("{}, stray funds: {}", name, with_stray);
+            let mut fixture = Fixture::new();
+            let id = fixture.create(AMOUNT);
+            if with_stray {
+                fixture.deposit_stray(fixture.stranger, id, stray.as_u64());
+            }
+            path(&mut fixture, id);
+
+            assert_eq!(fixture.state(id), state, "{}", name);
+            assert!(state.is_final(), "{}", name);
+            // Stray funds are only refunded by cancel; otherwise they await a sweep
+            let left = if with_stray && !refunds_stray {
+                stray
+            } else {
+                U512::zero()
+            };
+            let balances: (U512, U512) = fixture.view(
+                fixture.stranger,
+                entry_points::GET_PURSE_BALANCES,
+                id_args(id),
+            );
+            assert_eq!(balances, (U512::zero(), left), "{}", name);
+            let refunded = refunded || (with_stray && refunds_stray);
+            assert_eq!(fixture.field::<bool>("refunded", id), refunded, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn test_upgrade_moves_first_release_escrow_into_escrow_zero() {
+    let args = runtime_args! {
+        "amount" => AMOUNT,
+        "description" => "Invoice INV-001".to_string(),
+    };
+    let mut fixture = Fixture::install_baseline(args);
+    // The first release made the installer both issuer and payer and took
+    // no escrow id; `fund_session.wasm` passes one, which it ignores
+    let owner = fixture.issuer;
+    fixture.call_ok(owner, entry_points::ACCEPT, RuntimeArgs::new());
+    fixture.fund(owner, 0, AMOUNT).expect_success().commit();
+
+    // The first release is disabled by default, so it can't release the
+    // purse that escrow 0 now owns
+    let baseline = fixture.contract_hash;
+    fixture.upgrade(RuntimeArgs::new());
+    fixture
+        .call_version(baseline, owner, entry_points::RELEASE, RuntimeArgs::new())
+        .expect_failure();
+    // Views run without committing, so this sees the migration but does not keep it
+    let balances: (U512, U512) = fixture.view(
+        fixture.stranger,
+        entry_points::GET_PURSE_BALANCES,
+        id_args(0),
+    );
+    assert_eq!(balances, (U512::from(AMOUNT), U512::from(AMOUNT)));
+
+    // The owner pays for the call, so its gain is the amount less gas
+    let owner_before = fixture.balance_of(owner);
+    fixture.call_ok(owner, entry_points::RELEASE, id_args(0));
+    assert!(fixture.balance_of(owner) > owner_before);
+    assert_eq!(fixture.escrow_purse_balance(0), U512::zero());
+    assert_eq!(fixture.escrow_count(), 1);
+    assert_eq!(fixture.state(0), State::Released);
+    assert_eq!(fixture.field::<Key>("issuer", 0), Key::from(owner));
+    assert_eq!(fixture.field::<Key>("payer", 0), Key::from(owner));
+    assert_eq!(fixture.field::<String>("description", 0), "Invoice INV-001");
+
+    // New escrows follow the migrated one
+    assert_eq!(fixture.create(AMOUNT), 1);
+}
+
+#[test]
+fn test_upgrade_disables_previous_version_unless_asked_not_to() {
+    let mut fixture = Fixture::new();
+    let id = fixture.create(AMOUNT);
+    let payer = fixture.payer;
+
+    let first = fixture.contract_hash;
+    fixture.upgrade(runtime_args! { "disable_previous" => true });
+    assert_ne!(fixture.contract_hash, first);
+    fixture
+        .call_version(first, payer, entry_points::ACCEPT, id_args(id))
+        .expect_failure();
+
+    let second = fixture.contract_hash;
+    fixture.upgrade(runtime_args! { "disable_previous" => false });
+    fixture
+        .call_version(second, payer, entry_points::ACCEPT, id_args(id))
+        .expect_success()
+        .commit();
+    assert_eq!(fixture.state(id), State::Accepted);
+}
+
+#[test]
+fn test_contract_issuer_is_paid_into_its_payout_purse() {
+    let mut fixture = Fixture::new();
+    let proxy = fixture.install_proxy(fixture.issuer);
+    let (issuer, payer) = (fixture.issuer, fixture.payer);
+
+    let args = runtime_args! {
+        "payer" => Key::from(payer),
+        "amount" => U512::from(AMOUNT),
+        "description" => "Marketplace order".to_string(),
+    };
+    fixture
+        .call_via(issuer, &proxy, entry_points::CREATE_ESCROW, args.clone())
+        .expect_failure();
+    assert_user_error(&fixture.builder, Error::PayoutPurseRequired);
+
+    let mut with_purse = args;
+    with_purse.insert("issuer_purse", proxy.purse).unwrap();
+    fixture
+        .call_via(issuer, &proxy, entry_points::CREATE_ESCROW, with_purse)
+        .expect_success()
+        .commit();
+    let id = 0;
+    assert_eq!(fixture.field::<Key>("issuer", id), proxy.key);
+
+    // The account that sent the proxy call is not the issuer
+    fixture.call_err(
+        issuer,
+        entry_points::CANCEL,
+        id_args(id),
+        Error::Unauthorized,
+    );
+
+    fixture.accept_and_fund(id);
+    fixture.call_ok(payer, entry_points::RELEASE, id_args(id));
+    assert_eq!(fixture.state(id), State::Released);
+    assert_eq!(
+        fixture.builder.get_purse_balance(proxy.purse),
+        U512::from(AMOUNT)
+    );
+}
+
+#[test]
+fn test_only_the_issuer_chooses_where_its_payouts_go() {
+    let mut fixture = Fixture::new();
+    let issuer_proxy = fixture.install_proxy(fixture.issuer);
+    let stranger_proxy = fixture.install_proxy(fixture.stranger);
+    let (issuer, payer, stranger) = (fixture.issuer, fixture.payer, fixture.stranger);
+    let args = |issuer: Key, with_purse: bool| {
+        let mut args = runtime_args! {
+            "issuer" => issuer,
+            "payer" => Key::from(payer),
+            "amount" => U512::from(AMOUNT),
+            "description" => "Marketplace order".to_string(),
+        };
+        if with_purse {
+            args.insert("issuer_purse", stranger_proxy.purse).unwrap();
+        }
+        args
+    };
+
+    // Another contract can't name a contract issuer
+    fixture
+        .call_via(
+            stranger,
+            &stranger_proxy,
+            entry_points::CREATE_ESCROW,
+            args(issuer_proxy.key, true),
+        )
+        .expect_failure();
+    assert_user_error(&fixture.builder, Error::Unauthorized);
+
+    // An account issuer is paid into its main purse, so a payout purse is
+    // rejected rather than ignored, even when the issuer passes it
+    fixture
+        .call_via(
+            stranger,
+            &stranger_proxy,
+            entry_points::CREATE_ESCROW,
+            args(Key::from(issuer), true),
+        )
+        .expect_failure();
+    assert_user_error(&fixture.builder, Error::UnexpectedPayoutPurse);
+    let mut own_purse = args(Key::from(issuer), false);
+    own_purse
+        .insert("issuer_purse", fixture.main_purse(issuer))
+        .unwrap();
+    fixture.call_err(
+        issuer,
+        entry_points::CREATE_ESCROW,
+        own_purse,
+        Error::UnexpectedPayoutPurse,
+    );
+    assert_eq!(fixture.escrow_count(), 0);
+
+    // Naming an account issuer without a purse is allowed; it is paid directly
+    let for_account = args(Key::from(issuer), false);
+    fixture
+        .call_via(
+            stranger,
+            &stranger_proxy,
+            entry_points::CREATE_ESCROW,
+            for_account,
+        )
+        .expect_success()
+        .commit();
+    assert_eq!(fixture.field::<Key>("issuer", 0), Key::from(issuer));
+}
+
+#[test]
+fn test_summary_decodes_with_the_client() {
+    let mut fixture = Fixture::new();
+    let proxy = fixture.install_proxy(fixture.payer);
+    let (issuer, payer, arbiter) = (fixture.issuer, fixture.payer, fixture.arbiter);
+    let args = runtime_args! {
+        "payer" => proxy.key,
+        "arbiter" => Key::from(arbiter),
+        "amount" => U512::from(AMOUNT),
+        "description" => "DAO grant".to_string(),
+    };
+    fixture.call_ok(issuer, entry_points::CREATE_ESCROW, args);
+    let args = runtime_args! { "escrow_id" => 0u64, "payer_purse" => proxy.purse };
+    fixture
+        .call_via(payer, &proxy, entry_points::ACCEPT, args)
+        .expect_success()
+        .commit();
+
+    let value = fixture.view_value(issuer, entry_points::GET_SUMMARY, id_args(0));
+    assert_eq!(*value.cl_type(), CLType::Any);
+    let summary = native::EscrowSummary::from_cl_value(&value).unwrap();
+    assert_eq!(
+        summary,
+        native::EscrowSummary {
+            escrow_id: 0,
+            state: State::Accepted,
+            issuer: Key::from(issuer),
+            payer: proxy.key,
+            arbiter: Some(Key::from(arbiter)),
+            amount: U512::from(AMOUNT),
+            balance: U512::zero(),
+            description: "DAO grant".to_string(),
+            due_date: None,
+            created_at: summary.created_at,
+            accepted_at: summary.accepted_at,
+            funded_at: None,
+            released_at: None,
+            dispute: None,
+            refunded: false,
+            issuer_purse: None,
+            payer_purse: Some(proxy.purse.remove_access_rights()),
+        }
+    );
+    assert!(summary.accepted_at.is_some());
+}
+
+#[test]
+fn test_contract_payer_funds_from_its_purse_and_is_refunded() {
+    let mut fixture = Fixture::new();
+    let proxy = fixture.install_proxy(fixture.payer);
+    let (issuer, payer, arbiter) = (fixture.issuer, fixture.payer, fixture.arbiter);
+
+    let args = runtime_args! {
+        "payer" => proxy.key,
+        "arbiter" => Key::from(arbiter),
+        "amount" => U512::from(AMOUNT),
+        "description" => "DAO grant".to_string(),
+    };
+    fixture.call_ok(issuer, entry_points::CREATE_ESCROW, args);
+    let id = 0;
+
+    // Only the contract itself acts as payer, not the account calling it
+    fixture.call_err(
+        payer,
+        entry_points::ACCEPT,
+        id_args(id),
+        Error::Unauthorized,
+    );
+    fixture
+        .call_via(payer, &proxy, entry_points::ACCEPT, id_args(id))
+        .expect_failure();
+    assert_user_error(&fixture.builder, Error::PayoutPurseRequired);
+
+    let args = runtime_args! { "escrow_id" => id, "payer_purse" => proxy.purse };
+    fixture
+        .call_via(payer, &proxy, entry_points::ACCEPT, args)
+        .expect_success()
+        .commit();
+
+    fixture.top_up(payer, proxy.purse, AMOUNT);
+    let args = runtime_args! { "escrow_id" => id, "source" => proxy.purse };
+    fixture
+        .call_via(payer, &proxy, entry_points::FUND, args)
+        .expect_success()
+        .commit();
+    assert_eq!(fixture.state(id), State::Funded);
+    assert_eq!(fixture.builder.get_purse_balance(proxy.purse), U512::zero());
+
+    let args = runtime_args! { "escrow_id" => id, "reason" => "Milestone missed".to_string() };
+    fixture.call_ok(issuer, entry_points::DISPUTE, args);
+    let args = runtime_args! { "escrow_id" => id, "release_to_receiver" => false };
+    fixture.call_ok(arbiter, entry_points::RESOLVE_DISPUTE, args);
+    assert_eq!(fixture.state(id), State::Released);
+    assert_eq!(
+        fixture.builder.get_purse_balance(proxy.purse),
+        U512::from(AMOUNT)
+    );
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+
+    // Stray funds swept to the contract payer go to its payout purse
+    fixture.deposit_stray(fixture.stranger, id, AMOUNT);
+    let to_proxy = runtime_args! { "escrow_id" => id, "recipient" => proxy.key };
+    fixture.call_ok(issuer, entry_points::SWEEP, to_proxy.clone());
+    fixture
+        .call_via(payer, &proxy, entry_points::SWEEP, to_proxy)
+        .expect_success()
+        .commit();
+    assert_eq!(
+        fixture.builder.get_purse_balance(proxy.purse),
+        U512::from(2 * AMOUNT)
+    );
+    assert_eq!(fixture.escrow_purse_balance(id), U512::zero());
+}
+
+fn create_and_fund_args(fixture: &Fixture, amount: u64) -> RuntimeArgs {
+    runtime_args! {
+        "escrow_contract" => fixture.contract_hash,
+        "issuer" => Key::from(fixture.issuer),
+        "arbiter" => Key::from(fixture.arbiter),
+        "amount" => U512::from(amount),
+        "description" => "Pre-paid PO-17".to_string(),
+    }
+}
+
+#[test]
+fn test_create_and_fund_session_leaves_escrow_funded() {
+    let mut fixture = Fixture::new();
+    let (issuer, payer) = (fixture.issuer, fixture.payer);
+
+    let args = create_and_fund_args(&fixture, AMOUNT);
+    fixture
+        .run_create_and_fund(payer, args)
+        .expect_success()
+        .commit();
+    let id = 0;
+    assert_eq!(fixture.escrow_count(), 1);
+    assert_eq!(fixture.state(id), State::Funded);
+    assert_eq!(fixture.field::<Key>("issuer", id), Key::from(issuer));
+    assert_eq!(fixture.field::<Key>("payer", id), Key::from(payer));
+    assert!(fixture.field::<Option<u64>>("accepted_at", id).is_some());
+    assert_eq!(fixture.field::<U512>("balance", id), U512::from(AMOUNT));
+    assert_eq!(fixture.escrow_purse_balance(id), U512::from(AMOUNT));
+
+    let issuer_before = fixture.balance_of(issuer);
+    fixture.call_ok(payer, entry_points::RELEASE, id_args(id));
+    assert_eq!(fixture.balance_of(issuer), issuer_before + AMOUNT);
+}
+
+#[test]
+fn test_create_and_fund_session_reverts_as_a_whole() {
+    let mut fixture = Fixture::new();
+    let (issuer, payer) = (fixture.issuer, fixture.payer);
+
+    // Rejected by `create_escrow`
+    let args = create_and_fund_args(&fixture, AMOUNT);
+    fixture.run_create_and_fund(issuer, args).expect_failure();
+    assert_user_error(&fixture.builder, Error::PayerIsIssuer);
+
+    // Created and accepted, then the payer can't cover the amount
+    let balance_before = fixture.balance_of(payer);
+    let args = create_and_fund_args(&fixture, balance_before.as_u64() + 1);
+    fixture.run_create_and_fund(payer, args).expect_failure();
+    assert_user_error(&fixture.builder, Error::TransferFailed);
+
+    assert_eq!(fixture.escrow_count(), 0);
+    assert!(fixture.balance_of(payer) <= balance_before);
+}
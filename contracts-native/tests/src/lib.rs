@@ -0,0 +1,10 @@
+//! Tests for the native escrow contract, run against its compiled WASM in an
+//! in-process Casper execution engine. `build.rs` builds the wasm with
+//! `make build` in contracts-native.
+
+#[cfg(test)]
+mod args_conformance;
+#[cfg(test)]
+mod fixture;
+#[cfg(test)]
+mod integration_tests;
@@ -1,10 +1,17 @@
 //! Main Escrow contract implementation
 
+use escrow_core::{Action, Role};
+use odra::casper_types::bytesrepr::Bytes;
+use odra::casper_types::U512;
 use odra::prelude::*;
-use odra::prelude::{Address, Var};
+use odra::prelude::{Address, List, Mapping, Var};
 
 use crate::events::*;
 use crate::types::*;
+use crate::validation::validate_config;
+
+/// Maximum number of history entries returned by a single `get_history` call
+pub const MAX_HISTORY_PAGE: u32 = 50;
 
 /// Main Escrow contract for B2B invoice workflows
 #[odra::module(events = [
@@ -14,7 +21,9 @@ use crate::types::*;
     FundsReleased,
     EscrowCancelled,
     DisputeRaised,
-    DisputeResolved
+    DisputeResolved,
+    ReleaseApproved,
+    SecretRevealed
 ])]
 pub struct Escrow {
     /// Current state of the escrow
@@ -22,7 +31,17 @@ pub struct Escrow {
     /// Invoice data
     invoice: Var<Invoice>,
     /// Amount currently held in escrow
-    balance: Var<u64>,
+    balance: Var<U512>,
+    /// Release rule supplied at creation
+    release_conditions: Var<Vec<ConditionGroup>>,
+    /// Release approvals recorded per address
+    approvals: Mapping<Address, bool>,
+    /// Hashes whose preimage has been revealed
+    revealed_secrets: Mapping<[u8; 32], bool>,
+    /// Append-only log of state transitions
+    history: List<Transition>,
+    /// Details of the raised dispute, if any
+    dispute: Var<DisputeInfo>,
 }
 
 #[odra::module]
@@ -32,37 +51,46 @@ impl Escrow {
         let caller = self.env().caller();
         let now = self.env().get_block_time();
 
+        if let Err(error) = validate_config(&config, caller, now) {
+            self.env().revert(error);
+        }
+
         let invoice = Invoice {
             id: config.id.clone(),
             description: config.description,
             amount: config.amount,
+            currency: config.currency.clone(),
             issuer: caller,
             payer: config.payer,
             arbiter: config.arbiter,
             created_at: now,
             due_date: config.due_date,
+            accepted_at: None,
+            funded_at: None,
+            released_at: None,
         };
 
         self.invoice.set(invoice);
-        self.state.set(EscrowState::Draft);
-        self.balance.set(0);
+        self.set_state(EscrowState::Draft);
+        self.balance.set(U512::zero());
+        self.release_conditions.set(config.release_conditions);
 
         self.env().emit_event(EscrowCreated {
             escrow_id: config.id,
             issuer: caller,
             payer: config.payer,
             amount: config.amount,
+            currency_symbol: config.currency.symbol,
+            currency_decimals: config.currency.decimals,
         });
     }
 
     /// Accept the escrow terms (called by payer)
     pub fn accept(&mut self) {
-        self.require_state(EscrowState::Draft);
-        self.require_payer();
-
+        let next = self.advance(Action::Accept);
         let invoice = self.invoice.get().unwrap();
 
-        self.state.set(EscrowState::Accepted);
+        self.set_state(next);
 
         self.env().emit_event(EscrowAccepted {
             escrow_id: invoice.id,
@@ -72,18 +100,21 @@ impl Escrow {
 
     /// Deposit funds into escrow (called by payer)
     /// Note: In production, this would handle actual token transfers
-    pub fn fund(&mut self, amount: u64) {
-        self.require_state(EscrowState::Accepted);
-        self.require_payer();
-
+    pub fn fund(&mut self, amount: U512) {
+        let next = self.advance(Action::Fund);
         let invoice = self.invoice.get().unwrap();
 
-        if amount < invoice.amount {
-            self.env().revert(EscrowError::InsufficientFunds);
+        if let Err(error) = escrow_core::check_funding(&invoice.amount, &amount) {
+            self.env().revert(EscrowError::from(error));
         }
 
-        self.balance.set(amount);
-        self.state.set(EscrowState::Funded);
+        let balance = self
+            .balance
+            .get_or_default()
+            .checked_add(amount)
+            .unwrap_or_revert_with(self, ArithmeticsError::AdditionOverflow);
+        self.balance.set(balance);
+        self.set_state(next);
 
         self.env().emit_event(FundsDeposited {
             escrow_id: invoice.id,
@@ -92,16 +123,64 @@ impl Escrow {
         });
     }
 
-    /// Release funds to the issuer (called by payer to approve)
+    /// Record the caller's approval of the release (payer, attestor or approver)
+    pub fn approve(&mut self) {
+        self.advance(Action::Approve);
+
+        let invoice = self.invoice.get().unwrap();
+        let caller = self.env().caller();
+
+        self.approvals.set(&caller, true);
+
+        self.env().emit_event(ReleaseApproved {
+            escrow_id: invoice.id,
+            approver: caller,
+        });
+    }
+
+    /// Reveal the preimage of a hash named in a `SecretRevealed` condition
+    pub fn reveal_secret(&mut self, secret: Bytes) {
+        self.advance(Action::RevealSecret);
+
+        let hash = self.env().hash(&secret);
+        let expected = self.effective_conditions().iter().any(|group| {
+            group.all_of.iter().any(|condition| {
+                matches!(condition, ReleaseCondition::SecretRevealed { hash: h } if *h == hash)
+            })
+        });
+        if !expected {
+            self.env().revert(EscrowError::InvalidSecret);
+        }
+
+        self.revealed_secrets.set(&hash, true);
+
+        let invoice = self.invoice.get().unwrap();
+        self.env().emit_event(SecretRevealed {
+            escrow_id: invoice.id,
+            revealed_by: self.env().caller(),
+        });
+    }
+
+    /// Release funds to the issuer once the release conditions are met.
+    /// A call by the payer also counts as the payer's approval.
     pub fn release(&mut self) {
-        self.require_state(EscrowState::Funded);
-        self.require_payer();
+        let next = self.advance(Action::Release);
 
         let invoice = self.invoice.get().unwrap();
+        let caller = self.env().caller();
+
+        if caller == invoice.payer {
+            self.approvals.set(&caller, true);
+        }
+
+        if !self.get_unmet_conditions().iter().any(|g| g.all_of.is_empty()) {
+            self.env().revert(EscrowError::ConditionsNotMet);
+        }
+
         let amount = self.balance.get_or_default();
 
-        self.balance.set(0);
-        self.state.set(EscrowState::Released);
+        self.balance.set(U512::zero());
+        self.set_state(next);
 
         // In production: transfer tokens to issuer here
 
@@ -114,29 +193,11 @@ impl Escrow {
 
     /// Cancel the escrow (only in Draft or Accepted state)
     pub fn cancel(&mut self) {
-        let state = self.state.get_or_default();
+        let next = self.advance(Action::Cancel);
         let invoice = self.invoice.get().unwrap();
         let caller = self.env().caller();
 
-        match state {
-            EscrowState::Draft => {
-                // Only issuer can cancel in draft
-                if caller != invoice.issuer {
-                    self.env().revert(EscrowError::Unauthorized);
-                }
-            }
-            EscrowState::Accepted => {
-                // Both parties can cancel before funding
-                if caller != invoice.issuer && caller != invoice.payer {
-                    self.env().revert(EscrowError::Unauthorized);
-                }
-            }
-            _ => {
-                self.env().revert(EscrowError::InvalidState);
-            }
-        }
-
-        self.state.set(EscrowState::Cancelled);
+        self.set_state(next);
 
         self.env().emit_event(EscrowCancelled {
             escrow_id: invoice.id,
@@ -146,16 +207,17 @@ impl Escrow {
 
     /// Raise a dispute (only when funded)
     pub fn dispute(&mut self, reason: String) {
-        self.require_state(EscrowState::Funded);
+        let next = self.advance(Action::Dispute);
 
         let invoice = self.invoice.get().unwrap();
         let caller = self.env().caller();
 
-        if caller != invoice.issuer && caller != invoice.payer {
-            self.env().revert(EscrowError::Unauthorized);
-        }
-
-        self.state.set(EscrowState::Disputed);
+        self.dispute.set(DisputeInfo {
+            raised_by: caller,
+            reason: reason.clone(),
+            raised_at: self.env().get_block_time(),
+        });
+        self.set_state(next);
 
         self.env().emit_event(DisputeRaised {
             escrow_id: invoice.id,
@@ -166,14 +228,13 @@ impl Escrow {
 
     /// Resolve a dispute (only by arbiter)
     pub fn resolve_dispute(&mut self, release_to_receiver: bool) {
-        self.require_state(EscrowState::Disputed);
-        self.require_arbiter();
+        let next = self.advance(Action::ResolveDispute);
 
         let invoice = self.invoice.get().unwrap();
         let amount = self.balance.get_or_default();
 
-        self.balance.set(0);
-        self.state.set(EscrowState::Released);
+        self.balance.set(U512::zero());
+        self.set_state(next);
 
         let receiver = if release_to_receiver {
             invoice.issuer
@@ -209,36 +270,131 @@ impl Escrow {
     }
 
     /// Get the current balance held in escrow
-    pub fn get_balance(&self) -> u64 {
+    pub fn get_balance(&self) -> U512 {
         self.balance.get_or_default()
     }
 
+    /// Get a complete snapshot of the escrow in a single call
+    pub fn get_summary(&self) -> EscrowSummary {
+        EscrowSummary {
+            state: self.state.get_or_default(),
+            invoice: self.invoice.get().unwrap(),
+            balance: self.balance.get_or_default(),
+            dispute: self.dispute.get(),
+            pending_conditions: self.get_unmet_conditions(),
+        }
+    }
+
+    /// Get the release rule, with the payer-approval default applied
+    pub fn get_release_conditions(&self) -> Vec<ConditionGroup> {
+        self.effective_conditions()
+    }
+
+    /// Get the release rule reduced to the conditions that are still unmet.
+    /// A group with no remaining conditions means funds can be released.
+    pub fn get_unmet_conditions(&self) -> Vec<ConditionGroup> {
+        let invoice = self.invoice.get().unwrap();
+        self.effective_conditions()
+            .into_iter()
+            .map(|group| ConditionGroup {
+                all_of: group
+                    .all_of
+                    .into_iter()
+                    .filter(|condition| !self.is_condition_met(condition, &invoice))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Get the number of recorded state transitions
+    pub fn get_history_len(&self) -> u32 {
+        self.history.len()
+    }
+
+    /// Get up to `limit` history entries starting at `offset`, oldest first.
+    /// `limit` is capped at `MAX_HISTORY_PAGE`.
+    pub fn get_history(&self, offset: u32, limit: u32) -> Vec<Transition> {
+        let end = offset
+            .saturating_add(limit.min(MAX_HISTORY_PAGE))
+            .min(self.history.len());
+        (offset..end).filter_map(|i| self.history.get(i)).collect()
+    }
+
     // --- Internal helpers ---
 
-    fn require_state(&self, expected: EscrowState) {
-        let current = self.state.get_or_default();
-        if core::mem::discriminant(&current) != core::mem::discriminant(&expected) {
-            self.env().revert(EscrowError::InvalidState);
+    /// Move to a new state, stamping the invoice and appending to the history
+    fn set_state(&mut self, to: EscrowState) {
+        let now = self.env().get_block_time();
+
+        if let Some(mut invoice) = self.invoice.get() {
+            match to {
+                EscrowState::Accepted => invoice.accepted_at = Some(now),
+                EscrowState::Funded => invoice.funded_at = Some(now),
+                EscrowState::Released => invoice.released_at = Some(now),
+                _ => {}
+            }
+            self.invoice.set(invoice);
         }
+
+        let from = self.state.get();
+        self.state.set(to.clone());
+        self.history.push(Transition {
+            from,
+            to,
+            actor: self.env().caller(),
+            timestamp: now,
+        });
     }
 
-    fn require_payer(&self) {
+    /// Check `action` by the caller against the shared transition table,
+    /// returning the state it leads to
+    fn advance(&self, action: Action) -> EscrowState {
         let invoice = self.invoice.get().unwrap();
-        if self.env().caller() != invoice.payer {
-            self.env().revert(EscrowError::Unauthorized);
+        let role = Role::of(
+            &self.env().caller(),
+            &invoice.issuer,
+            &invoice.payer,
+            invoice.arbiter.as_ref(),
+        );
+        match escrow_core::transition(self.state.get_or_default().into(), action, role) {
+            Ok(next) => next.into(),
+            Err(error) => self.env().revert(EscrowError::from(error)),
         }
     }
 
-    fn require_arbiter(&self) {
-        let invoice = self.invoice.get().unwrap();
-        match invoice.arbiter {
-            Some(arbiter) if self.env().caller() == arbiter => {}
-            _ => self.env().revert(EscrowError::Unauthorized),
+    fn effective_conditions(&self) -> Vec<ConditionGroup> {
+        let groups = self.release_conditions.get_or_default();
+        if groups.is_empty() {
+            vec![ConditionGroup {
+                all_of: vec![ReleaseCondition::PayerApproval],
+            }]
+        } else {
+            groups
+        }
+    }
+
+    fn is_condition_met(&self, condition: &ReleaseCondition, invoice: &Invoice) -> bool {
+        match condition {
+            ReleaseCondition::PayerApproval => self.approvals.get_or_default(&invoice.payer),
+            ReleaseCondition::TimeElapsed { after } => self.env().get_block_time() >= *after,
+            ReleaseCondition::AttestorSigned { attestor } => self.approvals.get_or_default(attestor),
+            ReleaseCondition::Approvals {
+                approvers,
+                threshold,
+            } => {
+                let approved = approvers
+                    .iter()
+                    .filter(|approver| self.approvals.get_or_default(approver))
+                    .count();
+                approved >= *threshold as usize
+            }
+            ReleaseCondition::SecretRevealed { hash } => self.revealed_secrets.get_or_default(hash),
         }
     }
 }
 
-/// Errors that can occur in the Escrow contract
+/// Errors that can occur in the Escrow contract.
+/// Codes match `escrow_core::Error`, which the native contract reports.
 #[odra::odra_error]
 pub enum EscrowError {
     /// Caller is not authorized to perform this action
@@ -249,14 +405,103 @@ pub enum EscrowError {
     InsufficientFunds = 3,
     /// Escrow not found
     NotFound = 4,
+    /// Release conditions are not yet satisfied
+    ConditionsNotMet = 5,
+    /// Revealed secret does not match any release condition
+    InvalidSecret = 6,
+    /// Escrow amount must be greater than zero
+    ZeroAmount = 7,
+    /// Payer must differ from the issuer
+    PayerIsIssuer = 8,
+    /// Arbiter must not be the issuer or the payer
+    ArbiterIsParty = 9,
+    /// Due date must lie in the future
+    DueDateInPast = 10,
+    /// Escrow id must not be empty
+    EmptyId = 11,
+    /// Escrow id exceeds the maximum length
+    IdTooLong = 12,
+    /// Description must not be empty
+    EmptyDescription = 13,
+    /// Description exceeds the maximum length
+    DescriptionTooLong = 14,
+    /// Release condition is malformed (empty group, bad threshold, duplicate approvers)
+    InvalidReleaseCondition = 15,
+    /// Moving funds out of or into the escrow purse failed
+    TransferFailed = 16,
+    /// Escrow purse holds no funds beyond the recorded balance
+    NothingToSweep = 17,
+    /// A contract party must pass a purse it can receive payouts into
+    PayoutPurseRequired = 18,
+    /// Party must be an account or a contract package
+    InvalidParty = 19,
+    /// Accounts are paid into their main purse and take no payout purse
+    UnexpectedPayoutPurse = 20,
+}
+
+impl From<escrow_core::Error> for EscrowError {
+    fn from(error: escrow_core::Error) -> Self {
+        use escrow_core::Error;
+        match error {
+            Error::Unauthorized => EscrowError::Unauthorized,
+            Error::InvalidState => EscrowError::InvalidState,
+            Error::InsufficientFunds => EscrowError::InsufficientFunds,
+            Error::NotFound => EscrowError::NotFound,
+            Error::ConditionsNotMet => EscrowError::ConditionsNotMet,
+            Error::InvalidSecret => EscrowError::InvalidSecret,
+            Error::ZeroAmount => EscrowError::ZeroAmount,
+            Error::PayerIsIssuer => EscrowError::PayerIsIssuer,
+            Error::ArbiterIsParty => EscrowError::ArbiterIsParty,
+            Error::DueDateInPast => EscrowError::DueDateInPast,
+            Error::EmptyId => EscrowError::EmptyId,
+            Error::IdTooLong => EscrowError::IdTooLong,
+            Error::EmptyDescription => EscrowError::EmptyDescription,
+            Error::DescriptionTooLong => EscrowError::DescriptionTooLong,
+            Error::InvalidReleaseCondition => EscrowError::InvalidReleaseCondition,
+            Error::TransferFailed => EscrowError::TransferFailed,
+            Error::NothingToSweep => EscrowError::NothingToSweep,
+            Error::PayoutPurseRequired => EscrowError::PayoutPurseRequired,
+            Error::InvalidParty => EscrowError::InvalidParty,
+            Error::UnexpectedPayoutPurse => EscrowError::UnexpectedPayoutPurse,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Escrow;
-    use crate::escrow::EscrowInitArgs;
+    use crate::escrow::{EscrowError, EscrowHostRef, EscrowInitArgs};
     use crate::types::*;
-    use odra::host::Deployer;
+    use crate::validation::{MAX_DESCRIPTION_LENGTH, MAX_ID_LENGTH};
+    use odra::casper_types::bytesrepr::Bytes;
+    use odra::casper_types::{Digest, U512};
+    use odra::host::{Deployer, HostEnv};
+    use odra::prelude::*;
+    use odra::prelude::Address;
+
+    /// Deploys an escrow with the given release rule and funds it
+    fn funded_escrow(env: &HostEnv, release_conditions: Vec<ConditionGroup>) -> EscrowHostRef {
+        let issuer = env.get_account(0);
+        let payer = env.get_account(1);
+
+        env.set_caller(issuer);
+        let config = EscrowConfig {
+            id: "INV-002".to_string(),
+            description: "Conditional release".to_string(),
+            amount: U512::from(1000),
+            currency: Currency::cspr(),
+            payer,
+            arbiter: None,
+            due_date: None,
+            release_conditions,
+        };
+        let mut escrow = Escrow::deploy(env, EscrowInitArgs { config });
+
+        env.set_caller(payer);
+        escrow.accept();
+        escrow.fund(U512::from(1000));
+        escrow
+    }
 
     #[test]
     fn test_escrow_lifecycle() {
@@ -270,10 +515,12 @@ mod tests {
         let config = EscrowConfig {
             id: "INV-001".to_string(),
             description: "Services rendered".to_string(),
-            amount: 1000,
+            amount: U512::from(1000),
+            currency: Currency::cspr(),
             payer,
             arbiter: None,
             due_date: None,
+            release_conditions: vec![],
         };
 
         let init_args = EscrowInitArgs { config };
@@ -287,13 +534,393 @@ mod tests {
         assert!(matches!(escrow.get_state(), EscrowState::Accepted));
 
         // Payer funds
-        escrow.fund(1000);
+        escrow.fund(U512::from(1000));
         assert!(matches!(escrow.get_state(), EscrowState::Funded));
-        assert_eq!(escrow.get_balance(), 1000);
+        assert_eq!(escrow.get_balance(), U512::from(1000));
 
         // Payer releases
         escrow.release();
         assert!(matches!(escrow.get_state(), EscrowState::Released));
-        assert_eq!(escrow.get_balance(), 0);
+        assert_eq!(escrow.get_balance(), U512::zero());
+
+        let invoice = escrow.get_invoice().unwrap();
+        assert!(invoice.accepted_at.is_some());
+        assert!(invoice.funded_at.is_some());
+        assert!(invoice.released_at.is_some());
+    }
+
+    #[test]
+    fn test_history_records_transitions() {
+        let env = odra_test::env();
+        let issuer = env.get_account(0);
+        let payer = env.get_account(1);
+
+        let mut escrow = funded_escrow(&env, vec![]);
+        env.advance_block_time(500);
+        escrow.release();
+
+        assert_eq!(escrow.get_history_len(), 4);
+        let history = escrow.get_history(0, 10);
+        let steps: Vec<_> = history
+            .iter()
+            .map(|t| (t.from.clone(), t.to.clone(), t.actor))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (None, EscrowState::Draft, issuer),
+                (Some(EscrowState::Draft), EscrowState::Accepted, payer),
+                (Some(EscrowState::Accepted), EscrowState::Funded, payer),
+                (Some(EscrowState::Funded), EscrowState::Released, payer),
+            ]
+        );
+        assert_eq!(history[3].timestamp, history[2].timestamp + 500);
+
+        // Pagination
+        assert_eq!(escrow.get_history(1, 2), history[1..3].to_vec());
+        assert_eq!(escrow.get_history(3, 10), history[3..].to_vec());
+        assert!(escrow.get_history(4, 10).is_empty());
+        assert!(escrow.get_history(u32::MAX, u32::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_release_requires_every_condition_in_group() {
+        let env = odra_test::env();
+        let issuer = env.get_account(0);
+        let unlock_at = env.block_time() + 1000;
+
+        let mut escrow = funded_escrow(
+            &env,
+            vec![ConditionGroup {
+                all_of: vec![
+                    ReleaseCondition::PayerApproval,
+                    ReleaseCondition::TimeElapsed { after: unlock_at },
+                ],
+            }],
+        );
+
+        escrow.approve();
+        assert_eq!(
+            escrow.try_release(),
+            Err(EscrowError::ConditionsNotMet.into())
+        );
+        assert_eq!(
+            escrow.get_unmet_conditions(),
+            vec![ConditionGroup {
+                all_of: vec![ReleaseCondition::TimeElapsed { after: unlock_at }],
+            }]
+        );
+
+        env.advance_block_time(1000);
+        env.set_caller(issuer);
+        escrow.release();
+        assert!(matches!(escrow.get_state(), EscrowState::Released));
+        assert_eq!(escrow.get_balance(), U512::zero());
+    }
+
+    #[test]
+    fn test_release_when_any_group_is_met() {
+        let env = odra_test::env();
+        let approvers = vec![env.get_account(2), env.get_account(3), env.get_account(4)];
+        let secret = Bytes::from(b"delivery-code".to_vec());
+        let hash = Digest::hash(secret.as_slice()).value();
+
+        let mut escrow = funded_escrow(
+            &env,
+            vec![
+                ConditionGroup {
+                    all_of: vec![ReleaseCondition::Approvals {
+                        approvers: approvers.clone(),
+                        threshold: 2,
+                    }],
+                },
+                ConditionGroup {
+                    all_of: vec![ReleaseCondition::SecretRevealed { hash }],
+                },
+            ],
+        );
+
+        // The payer's own approval does not satisfy either group
+        assert_eq!(
+            escrow.try_release(),
+            Err(EscrowError::ConditionsNotMet.into())
+        );
+
+        env.set_caller(approvers[0]);
+        escrow.approve();
+        assert_eq!(
+            escrow.try_reveal_secret(Bytes::from(b"wrong".to_vec())),
+            Err(EscrowError::InvalidSecret.into())
+        );
+        escrow.reveal_secret(secret);
+
+        let unmet = escrow.get_unmet_conditions();
+        assert_eq!(unmet[0].all_of.len(), 1);
+        assert!(unmet[1].all_of.is_empty());
+
+        env.set_caller(env.get_account(1));
+        escrow.release();
+        assert!(matches!(escrow.get_state(), EscrowState::Released));
+    }
+
+    #[test]
+    fn test_approvals_threshold_and_attestor() {
+        let env = odra_test::env();
+        let attestor = env.get_account(2);
+        let approvers = vec![env.get_account(3), env.get_account(4)];
+
+        let mut escrow = funded_escrow(
+            &env,
+            vec![ConditionGroup {
+                all_of: vec![
+                    ReleaseCondition::AttestorSigned { attestor },
+                    ReleaseCondition::Approvals {
+                        approvers: approvers.clone(),
+                        threshold: 2,
+                    },
+                ],
+            }],
+        );
+
+        env.set_caller(attestor);
+        escrow.approve();
+        env.set_caller(approvers[0]);
+        escrow.approve();
+        assert_eq!(escrow.get_unmet_conditions()[0].all_of.len(), 1);
+
+        // Only parties may trigger the release
+        assert_eq!(escrow.try_release(), Err(EscrowError::Unauthorized.into()));
+
+        env.set_caller(approvers[1]);
+        escrow.approve();
+        assert!(escrow.get_unmet_conditions()[0].all_of.is_empty());
+
+        env.set_caller(env.get_account(0));
+        escrow.release();
+        assert!(matches!(escrow.get_state(), EscrowState::Released));
+    }
+
+    #[test]
+    fn test_amount_beyond_u64() {
+        let env = odra_test::env();
+        let issuer = env.get_account(0);
+        let payer = env.get_account(1);
+        let amount = U512::from(u64::MAX) * U512::from(1_000_000u64);
+
+        env.set_caller(issuer);
+        let config = EscrowConfig {
+            id: "INV-003".to_string(),
+            description: "Token-denominated invoice".to_string(),
+            amount,
+            currency: Currency {
+                symbol: "WETH".to_string(),
+                decimals: 18,
+            },
+            payer,
+            arbiter: None,
+            due_date: None,
+            release_conditions: vec![],
+        };
+        let mut escrow = Escrow::deploy(&env, EscrowInitArgs { config });
+
+        let invoice = escrow.get_invoice().unwrap();
+        assert_eq!(invoice.amount, amount);
+        assert_eq!(invoice.currency.decimals, 18);
+
+        env.set_caller(payer);
+        escrow.accept();
+        assert_eq!(
+            escrow.try_fund(amount - 1),
+            Err(EscrowError::InsufficientFunds.into())
+        );
+        escrow.fund(amount);
+        assert_eq!(escrow.get_balance(), amount);
+    }
+
+    fn valid_config(env: &HostEnv) -> EscrowConfig {
+        EscrowConfig {
+            id: "INV-004".to_string(),
+            description: "Validated invoice".to_string(),
+            amount: U512::from(1000),
+            currency: Currency::cspr(),
+            payer: env.get_account(1),
+            arbiter: Some(env.get_account(2)),
+            due_date: Some(env.block_time() + 1000),
+            release_conditions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_init_rejects_invalid_config() {
+        let env = odra_test::env();
+        let issuer = env.get_account(0);
+        let payer = env.get_account(1);
+        let approver = env.get_account(3);
+        env.set_caller(issuer);
+
+        let approvals = |approvers: Vec<Address>, threshold: u32| {
+            vec![ConditionGroup {
+                all_of: vec![ReleaseCondition::Approvals {
+                    approvers,
+                    threshold,
+                }],
+            }]
+        };
+
+        let cases = vec![
+            (
+                EscrowConfig {
+                    id: String::new(),
+                    ..valid_config(&env)
+                },
+                EscrowError::EmptyId,
+            ),
+            (
+                EscrowConfig {
+                    id: "X".repeat(MAX_ID_LENGTH + 1),
+                    ..valid_config(&env)
+                },
+                EscrowError::IdTooLong,
+            ),
+            (
+                EscrowConfig {
+                    description: String::new(),
+                    ..valid_config(&env)
+                },
+                EscrowError::EmptyDescription,
+            ),
+            (
+                EscrowConfig {
+                    description: "X".repeat(MAX_DESCRIPTION_LENGTH + 1),
+                    ..valid_config(&env)
+                },
+                EscrowError::DescriptionTooLong,
+            ),
+            (
+                EscrowConfig {
+                    amount: U512::zero(),
+                    ..valid_config(&env)
+                },
+                EscrowError::ZeroAmount,
+            ),
+            (
+                EscrowConfig {
+                    payer: issuer,
+                    ..valid_config(&env)
+                },
+                EscrowError::PayerIsIssuer,
+            ),
+            (
+                EscrowConfig {
+                    arbiter: Some(issuer),
+                    ..valid_config(&env)
+                },
+                EscrowError::ArbiterIsParty,
+            ),
+            (
+                EscrowConfig {
+                    arbiter: Some(payer),
+                    ..valid_config(&env)
+                },
+                EscrowError::ArbiterIsParty,
+            ),
+            (
+                EscrowConfig {
+                    due_date: Some(env.block_time()),
+                    ..valid_config(&env)
+                },
+                EscrowError::DueDateInPast,
+            ),
+            (
+                EscrowConfig {
+                    release_conditions: vec![ConditionGroup::default()],
+                    ..valid_config(&env)
+                },
+                EscrowError::InvalidReleaseCondition,
+            ),
+            (
+                EscrowConfig {
+                    release_conditions: approvals(vec![approver], 0),
+                    ..valid_config(&env)
+                },
+                EscrowError::InvalidReleaseCondition,
+            ),
+            (
+                EscrowConfig {
+                    release_conditions: approvals(vec![approver], 2),
+                    ..valid_config(&env)
+                },
+                EscrowError::InvalidReleaseCondition,
+            ),
+            (
+                EscrowConfig {
+                    release_conditions: approvals(vec![approver, approver], 2),
+                    ..valid_config(&env)
+                },
+                EscrowError::InvalidReleaseCondition,
+            ),
+        ];
+
+        for (config, error) in cases {
+            assert_eq!(
+                Escrow::try_deploy(&env, EscrowInitArgs { config }).err(),
+                Some(error.into())
+            );
+        }
+
+        assert!(Escrow::try_deploy(
+            &env,
+            EscrowInitArgs {
+                config: valid_config(&env)
+            }
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_error_codes_match_core() {
+        for &error in escrow_core::Error::ALL {
+            assert_eq!(EscrowError::from(error) as u16, error.code());
+        }
+    }
+
+    #[test]
+    fn test_summary_snapshot() {
+        let env = odra_test::env();
+        let issuer = env.get_account(0);
+        let payer = env.get_account(1);
+        let arbiter = env.get_account(2);
+
+        env.set_caller(issuer);
+        let config = valid_config(&env);
+        let mut escrow = Escrow::deploy(&env, EscrowInitArgs { config });
+
+        let summary = escrow.get_summary();
+        assert_eq!(summary.state, EscrowState::Draft);
+        assert_eq!(summary.invoice.issuer, issuer);
+        assert_eq!(summary.invoice.payer, payer);
+        assert_eq!(summary.invoice.arbiter, Some(arbiter));
+        assert_eq!(summary.invoice.amount, U512::from(1000));
+        assert_eq!(summary.balance, U512::zero());
+        assert_eq!(summary.dispute, None);
+        assert_eq!(
+            summary.pending_conditions,
+            vec![ConditionGroup {
+                all_of: vec![ReleaseCondition::PayerApproval],
+            }]
+        );
+
+        env.set_caller(payer);
+        escrow.accept();
+        escrow.fund(U512::from(1000));
+        escrow.dispute("Goods not delivered".to_string());
+
+        let summary = escrow.get_summary();
+        assert_eq!(summary.state, EscrowState::Disputed);
+        assert_eq!(summary.balance, U512::from(1000));
+        assert!(summary.invoice.funded_at.is_some());
+        let dispute = summary.dispute.unwrap();
+        assert_eq!(dispute.raised_by, payer);
+        assert_eq!(dispute.reason, "Goods not delivered");
     }
 }
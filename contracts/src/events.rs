@@ -1,5 +1,6 @@
 //! Events emitted by the Escrow contract
 
+use odra::casper_types::U512;
 use odra::prelude::*;
 use odra::prelude::Address;
 
@@ -9,7 +10,9 @@ pub struct EscrowCreated {
     pub escrow_id: String,
     pub issuer: Address,
     pub payer: Address,
-    pub amount: u64,
+    pub amount: U512,
+    pub currency_symbol: String,
+    pub currency_decimals: u8,
 }
 
 /// Emitted when an escrow is accepted by the payer
@@ -24,7 +27,7 @@ pub struct EscrowAccepted {
 pub struct FundsDeposited {
     pub escrow_id: String,
     pub payer: Address,
-    pub amount: u64,
+    pub amount: U512,
 }
 
 /// Emitted when funds are released to the receiver
@@ -32,7 +35,7 @@ pub struct FundsDeposited {
 pub struct FundsReleased {
     pub escrow_id: String,
     pub receiver: Address,
-    pub amount: u64,
+    pub amount: U512,
 }
 
 /// Emitted when an escrow is cancelled
@@ -57,3 +60,17 @@ pub struct DisputeResolved {
     pub resolved_by: Address,
     pub release_to_receiver: bool,
 }
+
+/// Emitted when an address approves the release of funds
+#[odra::event]
+pub struct ReleaseApproved {
+    pub escrow_id: String,
+    pub approver: Address,
+}
+
+/// Emitted when a secret required for release is revealed
+#[odra::event]
+pub struct SecretRevealed {
+    pub escrow_id: String,
+    pub revealed_by: Address,
+}
@@ -11,6 +11,7 @@ extern crate alloc;
 pub mod escrow;
 pub mod events;
 pub mod types;
+pub mod validation;
 
 pub use escrow::Escrow;
 pub use events::*;
@@ -1,5 +1,6 @@
 //! Core types for the Escrow contract
 
+use odra::casper_types::U512;
 use odra::prelude::*;
 use odra::prelude::Address;
 
@@ -22,6 +23,32 @@ pub enum EscrowState {
     Disputed,
 }
 
+impl From<EscrowState> for escrow_core::State {
+    fn from(state: EscrowState) -> Self {
+        match state {
+            EscrowState::Draft => escrow_core::State::Draft,
+            EscrowState::Accepted => escrow_core::State::Accepted,
+            EscrowState::Funded => escrow_core::State::Funded,
+            EscrowState::Released => escrow_core::State::Released,
+            EscrowState::Cancelled => escrow_core::State::Cancelled,
+            EscrowState::Disputed => escrow_core::State::Disputed,
+        }
+    }
+}
+
+impl From<escrow_core::State> for EscrowState {
+    fn from(state: escrow_core::State) -> Self {
+        match state {
+            escrow_core::State::Draft => EscrowState::Draft,
+            escrow_core::State::Accepted => EscrowState::Accepted,
+            escrow_core::State::Funded => EscrowState::Funded,
+            escrow_core::State::Released => EscrowState::Released,
+            escrow_core::State::Cancelled => EscrowState::Cancelled,
+            escrow_core::State::Disputed => EscrowState::Disputed,
+        }
+    }
+}
+
 /// Roles within an escrow
 #[odra::odra_type]
 pub enum Role {
@@ -41,7 +68,9 @@ pub struct Invoice {
     /// Description of goods/services
     pub description: String,
     /// Amount in smallest token unit
-    pub amount: u64,
+    pub amount: U512,
+    /// Currency the amount is denominated in
+    pub currency: Currency,
     /// Invoice issuer address
     pub issuer: Address,
     /// Invoice payer address
@@ -52,6 +81,12 @@ pub struct Invoice {
     pub created_at: u64,
     /// Due date timestamp (optional)
     pub due_date: Option<u64>,
+    /// Timestamp at which the payer accepted the escrow
+    pub accepted_at: Option<u64>,
+    /// Timestamp at which the escrow was funded
+    pub funded_at: Option<u64>,
+    /// Timestamp at which funds were released
+    pub released_at: Option<u64>,
 }
 
 /// Configuration for creating a new escrow
@@ -61,12 +96,101 @@ pub struct EscrowConfig {
     pub id: String,
     /// Description
     pub description: String,
-    /// Amount to be held in escrow
-    pub amount: u64,
+    /// Amount to be held in escrow, in smallest token unit
+    pub amount: U512,
+    /// Currency the amount is denominated in
+    pub currency: Currency,
     /// Payer address
     pub payer: Address,
     /// Optional arbiter for disputes
     pub arbiter: Option<Address>,
     /// Optional due date
     pub due_date: Option<u64>,
+    /// Release rule: funds are released once every condition of at least
+    /// one group is met. An empty list falls back to payer approval.
+    pub release_conditions: Vec<ConditionGroup>,
+}
+
+/// Details of a dispute raised on an escrow
+#[odra::odra_type]
+pub struct DisputeInfo {
+    /// Party that raised the dispute
+    pub raised_by: Address,
+    /// Reason given for the dispute
+    pub reason: String,
+    /// Block time at which the dispute was raised
+    pub raised_at: u64,
+}
+
+/// Complete snapshot of an escrow, returned by `get_summary`
+#[odra::odra_type]
+pub struct EscrowSummary {
+    /// Current state
+    pub state: EscrowState,
+    /// Invoice: parties, amount, currency, timestamps and due date
+    pub invoice: Invoice,
+    /// Amount currently held in escrow
+    pub balance: U512,
+    /// Dispute details, if a dispute was ever raised
+    pub dispute: Option<DisputeInfo>,
+    /// Release conditions that are still unmet, per group
+    pub pending_conditions: Vec<ConditionGroup>,
+}
+
+/// A single entry in an escrow's state-transition history
+#[odra::odra_type]
+pub struct Transition {
+    /// State before the transition, `None` for the creation entry
+    pub from: Option<EscrowState>,
+    /// State after the transition
+    pub to: EscrowState,
+    /// Address that triggered the transition
+    pub actor: Address,
+    /// Block time of the transition
+    pub timestamp: u64,
+}
+
+/// Describes the token an escrow amount is denominated in
+#[odra::odra_type]
+pub struct Currency {
+    /// Ticker symbol, e.g. "CSPR"
+    pub symbol: String,
+    /// Number of decimal places of the smallest unit
+    pub decimals: u8,
+}
+
+impl Currency {
+    /// Native CSPR, denominated in motes (9 decimals)
+    pub fn cspr() -> Self {
+        Self {
+            symbol: "CSPR".to_string(),
+            decimals: 9,
+        }
+    }
+}
+
+/// A single predicate that must hold before funds can be released
+#[odra::odra_type]
+pub enum ReleaseCondition {
+    /// The payer has approved the release
+    PayerApproval,
+    /// The block time has reached the given timestamp
+    TimeElapsed { after: u64 },
+    /// The given attestor has signed off on the release
+    AttestorSigned { attestor: Address },
+    /// At least `threshold` of the listed approvers have approved
+    Approvals {
+        approvers: Vec<Address>,
+        threshold: u32,
+    },
+    /// A preimage of the given blake2b hash has been revealed
+    SecretRevealed { hash: [u8; 32] },
+}
+
+/// Conditions combined with AND: the group holds when all of them are met
+#[odra::odra_type]
+#[derive(Default)]
+pub struct ConditionGroup {
+    /// Conditions that must all be met
+    pub all_of: Vec<ReleaseCondition>,
 }
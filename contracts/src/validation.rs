@@ -0,0 +1,52 @@
+//! Input validation for escrow creation
+
+use escrow_core::{validate_description, validate_due_date, validate_id, validate_parties};
+use odra::prelude::*;
+use odra::prelude::Address;
+
+use crate::escrow::EscrowError;
+use crate::types::{EscrowConfig, ReleaseCondition};
+
+pub use escrow_core::{MAX_DESCRIPTION_LENGTH, MAX_ID_LENGTH};
+
+/// Check an escrow configuration submitted by `issuer` at block time `now`
+pub fn validate_config(config: &EscrowConfig, issuer: Address, now: u64) -> Result<(), EscrowError> {
+    validate_id(&config.id)?;
+    validate_description(&config.description)?;
+
+    if config.amount.is_zero() {
+        return Err(EscrowError::ZeroAmount);
+    }
+    validate_parties(&issuer, &config.payer, config.arbiter.as_ref())?;
+    validate_due_date(config.due_date, now)?;
+
+    for group in &config.release_conditions {
+        if group.all_of.is_empty() {
+            return Err(EscrowError::InvalidReleaseCondition);
+        }
+        for condition in &group.all_of {
+            validate_condition(condition)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_condition(condition: &ReleaseCondition) -> Result<(), EscrowError> {
+    match condition {
+        ReleaseCondition::Approvals {
+            approvers,
+            threshold,
+        } => {
+            let mut distinct = BTreeSet::new();
+            if !approvers.iter().all(|approver| distinct.insert(approver)) {
+                return Err(EscrowError::InvalidReleaseCondition);
+            }
+            if *threshold == 0 || *threshold as usize > approvers.len() {
+                return Err(EscrowError::InvalidReleaseCondition);
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
@@ -17,6 +17,7 @@ import {
   RpcClient,
   Args,
   CLValue,
+  Key,
   Deploy,
   DeployHeader,
   ExecutableDeployItem,
@@ -131,6 +132,7 @@ function getAccountHash(publicKeyHex: string) {
  */
 async function deployEscrowContract(
   deployerPrivateKey: PrivateKey,
+  payerPublicKeyHex: string,
   amountCspr: number,
   description: string
 ): Promise<string> {
@@ -151,15 +153,17 @@ async function deployEscrowContract(
   const amountMotes = BigInt(amountCspr) * BigInt(1_000_000_000);
 
   console.log('=== DEPLOY PARAMETERS ===');
-  console.log('Deployer (issuer/payer):', issuerPublicKey.toHex());
+  console.log('Deployer (issuer):', issuerPublicKey.toHex());
+  console.log('Payer:', payerPublicKeyHex);
   console.log('Amount:', amountCspr, 'CSPR (', amountMotes.toString(), 'motes)');
   console.log('Description:', description);
   console.log('=========================');
 
-  // Build runtime args - simplified for demo (only amount and description)
-  // The contract uses runtime::get_caller() for issuer and payer
+  // Build runtime args. The issuer defaults to the deploying account;
+  // the contract rejects a payer equal to the issuer.
   // IMPORTANT: Contract expects u64, not U512!
   const args = Args.fromMap({
+    'payer': CLValue.newCLKey(Key.newKey(getAccountHash(payerPublicKeyHex).toPrefixedString())),
     'amount': CLValue.newCLUint64(amountMotes),
     'description': CLValue.newCLString(description),
   });
@@ -206,13 +210,13 @@ async function deployEscrowContract(
 async function main() {
   const args = process.argv.slice(2);
 
-  if (args.length < 2) {
-    console.log('Usage: npx tsx scripts/deploy-escrow.ts <amount_cspr> <description>');
-    console.log('Example: npx tsx scripts/deploy-escrow.ts 10 "Invoice for services"');
+  if (args.length < 3) {
+    console.log('Usage: npx tsx scripts/deploy-escrow.ts <payer_public_key> <amount_cspr> <description>');
+    console.log('Example: npx tsx scripts/deploy-escrow.ts 0203abc... 10 "Invoice for services"');
     process.exit(1);
   }
 
-  const [amountStr, ...descParts] = args;
+  const [payerPublicKey, amountStr, ...descParts] = args;
   const amount = parseInt(amountStr, 10);
   const description = descParts.join(' ');
 
@@ -248,6 +252,7 @@ async function main() {
     console.log('\nDeploying escrow contract...');
     const deployHash = await deployEscrowContract(
       deployerKey,
+      payerPublicKey,
       amount,
       description
     );
@@ -1,3 +1,5 @@
+import { ESCROW_ERRORS, ESCROW_ERROR_DESCRIPTIONS } from '@/lib/casper/escrow-codes';
+
 export default function Integration() {
   return (
     <article className="prose animate-fade-in">
@@ -202,7 +204,8 @@ eventStream.start();`}</code></pre>
 
       <h2 id="error-handling">Error Handling</h2>
       <p>
-        The contract returns specific error codes for different failure scenarios:
+        Failed calls revert with <code>User error: &lt;code&gt;</code>. Both contracts use the
+        same codes:
       </p>
       <div className="not-prose overflow-x-auto my-6">
         <table className="w-full text-sm">
@@ -214,36 +217,13 @@ eventStream.start();`}</code></pre>
             </tr>
           </thead>
           <tbody>
-            <tr className="border-b border-[var(--border)]">
-              <td className="py-3 px-4"><code>1</code></td>
-              <td className="py-3 px-4 text-[var(--muted)]">NotPayer</td>
-              <td className="py-3 px-4 text-[var(--muted)]">Caller is not the designated payer</td>
-            </tr>
-            <tr className="border-b border-[var(--border)]">
-              <td className="py-3 px-4"><code>2</code></td>
-              <td className="py-3 px-4 text-[var(--muted)]">NotIssuer</td>
-              <td className="py-3 px-4 text-[var(--muted)]">Caller is not the designated issuer</td>
-            </tr>
-            <tr className="border-b border-[var(--border)]">
-              <td className="py-3 px-4"><code>3</code></td>
-              <td className="py-3 px-4 text-[var(--muted)]">NotArbiter</td>
-              <td className="py-3 px-4 text-[var(--muted)]">Caller is not the designated arbiter</td>
-            </tr>
-            <tr className="border-b border-[var(--border)]">
-              <td className="py-3 px-4"><code>4</code></td>
-              <td className="py-3 px-4 text-[var(--muted)]">InvalidState</td>
-              <td className="py-3 px-4 text-[var(--muted)]">Action not allowed in current state</td>
-            </tr>
-            <tr className="border-b border-[var(--border)]">
-              <td className="py-3 px-4"><code>5</code></td>
-              <td className="py-3 px-4 text-[var(--muted)]">InsufficientFunds</td>
-              <td className="py-3 px-4 text-[var(--muted)]">Deposit less than invoice amount</td>
-            </tr>
-            <tr>
-              <td className="py-3 px-4"><code>6</code></td>
-              <td className="py-3 px-4 text-[var(--muted)]">NoArbiter</td>
-              <td className="py-3 px-4 text-[var(--muted)]">No arbiter set for dispute resolution</td>
-            </tr>
+            {Object.entries(ESCROW_ERRORS).map(([name, code]) => (
+              <tr key={code} className="border-b border-[var(--border)] last:border-0">
+                <td className="py-3 px-4"><code>{code}</code></td>
+                <td className="py-3 px-4 text-[var(--muted)]">{name}</td>
+                <td className="py-3 px-4 text-[var(--muted)]">{ESCROW_ERROR_DESCRIPTIONS[code]}</td>
+              </tr>
+            ))}
           </tbody>
         </table>
       </div>
@@ -0,0 +1,115 @@
+// Generated by contracts-core/src/bin/codegen.rs from escrow-core. Do not edit.
+
+/** Escrow state codes, as returned by `get_state` */
+export const ESCROW_STATES = {
+  Draft: 0,
+  Accepted: 1,
+  Funded: 2,
+  Released: 3,
+  Cancelled: 4,
+  Disputed: 5,
+} as const;
+
+export type EscrowStateName = keyof typeof ESCROW_STATES;
+export type EscrowStateCode = (typeof ESCROW_STATES)[EscrowStateName];
+
+/** Contract error codes, reported as `User error: <code>` */
+export const ESCROW_ERRORS = {
+  Unauthorized: 1,
+  InvalidState: 2,
+  InsufficientFunds: 3,
+  NotFound: 4,
+  ConditionsNotMet: 5,
+  InvalidSecret: 6,
+  ZeroAmount: 7,
+  PayerIsIssuer: 8,
+  ArbiterIsParty: 9,
+  DueDateInPast: 10,
+  EmptyId: 11,
+  IdTooLong: 12,
+  EmptyDescription: 13,
+  DescriptionTooLong: 14,
+  InvalidReleaseCondition: 15,
+  TransferFailed: 16,
+  NothingToSweep: 17,
+  PayoutPurseRequired: 18,
+  InvalidParty: 19,
+  UnexpectedPayoutPurse: 20,
+} as const;
+
+export type EscrowErrorName = keyof typeof ESCROW_ERRORS;
+export type EscrowErrorCode = (typeof ESCROW_ERRORS)[EscrowErrorName];
+
+export const ESCROW_ERROR_DESCRIPTIONS: Record<EscrowErrorCode, string> = {
+  1: "Caller is not authorized to perform this action",
+  2: "Invalid state for this operation",
+  3: "Insufficient funds provided",
+  4: "Escrow not found",
+  5: "Release conditions are not yet satisfied",
+  6: "Revealed secret does not match any release condition",
+  7: "Escrow amount must be greater than zero",
+  8: "Payer must differ from the issuer",
+  9: "Arbiter must not be the issuer or the payer",
+  10: "Due date must lie in the future",
+  11: "Escrow id must not be empty",
+  12: "Escrow id exceeds the maximum length",
+  13: "Description must not be empty",
+  14: "Description exceeds the maximum length",
+  15: "Release condition is malformed (empty group, bad threshold, duplicate approvers)",
+  16: "Moving funds out of or into the escrow purse failed",
+  17: "Escrow purse holds no funds beyond the recorded balance",
+  18: "A contract party must pass a purse it can receive payouts into",
+  19: "Party must be an account or a contract package",
+  20: "Accounts are paid into their main purse and take no payout purse",
+};
+
+/** Entry point names of the Odra and native contracts */
+export const ENTRY_POINTS = {
+  INIT: "init",
+  CREATE_ESCROW: "create_escrow",
+  ACCEPT: "accept",
+  FUND: "fund",
+  APPROVE: "approve",
+  REVEAL_SECRET: "reveal_secret",
+  RELEASE: "release",
+  CANCEL: "cancel",
+  DISPUTE: "dispute",
+  RESOLVE_DISPUTE: "resolve_dispute",
+  GET_STATE: "get_state",
+  GET_BALANCE: "get_balance",
+  GET_SUMMARY: "get_summary",
+  GET_INVOICE: "get_invoice",
+  GET_RELEASE_CONDITIONS: "get_release_conditions",
+  GET_UNMET_CONDITIONS: "get_unmet_conditions",
+  GET_HISTORY_LEN: "get_history_len",
+  GET_HISTORY: "get_history",
+  GET_ESCROW_COUNT: "get_escrow_count",
+  GET_PURSE_BALANCES: "get_purse_balances",
+  SWEEP: "sweep",
+} as const;
+
+export type EntryPointName = (typeof ENTRY_POINTS)[keyof typeof ENTRY_POINTS];
+
+/**
+ * Fields of the native `get_summary` value with their CL types. The value is
+ * typed `Any` and holds each field's bytes in this order, with no length prefix.
+ */
+export const NATIVE_SUMMARY_LAYOUT = [
+  { field: "escrow_id", clType: "U64" },
+  { field: "state", clType: "U8" },
+  { field: "issuer", clType: "Key" },
+  { field: "payer", clType: "Key" },
+  { field: "arbiter", clType: "Option<Key>" },
+  { field: "amount", clType: "U512" },
+  { field: "balance", clType: "U512" },
+  { field: "description", clType: "String" },
+  { field: "due_date", clType: "Option<U64>" },
+  { field: "created_at", clType: "U64" },
+  { field: "accepted_at", clType: "Option<U64>" },
+  { field: "funded_at", clType: "Option<U64>" },
+  { field: "released_at", clType: "Option<U64>" },
+  { field: "dispute", clType: "Option<(Key, String, U64)>" },
+  { field: "refunded", clType: "Bool" },
+  { field: "issuer_purse", clType: "Option<URef>" },
+  { field: "payer_purse", clType: "Option<URef>" },
+] as const;
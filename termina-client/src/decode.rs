@@ -0,0 +1,102 @@
+//! Decoding of contract return values and revert codes
+
+use std::fmt;
+
+use casper_types::{
+    bytesrepr::{self, FromBytes},
+    ApiError, CLValue,
+};
+use escrow_core::{Error, State};
+
+/// A returned value that doesn't decode as expected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes don't hold a value of the expected type
+    Bytes(bytesrepr::Error),
+    /// A state code outside the escrow state machine
+    UnknownState(u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Bytes(error) => write!(f, "malformed value: {}", error),
+            DecodeError::UnknownState(code) => write!(f, "unknown escrow state {}", code),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<bytesrepr::Error> for DecodeError {
+    fn from(error: bytesrepr::Error) -> Self {
+        DecodeError::Bytes(error)
+    }
+}
+
+/// Decode `T` from all of `bytes`
+pub(crate) fn from_exact_bytes<T: FromBytes>(bytes: &[u8]) -> Result<T, DecodeError> {
+    Ok(bytesrepr::deserialize_from_slice(bytes)?)
+}
+
+pub(crate) fn state_from_u8(code: u8) -> Result<State, DecodeError> {
+    State::from_u8(code).ok_or(DecodeError::UnknownState(code))
+}
+
+/// Decode a `get_state` result of either contract. Odra returns its
+/// `EscrowState` enum and the native contract a `u8`; both are the state code.
+pub fn decode_state(value: &CLValue) -> Result<State, DecodeError> {
+    state_from_u8(from_exact_bytes(value.inner_bytes())?)
+}
+
+/// Escrow error a failed call reverted with, or `None` for any other failure
+pub fn decode_error(error: ApiError) -> Option<Error> {
+    match error {
+        ApiError::User(code) => Error::from_code(code),
+        _ => None,
+    }
+}
+
+/// Escrow error named in a node's execution error message, such as
+/// `User error: 4`
+pub fn decode_error_message(message: &str) -> Option<Error> {
+    let (_, code) = message.split_once("User error: ")?;
+    let digits = code
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(code.len());
+    code[..digits].parse().ok().and_then(Error::from_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_state() {
+        for state in State::ALL {
+            let value = CLValue::from_t(state as u8).unwrap();
+            assert_eq!(decode_state(&value), Ok(state));
+        }
+        let value = CLValue::from_t(6u8).unwrap();
+        assert_eq!(decode_state(&value), Err(DecodeError::UnknownState(6)));
+        let value = CLValue::from_t(2u64).unwrap();
+        assert_eq!(
+            decode_state(&value),
+            Err(DecodeError::Bytes(bytesrepr::Error::LeftOverBytes))
+        );
+    }
+
+    #[test]
+    fn test_decode_error() {
+        assert_eq!(
+            decode_error(ApiError::User(5)),
+            Some(Error::ConditionsNotMet)
+        );
+        assert_eq!(decode_error(ApiError::User(0)), None);
+        assert_eq!(decode_error(ApiError::InvalidArgument), None);
+
+        let message = ApiError::User(Error::NotFound.code()).to_string();
+        assert_eq!(decode_error_message(&message), Some(Error::NotFound));
+        assert_eq!(decode_error_message("ApiError::InvalidArgument [3]"), None);
+    }
+}
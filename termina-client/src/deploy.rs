@@ -0,0 +1,125 @@
+//! Deploy assembly shared by both contract flavours
+
+use std::fmt;
+
+use casper_types::{
+    bytesrepr::ToBytes, Deploy, DeployHash, DeployHeader, Digest, ExecutableDeployItem, PublicKey,
+    TimeDiff, Timestamp, U512,
+};
+
+/// Default time a deploy stays valid for
+const DEFAULT_TTL_MILLIS: u64 = 30 * 60 * 1000;
+/// Default gas price multiplier
+const DEFAULT_GAS_PRICE: u64 = 1;
+
+/// Arguments a builder can't encode for the contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The amount doesn't fit the `u64` the argument is declared as
+    AmountOutOfRange(U512),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::AmountOutOfRange(amount) => {
+                write!(f, "amount {} exceeds the u64 the contract takes", amount)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Account and network a deploy is built for
+#[derive(Clone, Debug)]
+pub struct DeployParams {
+    account: PublicKey,
+    chain_name: String,
+    timestamp: Timestamp,
+    ttl: TimeDiff,
+    gas_price: u64,
+}
+
+impl DeployParams {
+    /// Deploys from `account` on `chain_name`, timestamped now and valid for 30 minutes
+    pub fn new(account: PublicKey, chain_name: impl Into<String>) -> Self {
+        DeployParams {
+            account,
+            chain_name: chain_name.into(),
+            timestamp: Timestamp::now(),
+            ttl: TimeDiff::from_millis(DEFAULT_TTL_MILLIS),
+            gas_price: DEFAULT_GAS_PRICE,
+        }
+    }
+
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: TimeDiff) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+}
+
+/// Session code of one escrow operation and the payment it needs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowTransaction {
+    session: ExecutableDeployItem,
+    payment: U512,
+}
+
+impl EscrowTransaction {
+    pub(crate) fn new(session: ExecutableDeployItem, payment: u64) -> Self {
+        EscrowTransaction {
+            session,
+            payment: U512::from(payment),
+        }
+    }
+
+    /// Replace the default payment, in motes
+    pub fn with_payment(mut self, payment: U512) -> Self {
+        self.payment = payment;
+        self
+    }
+
+    pub fn session(&self) -> &ExecutableDeployItem {
+        &self.session
+    }
+
+    pub fn payment(&self) -> U512 {
+        self.payment
+    }
+
+    /// Unsigned deploy with standard payment
+    pub fn build(self, params: &DeployParams) -> Deploy {
+        let payment = ExecutableDeployItem::new_standard_payment(self.payment);
+        let mut body = payment.to_bytes().expect("payment code should serialize");
+        body.extend(
+            self.session
+                .to_bytes()
+                .expect("session code should serialize"),
+        );
+
+        let header = DeployHeader::new(
+            params.account.clone(),
+            params.timestamp,
+            params.ttl,
+            params.gas_price,
+            Digest::hash(body),
+            Vec::new(),
+            params.chain_name.clone(),
+        );
+        let hash = DeployHash::new(Digest::hash(
+            header.to_bytes().expect("deploy header should serialize"),
+        ));
+        Deploy::new(hash, header, payment, self.session)
+    }
+}
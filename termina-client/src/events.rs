@@ -0,0 +1,465 @@
+//! Events of both contract flavours
+//!
+//! Both contracts emit in the Casper Event Standard (CES) layout: the event
+//! name prefixed with `event_`, then the fields in order. Parties are `Key`s,
+//! which is also how Odra serializes an `Address`. `ReleaseApproved` and
+//! `SecretRevealed` come only from the Odra contract, `FundsSwept` only from
+//! the native one.
+
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    Key, U512,
+};
+
+use crate::decode::{from_exact_bytes, DecodeError};
+use crate::State;
+
+/// Prefix CES puts before every event name
+const EVENT_PREFIX: &str = "event_";
+
+/// A decoded escrow event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowEvent {
+    EscrowCreated {
+        escrow_id: String,
+        issuer: Key,
+        payer: Key,
+        amount: U512,
+        currency_symbol: String,
+        currency_decimals: u8,
+    },
+    EscrowAccepted {
+        escrow_id: String,
+        payer: Key,
+    },
+    FundsDeposited {
+        escrow_id: String,
+        payer: Key,
+        amount: U512,
+    },
+    /// Funds leaving the escrow: a release, a refund on cancel or the payout
+    /// of a resolved dispute
+    FundsReleased {
+        escrow_id: String,
+        receiver: Key,
+        amount: U512,
+    },
+    EscrowCancelled {
+        escrow_id: String,
+        cancelled_by: Key,
+    },
+    DisputeRaised {
+        escrow_id: String,
+        raised_by: Key,
+        reason: String,
+    },
+    DisputeResolved {
+        escrow_id: String,
+        resolved_by: Key,
+        release_to_receiver: bool,
+    },
+    ReleaseApproved {
+        escrow_id: String,
+        approver: Key,
+    },
+    SecretRevealed {
+        escrow_id: String,
+        revealed_by: Key,
+    },
+    FundsSwept {
+        escrow_id: String,
+        recipient: Key,
+        amount: U512,
+    },
+}
+
+impl EscrowEvent {
+    /// Every event name, as [`name`](EscrowEvent::name) gives it
+    pub const NAMES: [&'static str; 10] = [
+        "EscrowCreated",
+        "EscrowAccepted",
+        "FundsDeposited",
+        "FundsReleased",
+        "EscrowCancelled",
+        "DisputeRaised",
+        "DisputeResolved",
+        "ReleaseApproved",
+        "SecretRevealed",
+        "FundsSwept",
+    ];
+
+    /// Event name as the contracts emit it, without the CES prefix
+    pub fn name(&self) -> &'static str {
+        match self {
+            EscrowEvent::EscrowCreated { .. } => "EscrowCreated",
+            EscrowEvent::EscrowAccepted { .. } => "EscrowAccepted",
+            EscrowEvent::FundsDeposited { .. } => "FundsDeposited",
+            EscrowEvent::FundsReleased { .. } => "FundsReleased",
+            EscrowEvent::EscrowCancelled { .. } => "EscrowCancelled",
+            EscrowEvent::DisputeRaised { .. } => "DisputeRaised",
+            EscrowEvent::DisputeResolved { .. } => "DisputeResolved",
+            EscrowEvent::ReleaseApproved { .. } => "ReleaseApproved",
+            EscrowEvent::SecretRevealed { .. } => "SecretRevealed",
+            EscrowEvent::FundsSwept { .. } => "FundsSwept",
+        }
+    }
+
+    /// Escrow the event is about: the invoice id of an Odra escrow, the
+    /// escrow id of a native one
+    pub fn escrow_id(&self) -> &str {
+        match self {
+            EscrowEvent::EscrowCreated { escrow_id, .. }
+            | EscrowEvent::EscrowAccepted { escrow_id, .. }
+            | EscrowEvent::FundsDeposited { escrow_id, .. }
+            | EscrowEvent::FundsReleased { escrow_id, .. }
+            | EscrowEvent::EscrowCancelled { escrow_id, .. }
+            | EscrowEvent::DisputeRaised { escrow_id, .. }
+            | EscrowEvent::DisputeResolved { escrow_id, .. }
+            | EscrowEvent::ReleaseApproved { escrow_id, .. }
+            | EscrowEvent::SecretRevealed { escrow_id, .. }
+            | EscrowEvent::FundsSwept { escrow_id, .. } => escrow_id,
+        }
+    }
+
+    /// State the escrow is in after this event, given the state before it,
+    /// or `None` if the event doesn't change the state. `FundsReleased`
+    /// only moves a funded escrow; after a cancel or a resolved dispute it
+    /// records the payout.
+    pub fn next_state(&self, current: Option<State>) -> Option<State> {
+        let next = match self {
+            EscrowEvent::EscrowCreated { .. } => State::Draft,
+            EscrowEvent::EscrowAccepted { .. } => State::Accepted,
+            EscrowEvent::FundsDeposited { .. } => State::Funded,
+            EscrowEvent::FundsReleased { .. } if current == Some(State::Funded) => State::Released,
+            EscrowEvent::EscrowCancelled { .. } => State::Cancelled,
+            EscrowEvent::DisputeRaised { .. } => State::Disputed,
+            EscrowEvent::DisputeResolved { .. } => State::Released,
+            _ => return None,
+        };
+        (current != Some(next)).then_some(next)
+    }
+}
+
+/// Decode the bytes of an emitted event
+pub fn decode_event(bytes: &[u8]) -> Result<EscrowEvent, DecodeError> {
+    from_exact_bytes(bytes)
+}
+
+impl FromBytes for EscrowEvent {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (name, bytes) = String::from_bytes(bytes)?;
+        let (escrow_id, bytes) = String::from_bytes(bytes)?;
+        let name = name
+            .strip_prefix(EVENT_PREFIX)
+            .ok_or(bytesrepr::Error::Formatting)?;
+        match name {
+            "EscrowCreated" => {
+                let (issuer, bytes) = Key::from_bytes(bytes)?;
+                let (payer, bytes) = Key::from_bytes(bytes)?;
+                let (amount, bytes) = U512::from_bytes(bytes)?;
+                let (currency_symbol, bytes) = String::from_bytes(bytes)?;
+                let (currency_decimals, bytes) = u8::from_bytes(bytes)?;
+                let event = EscrowEvent::EscrowCreated {
+                    escrow_id,
+                    issuer,
+                    payer,
+                    amount,
+                    currency_symbol,
+                    currency_decimals,
+                };
+                Ok((event, bytes))
+            }
+            "EscrowAccepted" => {
+                let (payer, bytes) = Key::from_bytes(bytes)?;
+                Ok((EscrowEvent::EscrowAccepted { escrow_id, payer }, bytes))
+            }
+            "FundsDeposited" => {
+                let (payer, bytes) = Key::from_bytes(bytes)?;
+                let (amount, bytes) = U512::from_bytes(bytes)?;
+                let event = EscrowEvent::FundsDeposited {
+                    escrow_id,
+                    payer,
+                    amount,
+                };
+                Ok((event, bytes))
+            }
+            "FundsReleased" => {
+                let (receiver, bytes) = Key::from_bytes(bytes)?;
+                let (amount, bytes) = U512::from_bytes(bytes)?;
+                let event = EscrowEvent::FundsReleased {
+                    escrow_id,
+                    receiver,
+                    amount,
+                };
+                Ok((event, bytes))
+            }
+            "EscrowCancelled" => {
+                let (cancelled_by, bytes) = Key::from_bytes(bytes)?;
+                let event = EscrowEvent::EscrowCancelled {
+                    escrow_id,
+                    cancelled_by,
+                };
+                Ok((event, bytes))
+            }
+            "DisputeRaised" => {
+                let (raised_by, bytes) = Key::from_bytes(bytes)?;
+                let (reason, bytes) = String::from_bytes(bytes)?;
+                let event = EscrowEvent::DisputeRaised {
+                    escrow_id,
+                    raised_by,
+                    reason,
+                };
+                Ok((event, bytes))
+            }
+            "DisputeResolved" => {
+                let (resolved_by, bytes) = Key::from_bytes(bytes)?;
+                let (release_to_receiver, bytes) = bool::from_bytes(bytes)?;
+                let event = EscrowEvent::DisputeResolved {
+                    escrow_id,
+                    resolved_by,
+                    release_to_receiver,
+                };
+                Ok((event, bytes))
+            }
+            "ReleaseApproved" => {
+                let (approver, bytes) = Key::from_bytes(bytes)?;
+                Ok((
+                    EscrowEvent::ReleaseApproved {
+                        escrow_id,
+                        approver,
+                    },
+                    bytes,
+                ))
+            }
+            "SecretRevealed" => {
+                let (revealed_by, bytes) = Key::from_bytes(bytes)?;
+                let event = EscrowEvent::SecretRevealed {
+                    escrow_id,
+                    revealed_by,
+                };
+                Ok((event, bytes))
+            }
+            "FundsSwept" => {
+                let (recipient, bytes) = Key::from_bytes(bytes)?;
+                let (amount, bytes) = U512::from_bytes(bytes)?;
+                let event = EscrowEvent::FundsSwept {
+                    escrow_id,
+                    recipient,
+                    amount,
+                };
+                Ok((event, bytes))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+impl ToBytes for EscrowEvent {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = Vec::new();
+        buffer.extend(format!("{}{}", EVENT_PREFIX, self.name()).to_bytes()?);
+        buffer.extend(self.escrow_id().to_bytes()?);
+        match self {
+            EscrowEvent::EscrowCreated {
+                issuer,
+                payer,
+                amount,
+                currency_symbol,
+                currency_decimals,
+                ..
+            } => {
+                buffer.extend(issuer.to_bytes()?);
+                buffer.extend(payer.to_bytes()?);
+                buffer.extend(amount.to_bytes()?);
+                buffer.extend(currency_symbol.to_bytes()?);
+                buffer.extend(currency_decimals.to_bytes()?);
+            }
+            EscrowEvent::EscrowAccepted { payer: party, .. }
+            | EscrowEvent::EscrowCancelled {
+                cancelled_by: party,
+                ..
+            }
+            | EscrowEvent::ReleaseApproved {
+                approver: party, ..
+            }
+            | EscrowEvent::SecretRevealed {
+                revealed_by: party, ..
+            } => buffer.extend(party.to_bytes()?),
+            EscrowEvent::FundsDeposited {
+                payer: party,
+                amount,
+                ..
+            }
+            | EscrowEvent::FundsReleased {
+                receiver: party,
+                amount,
+                ..
+            }
+            | EscrowEvent::FundsSwept {
+                recipient: party,
+                amount,
+                ..
+            } => {
+                buffer.extend(party.to_bytes()?);
+                buffer.extend(amount.to_bytes()?);
+            }
+            EscrowEvent::DisputeRaised {
+                raised_by, reason, ..
+            } => {
+                buffer.extend(raised_by.to_bytes()?);
+                buffer.extend(reason.to_bytes()?);
+            }
+            EscrowEvent::DisputeResolved {
+                resolved_by,
+                release_to_receiver,
+                ..
+            } => {
+                buffer.extend(resolved_by.to_bytes()?);
+                buffer.extend(release_to_receiver.to_bytes()?);
+            }
+        }
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.to_bytes().map(|bytes| bytes.len()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::account::AccountHash;
+
+    use super::*;
+
+    fn account(seed: u8) -> Key {
+        Key::Account(AccountHash::new([seed; 32]))
+    }
+
+    fn events() -> Vec<EscrowEvent> {
+        let escrow_id = || "INV-001".to_string();
+        vec![
+            EscrowEvent::EscrowCreated {
+                escrow_id: escrow_id(),
+                issuer: account(1),
+                payer: account(2),
+                amount: U512::from(5_000),
+                currency_symbol: "CSPR".to_string(),
+                currency_decimals: 9,
+            },
+            EscrowEvent::EscrowAccepted {
+                escrow_id: escrow_id(),
+                payer: account(2),
+            },
+            EscrowEvent::FundsDeposited {
+                escrow_id: escrow_id(),
+                payer: account(2),
+                amount: U512::from(5_000),
+            },
+            EscrowEvent::ReleaseApproved {
+                escrow_id: escrow_id(),
+                approver: account(2),
+            },
+            EscrowEvent::SecretRevealed {
+                escrow_id: escrow_id(),
+                revealed_by: account(4),
+            },
+            EscrowEvent::DisputeRaised {
+                escrow_id: escrow_id(),
+                raised_by: account(1),
+                reason: "Late delivery".to_string(),
+            },
+            EscrowEvent::DisputeResolved {
+                escrow_id: escrow_id(),
+                resolved_by: account(3),
+                release_to_receiver: false,
+            },
+            EscrowEvent::FundsReleased {
+                escrow_id: escrow_id(),
+                receiver: account(2),
+                amount: U512::from(5_000),
+            },
+            EscrowEvent::EscrowCancelled {
+                escrow_id: escrow_id(),
+                cancelled_by: account(1),
+            },
+            EscrowEvent::FundsSwept {
+                escrow_id: escrow_id(),
+                recipient: account(1),
+                amount: U512::from(7),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let names: Vec<_> = events().iter().map(EscrowEvent::name).collect();
+        assert!(EscrowEvent::NAMES.iter().all(|name| names.contains(name)));
+        for event in events() {
+            let bytes = event.to_bytes().unwrap();
+            assert_eq!(bytes.len(), event.serialized_length());
+            assert_eq!(decode_event(&bytes), Ok(event));
+        }
+    }
+
+    #[test]
+    fn test_decode_ces_layout() {
+        let mut bytes = "event_EscrowAccepted".to_string().to_bytes().unwrap();
+        bytes.extend("7".to_string().to_bytes().unwrap());
+        bytes.extend(account(2).to_bytes().unwrap());
+        assert_eq!(
+            decode_event(&bytes),
+            Ok(EscrowEvent::EscrowAccepted {
+                escrow_id: "7".to_string(),
+                payer: account(2),
+            })
+        );
+
+        let unprefixed = EscrowEvent::EscrowAccepted {
+            escrow_id: "7".to_string(),
+            payer: account(2),
+        }
+        .to_bytes()
+        .unwrap()[6..]
+            .to_vec();
+        assert!(decode_event(&unprefixed).is_err());
+        let mut unknown = "event_Unknown".to_string().to_bytes().unwrap();
+        unknown.extend("7".to_string().to_bytes().unwrap());
+        assert_eq!(
+            decode_event(&unknown),
+            Err(DecodeError::Bytes(bytesrepr::Error::Formatting))
+        );
+    }
+
+    #[test]
+    fn test_next_state() {
+        let mut state = None;
+        let mut states = Vec::new();
+        for event in events() {
+            if let Some(next) = event.next_state(state) {
+                state = Some(next);
+                states.push(next);
+            }
+        }
+        // The payout after the dispute and the cancel of a settled escrow are
+        // replayed as they come, so only state-changing events show up
+        assert_eq!(
+            states,
+            [
+                State::Draft,
+                State::Accepted,
+                State::Funded,
+                State::Disputed,
+                State::Released,
+                State::Cancelled,
+            ]
+        );
+
+        let release = &events()[7];
+        assert_eq!(
+            release.next_state(Some(State::Funded)),
+            Some(State::Released)
+        );
+        assert_eq!(release.next_state(Some(State::Cancelled)), None);
+    }
+}
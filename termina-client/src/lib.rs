@@ -0,0 +1,29 @@
+//! Client SDK for Termina escrows
+//!
+//! Builds the deploys that install and drive both contract flavours, and
+//! decodes what they return:
+//!
+//! - [`odra`]: one Odra contract per escrow, addressed by its package hash
+//! - [`native`]: many escrows in one installed native contract, addressed by
+//!   its contract hash and an escrow id
+//!
+//! Every builder returns an [`EscrowTransaction`], which becomes an unsigned
+//! [`Deploy`](casper_types::Deploy) for the account and network in
+//! [`DeployParams`]. Deploys run on Casper 1.x and 2.0 nodes alike. Sign them
+//! with `Deploy::sign` or hand them to a wallet.
+//!
+//! [`events`] decodes the events both contracts emit, for indexers and
+//! notifiers following a node's event stream.
+//!
+//! States and error codes come from `escrow-core`, so they always match the
+//! contracts.
+
+mod decode;
+mod deploy;
+pub mod events;
+pub mod native;
+pub mod odra;
+
+pub use decode::{decode_error, decode_error_message, decode_state, DecodeError};
+pub use deploy::{BuildError, DeployParams, EscrowTransaction};
+pub use escrow_core::{Error, State};
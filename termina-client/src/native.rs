@@ -0,0 +1,277 @@
+//! Native escrow contract: many escrows in one installed contract
+//!
+//! Entry points are called on the contract hash with an escrow id. Funding
+//! needs a purse, which only session code can hand over, so `fund` and
+//! `create_and_fund` run the contract's session binaries; pass in their
+//! bytes from `contracts-native/target/wasm32-unknown-unknown/release`.
+
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes},
+    contracts::ContractHash,
+    runtime_args, CLValue, ExecutableDeployItem, Key, RuntimeArgs, URef, U512,
+};
+use escrow_core::{entry_points, State};
+
+use crate::decode::{from_exact_bytes, state_from_u8, DecodeError};
+use crate::{BuildError, EscrowTransaction};
+
+/// Default payment for installing the contract, in motes
+pub const INSTALL_PAYMENT: u64 = 150_000_000_000;
+/// Default payment for an entry point call, in motes
+pub const CALL_PAYMENT: u64 = 5_000_000_000;
+/// Default payment for the funding sessions, in motes
+pub const SESSION_PAYMENT: u64 = 10_000_000_000;
+
+const ARG_ESCROW_CONTRACT: &str = "escrow_contract";
+const ARG_ESCROW_ID: &str = "escrow_id";
+
+/// Terms of a new escrow. Parties are passed alongside.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowTerms {
+    pub amount: U512,
+    pub description: String,
+    pub arbiter: Option<Key>,
+    /// Block time in milliseconds after which payment is due
+    pub due_date: Option<u64>,
+}
+
+impl EscrowTerms {
+    pub fn new(amount: U512, description: impl Into<String>) -> Self {
+        EscrowTerms {
+            amount,
+            description: description.into(),
+            arbiter: None,
+            due_date: None,
+        }
+    }
+
+    pub fn with_arbiter(mut self, arbiter: Key) -> Self {
+        self.arbiter = Some(arbiter);
+        self
+    }
+
+    pub fn with_due_date(mut self, due_date: u64) -> Self {
+        self.due_date = Some(due_date);
+        self
+    }
+
+    /// Optional arguments are omitted when unset, as the contract expects
+    fn insert_into(&self, args: &mut RuntimeArgs) {
+        args.insert("description", self.description.clone())
+            .unwrap();
+        if let Some(arbiter) = self.arbiter {
+            args.insert("arbiter", arbiter).unwrap();
+        }
+        if let Some(due_date) = self.due_date {
+            args.insert("due_date", due_date).unwrap();
+        }
+    }
+}
+
+/// An installed native escrow contract
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NativeEscrow {
+    contract_hash: ContractHash,
+}
+
+impl NativeEscrow {
+    pub fn new(contract_hash: ContractHash) -> Self {
+        NativeEscrow { contract_hash }
+    }
+
+    pub fn contract_hash(&self) -> ContractHash {
+        self.contract_hash
+    }
+
+    /// Install the contract from `escrow.wasm`, without creating an escrow
+    pub fn install(wasm: Bytes) -> EscrowTransaction {
+        let session = ExecutableDeployItem::new_module_bytes(wasm, RuntimeArgs::new());
+        EscrowTransaction::new(session, INSTALL_PAYMENT)
+    }
+
+    /// Install the contract from `escrow.wasm` and create escrow 0 with the
+    /// installing account as issuer. The installer takes the amount as a
+    /// `u64`, so larger amounts are rejected; create those with `create_escrow`.
+    pub fn install_with_escrow(
+        wasm: Bytes,
+        payer: Key,
+        terms: &EscrowTerms,
+    ) -> Result<EscrowTransaction, BuildError> {
+        let amount =
+            u64::try_from(terms.amount).map_err(|_| BuildError::AmountOutOfRange(terms.amount))?;
+        let mut args = runtime_args! {
+            "payer" => payer,
+            "amount" => amount,
+        };
+        terms.insert_into(&mut args);
+        let session = ExecutableDeployItem::new_module_bytes(wasm, args);
+        Ok(EscrowTransaction::new(session, INSTALL_PAYMENT))
+    }
+
+    /// Create an escrow with the sending account as issuer
+    pub fn create_escrow(&self, payer: Key, terms: &EscrowTerms) -> EscrowTransaction {
+        let mut args = runtime_args! {
+            "payer" => payer,
+            "amount" => terms.amount,
+        };
+        terms.insert_into(&mut args);
+        self.call(entry_points::CREATE_ESCROW, args)
+    }
+
+    /// Create, accept and fund an escrow in one deploy with `create_and_fund.wasm`,
+    /// with the sending account as payer. The issuer must be an account.
+    pub fn create_and_fund(
+        &self,
+        wasm: Bytes,
+        issuer: Key,
+        terms: &EscrowTerms,
+    ) -> EscrowTransaction {
+        let mut args = runtime_args! {
+            ARG_ESCROW_CONTRACT => self.contract_hash,
+            "issuer" => issuer,
+            "amount" => terms.amount,
+        };
+        terms.insert_into(&mut args);
+        let session = ExecutableDeployItem::new_module_bytes(wasm, args);
+        EscrowTransaction::new(session, SESSION_PAYMENT)
+    }
+
+    /// Accept the escrow terms, as the payer
+    pub fn accept(&self, escrow_id: u64) -> EscrowTransaction {
+        self.call(entry_points::ACCEPT, id_args(escrow_id))
+    }
+
+    /// Fund the escrow from the payer's main purse with `fund_session.wasm`
+    pub fn fund(&self, wasm: Bytes, escrow_id: u64, amount: U512) -> EscrowTransaction {
+        let args = runtime_args! {
+            ARG_ESCROW_CONTRACT => self.contract_hash,
+            ARG_ESCROW_ID => escrow_id,
+            "amount" => amount,
+        };
+        let session = ExecutableDeployItem::new_module_bytes(wasm, args);
+        EscrowTransaction::new(session, SESSION_PAYMENT)
+    }
+
+    /// Pay out to the issuer, as the payer
+    pub fn release(&self, escrow_id: u64) -> EscrowTransaction {
+        self.call(entry_points::RELEASE, id_args(escrow_id))
+    }
+
+    pub fn cancel(&self, escrow_id: u64) -> EscrowTransaction {
+        self.call(entry_points::CANCEL, id_args(escrow_id))
+    }
+
+    pub fn dispute(&self, escrow_id: u64, reason: impl Into<String>) -> EscrowTransaction {
+        let args = runtime_args! { ARG_ESCROW_ID => escrow_id, "reason" => reason.into() };
+        self.call(entry_points::DISPUTE, args)
+    }
+
+    /// Settle a dispute, as the arbiter: pay the issuer or refund the payer
+    pub fn resolve_dispute(&self, escrow_id: u64, release_to_receiver: bool) -> EscrowTransaction {
+        let args = runtime_args! {
+            ARG_ESCROW_ID => escrow_id,
+            "release_to_receiver" => release_to_receiver,
+        };
+        self.call(entry_points::RESOLVE_DISPUTE, args)
+    }
+
+    fn call(&self, entry_point: &str, args: RuntimeArgs) -> EscrowTransaction {
+        let session = ExecutableDeployItem::new_stored_contract_by_hash(
+            self.contract_hash,
+            entry_point.to_string(),
+            args,
+        );
+        EscrowTransaction::new(session, CALL_PAYMENT)
+    }
+}
+
+fn id_args(escrow_id: u64) -> RuntimeArgs {
+    runtime_args! { ARG_ESCROW_ID => escrow_id }
+}
+
+/// Dispute raised on an escrow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dispute {
+    pub raised_by: Key,
+    pub reason: String,
+    pub raised_at: u64,
+}
+
+/// Escrow snapshot returned by `get_summary`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowSummary {
+    pub escrow_id: u64,
+    pub state: State,
+    pub issuer: Key,
+    pub payer: Key,
+    pub arbiter: Option<Key>,
+    pub amount: U512,
+    pub balance: U512,
+    pub description: String,
+    pub due_date: Option<u64>,
+    pub created_at: u64,
+    pub accepted_at: Option<u64>,
+    pub funded_at: Option<u64>,
+    pub released_at: Option<u64>,
+    pub dispute: Option<Dispute>,
+    /// Funds went back to the payer
+    pub refunded: bool,
+    /// Payout purses registered by contract parties, without access rights
+    pub issuer_purse: Option<URef>,
+    pub payer_purse: Option<URef>,
+}
+
+impl EscrowSummary {
+    /// Decode a `get_summary` result
+    pub fn from_cl_value(value: &CLValue) -> Result<Self, DecodeError> {
+        let bytes = value.inner_bytes().as_slice();
+        let (escrow_id, bytes) = u64::from_bytes(bytes)?;
+        let (state, bytes) = u8::from_bytes(bytes)?;
+        let (issuer, bytes) = Key::from_bytes(bytes)?;
+        let (payer, bytes) = Key::from_bytes(bytes)?;
+        let (arbiter, bytes) = Option::<Key>::from_bytes(bytes)?;
+        let (amount, bytes) = U512::from_bytes(bytes)?;
+        let (balance, bytes) = U512::from_bytes(bytes)?;
+        let (description, bytes) = String::from_bytes(bytes)?;
+        let (due_date, bytes) = Option::<u64>::from_bytes(bytes)?;
+        let (created_at, bytes) = u64::from_bytes(bytes)?;
+        let (accepted_at, bytes) = Option::<u64>::from_bytes(bytes)?;
+        let (funded_at, bytes) = Option::<u64>::from_bytes(bytes)?;
+        let (released_at, bytes) = Option::<u64>::from_bytes(bytes)?;
+        let (dispute, bytes) = Option::<(Key, String, u64)>::from_bytes(bytes)?;
+        let (refunded, bytes) = bool::from_bytes(bytes)?;
+        let (issuer_purse, bytes) = Option::<URef>::from_bytes(bytes)?;
+        let (payer_purse, bytes) = Option::<URef>::from_bytes(bytes)?;
+        if !bytes.is_empty() {
+            return Err(bytesrepr::Error::LeftOverBytes.into());
+        }
+        Ok(EscrowSummary {
+            escrow_id,
+            state: state_from_u8(state)?,
+            issuer,
+            payer,
+            arbiter,
+            amount,
+            balance,
+            description,
+            due_date,
+            created_at,
+            accepted_at,
+            funded_at,
+            released_at,
+            dispute: dispute.map(|(raised_by, reason, raised_at)| Dispute {
+                raised_by,
+                reason,
+                raised_at,
+            }),
+            refunded,
+            issuer_purse,
+            payer_purse,
+        })
+    }
+}
+
+/// Decode a `get_purse_balances` result as (recorded, actual)
+pub fn decode_purse_balances(value: &CLValue) -> Result<(U512, U512), DecodeError> {
+    from_exact_bytes(value.inner_bytes())
+}
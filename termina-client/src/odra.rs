@@ -0,0 +1,384 @@
+//! Odra escrow contract: one installed contract per escrow
+//!
+//! The types here mirror `contracts/src/types.rs` byte for byte. Odra
+//! serializes a struct as its fields in order and an enum as a `u8` variant
+//! index followed by the variant's fields; an `Address` is a `Key`.
+
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes, ToBytes, U8_SERIALIZED_LENGTH},
+    contracts::ContractPackageHash,
+    runtime_args, CLType, CLTyped, CLValue, ExecutableDeployItem, Key, RuntimeArgs, U512,
+};
+use escrow_core::{entry_points, State};
+
+use crate::decode::{from_exact_bytes, state_from_u8, DecodeError};
+use crate::EscrowTransaction;
+
+/// Default payment for installing an escrow contract, in motes
+pub const INSTALL_PAYMENT: u64 = 150_000_000_000;
+/// Default payment for an entry point call, in motes
+pub const CALL_PAYMENT: u64 = 5_000_000_000;
+
+// Installer arguments every Odra contract expects
+const ARG_PACKAGE_HASH_KEY_NAME: &str = "odra_cfg_package_hash_key_name";
+const ARG_ALLOW_KEY_OVERRIDE: &str = "odra_cfg_allow_key_override";
+const ARG_IS_UPGRADABLE: &str = "odra_cfg_is_upgradable";
+const ARG_IS_UPGRADE: &str = "odra_cfg_is_upgrade";
+
+/// Named key the installer stores the package hash under, as Odra's own
+/// deployer names it. Each install overwrites it.
+pub const PACKAGE_HASH_KEY_NAME: &str = "Escrow_package_hash";
+
+/// Implements the Odra encoding of a struct
+macro_rules! odra_struct {
+    ($name:ident { $($field:ident),+ $(,)? }) => {
+        impl ToBytes for $name {
+            fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+                let mut buffer = bytesrepr::allocate_buffer(self)?;
+                $(buffer.extend(self.$field.to_bytes()?);)+
+                Ok(buffer)
+            }
+
+            fn serialized_length(&self) -> usize {
+                0 $(+ self.$field.serialized_length())+
+            }
+        }
+
+        impl FromBytes for $name {
+            fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+                $(let ($field, bytes) = FromBytes::from_bytes(bytes)?;)+
+                Ok(($name { $($field),+ }, bytes))
+            }
+        }
+
+        impl CLTyped for $name {
+            fn cl_type() -> CLType {
+                CLType::Any
+            }
+        }
+    };
+}
+
+/// Token an escrow amount is denominated in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Currency {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl Currency {
+    /// Native CSPR, denominated in motes
+    pub fn cspr() -> Self {
+        Currency {
+            symbol: "CSPR".to_string(),
+            decimals: 9,
+        }
+    }
+}
+
+odra_struct!(Currency { symbol, decimals });
+
+/// A single predicate that must hold before funds can be released
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReleaseCondition {
+    PayerApproval,
+    TimeElapsed { after: u64 },
+    AttestorSigned { attestor: Key },
+    Approvals { approvers: Vec<Key>, threshold: u32 },
+    SecretRevealed { hash: [u8; 32] },
+}
+
+impl ToBytes for ReleaseCondition {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut buffer = bytesrepr::allocate_buffer(self)?;
+        match self {
+            ReleaseCondition::PayerApproval => buffer.push(0),
+            ReleaseCondition::TimeElapsed { after } => {
+                buffer.push(1);
+                buffer.extend(after.to_bytes()?);
+            }
+            ReleaseCondition::AttestorSigned { attestor } => {
+                buffer.push(2);
+                buffer.extend(attestor.to_bytes()?);
+            }
+            ReleaseCondition::Approvals {
+                approvers,
+                threshold,
+            } => {
+                buffer.push(3);
+                buffer.extend(approvers.to_bytes()?);
+                buffer.extend(threshold.to_bytes()?);
+            }
+            ReleaseCondition::SecretRevealed { hash } => {
+                buffer.push(4);
+                buffer.extend(hash.to_bytes()?);
+            }
+        }
+        Ok(buffer)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+            + match self {
+                ReleaseCondition::PayerApproval => 0,
+                ReleaseCondition::TimeElapsed { after } => after.serialized_length(),
+                ReleaseCondition::AttestorSigned { attestor } => attestor.serialized_length(),
+                ReleaseCondition::Approvals {
+                    approvers,
+                    threshold,
+                } => approvers.serialized_length() + threshold.serialized_length(),
+                ReleaseCondition::SecretRevealed { hash } => hash.serialized_length(),
+            }
+    }
+}
+
+impl FromBytes for ReleaseCondition {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, bytes) = u8::from_bytes(bytes)?;
+        match tag {
+            0 => Ok((ReleaseCondition::PayerApproval, bytes)),
+            1 => {
+                let (after, bytes) = u64::from_bytes(bytes)?;
+                Ok((ReleaseCondition::TimeElapsed { after }, bytes))
+            }
+            2 => {
+                let (attestor, bytes) = Key::from_bytes(bytes)?;
+                Ok((ReleaseCondition::AttestorSigned { attestor }, bytes))
+            }
+            3 => {
+                let (approvers, bytes) = Vec::<Key>::from_bytes(bytes)?;
+                let (threshold, bytes) = u32::from_bytes(bytes)?;
+                Ok((
+                    ReleaseCondition::Approvals {
+                        approvers,
+                        threshold,
+                    },
+                    bytes,
+                ))
+            }
+            4 => {
+                let (hash, bytes) = <[u8; 32]>::from_bytes(bytes)?;
+                Ok((ReleaseCondition::SecretRevealed { hash }, bytes))
+            }
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+impl CLTyped for ReleaseCondition {
+    fn cl_type() -> CLType {
+        CLType::Any
+    }
+}
+
+/// Conditions that must all hold for the group to hold
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConditionGroup {
+    pub all_of: Vec<ReleaseCondition>,
+}
+
+odra_struct!(ConditionGroup { all_of });
+
+/// Terms of a new escrow, passed to the Odra constructor.
+/// The installing account becomes the issuer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowConfig {
+    pub id: String,
+    pub description: String,
+    pub amount: U512,
+    pub currency: Currency,
+    pub payer: Key,
+    pub arbiter: Option<Key>,
+    pub due_date: Option<u64>,
+    /// Funds are released once every condition of at least one group holds.
+    /// An empty list falls back to payer approval.
+    pub release_conditions: Vec<ConditionGroup>,
+}
+
+odra_struct!(EscrowConfig {
+    id,
+    description,
+    amount,
+    currency,
+    payer,
+    arbiter,
+    due_date,
+    release_conditions,
+});
+
+/// Invoice details returned by `get_invoice` and inside `get_summary`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invoice {
+    pub id: String,
+    pub description: String,
+    pub amount: U512,
+    pub currency: Currency,
+    pub issuer: Key,
+    pub payer: Key,
+    pub arbiter: Option<Key>,
+    pub created_at: u64,
+    pub due_date: Option<u64>,
+    pub accepted_at: Option<u64>,
+    pub funded_at: Option<u64>,
+    pub released_at: Option<u64>,
+}
+
+odra_struct!(Invoice {
+    id,
+    description,
+    amount,
+    currency,
+    issuer,
+    payer,
+    arbiter,
+    created_at,
+    due_date,
+    accepted_at,
+    funded_at,
+    released_at,
+});
+
+/// Dispute raised on an escrow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeInfo {
+    pub raised_by: Key,
+    pub reason: String,
+    pub raised_at: u64,
+}
+
+odra_struct!(DisputeInfo {
+    raised_by,
+    reason,
+    raised_at,
+});
+
+/// Escrow snapshot returned by `get_summary`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowSummary {
+    pub state: State,
+    pub invoice: Invoice,
+    pub balance: U512,
+    pub dispute: Option<DisputeInfo>,
+    /// Release conditions that are still unmet, per group
+    pub pending_conditions: Vec<ConditionGroup>,
+}
+
+impl EscrowSummary {
+    /// Decode a `get_summary` result
+    pub fn from_cl_value(value: &CLValue) -> Result<Self, DecodeError> {
+        let raw: RawSummary = from_exact_bytes(value.inner_bytes())?;
+        Ok(EscrowSummary {
+            state: state_from_u8(raw.state)?,
+            invoice: raw.invoice,
+            balance: raw.balance,
+            dispute: raw.dispute,
+            pending_conditions: raw.pending_conditions,
+        })
+    }
+}
+
+/// `EscrowSummary` as serialized, with the state still a code
+struct RawSummary {
+    state: u8,
+    invoice: Invoice,
+    balance: U512,
+    dispute: Option<DisputeInfo>,
+    pending_conditions: Vec<ConditionGroup>,
+}
+
+odra_struct!(RawSummary {
+    state,
+    invoice,
+    balance,
+    dispute,
+    pending_conditions,
+});
+
+/// Decode a `get_invoice` result
+pub fn decode_invoice(value: &CLValue) -> Result<Option<Invoice>, DecodeError> {
+    from_exact_bytes(value.inner_bytes())
+}
+
+/// An installed Odra escrow contract, called through its package
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OdraEscrow {
+    package_hash: ContractPackageHash,
+}
+
+impl OdraEscrow {
+    pub fn new(package_hash: ContractPackageHash) -> Self {
+        OdraEscrow { package_hash }
+    }
+
+    pub fn package_hash(&self) -> ContractPackageHash {
+        self.package_hash
+    }
+
+    /// Install the contract from `wasm`, creating the escrow described by `config`
+    pub fn install(wasm: Bytes, config: EscrowConfig) -> EscrowTransaction {
+        let args = runtime_args! {
+            "config" => config,
+            ARG_PACKAGE_HASH_KEY_NAME => PACKAGE_HASH_KEY_NAME.to_string(),
+            ARG_ALLOW_KEY_OVERRIDE => true,
+            ARG_IS_UPGRADABLE => false,
+            ARG_IS_UPGRADE => false,
+        };
+        let session = ExecutableDeployItem::new_module_bytes(wasm, args);
+        EscrowTransaction::new(session, INSTALL_PAYMENT)
+    }
+
+    /// Accept the escrow terms, as the payer
+    pub fn accept(&self) -> EscrowTransaction {
+        self.call(entry_points::ACCEPT, RuntimeArgs::new())
+    }
+
+    /// Record a deposit of `amount`, as the payer
+    pub fn fund(&self, amount: U512) -> EscrowTransaction {
+        self.call(entry_points::FUND, runtime_args! { "amount" => amount })
+    }
+
+    /// Approve the release, as the payer, an attestor or an approver
+    pub fn approve(&self) -> EscrowTransaction {
+        self.call(entry_points::APPROVE, RuntimeArgs::new())
+    }
+
+    /// Reveal the preimage of a `SecretRevealed` condition's hash
+    pub fn reveal_secret(&self, secret: Bytes) -> EscrowTransaction {
+        self.call(
+            entry_points::REVEAL_SECRET,
+            runtime_args! { "secret" => secret },
+        )
+    }
+
+    /// Pay out to the issuer once the release conditions hold
+    pub fn release(&self) -> EscrowTransaction {
+        self.call(entry_points::RELEASE, RuntimeArgs::new())
+    }
+
+    pub fn cancel(&self) -> EscrowTransaction {
+        self.call(entry_points::CANCEL, RuntimeArgs::new())
+    }
+
+    pub fn dispute(&self, reason: impl Into<String>) -> EscrowTransaction {
+        self.call(
+            entry_points::DISPUTE,
+            runtime_args! { "reason" => reason.into() },
+        )
+    }
+
+    /// Settle a dispute, as the arbiter: pay the issuer or refund the payer
+    pub fn resolve_dispute(&self, release_to_receiver: bool) -> EscrowTransaction {
+        let args = runtime_args! { "release_to_receiver" => release_to_receiver };
+        self.call(entry_points::RESOLVE_DISPUTE, args)
+    }
+
+    fn call(&self, entry_point: &str, args: RuntimeArgs) -> EscrowTransaction {
+        let session = ExecutableDeployItem::new_stored_versioned_contract_by_hash(
+            self.package_hash,
+            None,
+            entry_point.to_string(),
+            args,
+        );
+        EscrowTransaction::new(session, CALL_PAYMENT)
+    }
+}
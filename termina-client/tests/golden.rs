@@ -0,0 +1,344 @@
+//! Golden fixtures of every transaction the client builds, as the JSON a node
+//! accepts. Any change to argument names, types or encoding shows up as a
+//! fixture diff. Regenerate after an intended change with
+//! `UPDATE_GOLDEN=1 cargo test`.
+
+use std::{fs, path::PathBuf};
+
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{Bytes, ToBytes},
+    contracts::{ContractHash, ContractPackageHash},
+    AccessRights, CLType, CLValue, Deploy, Key, PublicKey, SecretKey, Timestamp, URef, U512,
+};
+use escrow_core::entry_points;
+use termina_client::{
+    native::{self, EscrowTerms, NativeEscrow},
+    odra::{
+        self, ConditionGroup, Currency, DisputeInfo, EscrowConfig, Invoice, OdraEscrow,
+        ReleaseCondition,
+    },
+    BuildError, DeployParams, EscrowTransaction, State,
+};
+
+/// Stand-in for contract bytes; only the encoding is under test
+const WASM: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+const CHAIN_NAME: &str = "casper-test";
+/// 2024-01-01T00:00:00Z
+const TIMESTAMP_MILLIS: u64 = 1_704_067_200_000;
+
+fn secret_key() -> SecretKey {
+    SecretKey::ed25519_from_bytes([7; 32]).unwrap()
+}
+
+fn account(seed: u8) -> Key {
+    Key::Account(AccountHash::new([seed; 32]))
+}
+
+fn wasm() -> Bytes {
+    Bytes::from(WASM.to_vec())
+}
+
+/// Build and sign the transaction, then compare it with `fixtures/<name>.json`
+fn assert_golden(name: &str, transaction: EscrowTransaction) {
+    let secret_key = secret_key();
+    let params = DeployParams::new(PublicKey::from(&secret_key), CHAIN_NAME)
+        .with_timestamp(Timestamp::from(TIMESTAMP_MILLIS));
+    let mut deploy = transaction.build(&params);
+    deploy.sign(&secret_key);
+    assert_eq!(deploy.is_valid(), Ok(()), "{} is not a valid deploy", name);
+
+    let json = serde_json::to_string_pretty(&deploy).unwrap() + "\n";
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.json", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &json).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing {}, run with UPDATE_GOLDEN=1", path.display()));
+    assert_eq!(json, expected, "{} differs from its fixture", name);
+
+    let parsed: Deploy = serde_json::from_str(&expected).unwrap();
+    assert_eq!(parsed, deploy);
+}
+
+fn odra_escrow() -> OdraEscrow {
+    OdraEscrow::new(ContractPackageHash::new([0x0d; 32]))
+}
+
+fn native_escrow() -> NativeEscrow {
+    NativeEscrow::new(ContractHash::new([0x0e; 32]))
+}
+
+fn odra_config() -> EscrowConfig {
+    EscrowConfig {
+        id: "INV-001".to_string(),
+        description: "Website redesign".to_string(),
+        amount: U512::from(1_000_000_000_000u64),
+        currency: Currency::cspr(),
+        payer: account(2),
+        arbiter: Some(account(3)),
+        due_date: Some(TIMESTAMP_MILLIS + 86_400_000),
+        release_conditions: vec![
+            ConditionGroup {
+                all_of: vec![ReleaseCondition::PayerApproval],
+            },
+            ConditionGroup {
+                all_of: vec![
+                    ReleaseCondition::TimeElapsed {
+                        after: TIMESTAMP_MILLIS,
+                    },
+                    ReleaseCondition::Approvals {
+                        approvers: vec![account(4), account(5)],
+                        threshold: 1,
+                    },
+                    ReleaseCondition::SecretRevealed { hash: [0xab; 32] },
+                ],
+            },
+        ],
+    }
+}
+
+fn native_terms() -> EscrowTerms {
+    EscrowTerms::new(U512::from(250_000_000_000u64), "Logo design")
+        .with_arbiter(account(3))
+        .with_due_date(TIMESTAMP_MILLIS + 86_400_000)
+}
+
+#[test]
+fn test_odra_transactions() {
+    let escrow = odra_escrow();
+    assert_golden("odra_install", OdraEscrow::install(wasm(), odra_config()));
+    assert_golden("odra_accept", escrow.accept());
+    assert_golden("odra_fund", escrow.fund(U512::from(1_000_000_000_000u64)));
+    assert_golden("odra_approve", escrow.approve());
+    assert_golden(
+        "odra_reveal_secret",
+        escrow.reveal_secret(Bytes::from(b"open sesame".to_vec())),
+    );
+    assert_golden("odra_release", escrow.release());
+    assert_golden("odra_cancel", escrow.cancel());
+    assert_golden("odra_dispute", escrow.dispute("Work not delivered"));
+    assert_golden("odra_resolve_dispute", escrow.resolve_dispute(false));
+}
+
+#[test]
+fn test_native_transactions() {
+    let escrow = native_escrow();
+    assert_golden("native_install", NativeEscrow::install(wasm()));
+    assert_golden(
+        "native_install_with_escrow",
+        NativeEscrow::install_with_escrow(wasm(), account(2), &native_terms()).unwrap(),
+    );
+    assert_golden(
+        "native_create_escrow",
+        escrow.create_escrow(account(2), &EscrowTerms::new(U512::from(5u64), "Minimal")),
+    );
+    assert_golden(
+        "native_create_and_fund",
+        escrow.create_and_fund(wasm(), account(1), &native_terms()),
+    );
+    assert_golden("native_accept", escrow.accept(3));
+    assert_golden(
+        "native_fund",
+        escrow.fund(wasm(), 3, U512::from(250_000_000_000u64)),
+    );
+    assert_golden("native_release", escrow.release(3));
+    assert_golden("native_cancel", escrow.cancel(3));
+    assert_golden("native_dispute", escrow.dispute(3, "Work not delivered"));
+    assert_golden("native_resolve_dispute", escrow.resolve_dispute(3, true));
+}
+
+#[test]
+fn test_install_with_escrow_rejects_amounts_above_u64() {
+    let amount = U512::from(u64::MAX) + 1;
+    assert_eq!(
+        NativeEscrow::install_with_escrow(wasm(), account(2), &EscrowTerms::new(amount, "Fleet"))
+            .unwrap_err(),
+        BuildError::AmountOutOfRange(amount)
+    );
+}
+
+#[test]
+fn test_default_payments() {
+    assert_eq!(
+        OdraEscrow::install(wasm(), odra_config()).payment(),
+        U512::from(odra::INSTALL_PAYMENT)
+    );
+    assert_eq!(
+        native_escrow().fund(wasm(), 0, U512::one()).payment(),
+        U512::from(native::SESSION_PAYMENT)
+    );
+    let payment = U512::from(42u64);
+    assert_eq!(
+        native_escrow().accept(0).with_payment(payment).payment(),
+        payment
+    );
+}
+
+fn any_value(bytes: Vec<u8>) -> CLValue {
+    CLValue::from_components(CLType::Any, bytes)
+}
+
+#[test]
+fn test_decode_odra_summary() {
+    let config = odra_config();
+    let invoice = Invoice {
+        id: config.id,
+        description: config.description,
+        amount: config.amount,
+        currency: config.currency,
+        issuer: account(1),
+        payer: config.payer,
+        arbiter: config.arbiter,
+        created_at: TIMESTAMP_MILLIS,
+        due_date: config.due_date,
+        accepted_at: Some(TIMESTAMP_MILLIS + 1),
+        funded_at: Some(TIMESTAMP_MILLIS + 2),
+        released_at: None,
+    };
+    let dispute = Some(DisputeInfo {
+        raised_by: account(2),
+        reason: "Late".to_string(),
+        raised_at: TIMESTAMP_MILLIS + 3,
+    });
+    let pending = vec![ConditionGroup {
+        all_of: vec![ReleaseCondition::AttestorSigned {
+            attestor: Key::Hash([9; 32]),
+        }],
+    }];
+
+    let mut bytes = vec![State::Disputed as u8];
+    bytes.extend(invoice.to_bytes().unwrap());
+    bytes.extend(config.amount.to_bytes().unwrap());
+    bytes.extend(dispute.to_bytes().unwrap());
+    bytes.extend(pending.to_bytes().unwrap());
+    let summary = odra::EscrowSummary::from_cl_value(&any_value(bytes.clone())).unwrap();
+    assert_eq!(summary.state, State::Disputed);
+    assert_eq!(summary.invoice, invoice);
+    assert_eq!(summary.balance, config.amount);
+    assert_eq!(summary.dispute, dispute);
+    assert_eq!(summary.pending_conditions, pending);
+
+    let invoice_value = any_value(Some(invoice.clone()).to_bytes().unwrap());
+    assert_eq!(odra::decode_invoice(&invoice_value), Ok(Some(invoice)));
+
+    bytes[0] = 9;
+    assert!(odra::EscrowSummary::from_cl_value(&any_value(bytes)).is_err());
+}
+
+#[test]
+fn test_decode_native_summary() {
+    let mut bytes = Vec::new();
+    bytes.extend(7u64.to_bytes().unwrap());
+    bytes.extend((State::Released as u8).to_bytes().unwrap());
+    bytes.extend(account(1).to_bytes().unwrap());
+    bytes.extend(Key::Hash([9; 32]).to_bytes().unwrap());
+    bytes.extend(None::<Key>.to_bytes().unwrap());
+    bytes.extend(U512::from(100u64).to_bytes().unwrap());
+    bytes.extend(U512::zero().to_bytes().unwrap());
+    bytes.extend("Logo design".to_string().to_bytes().unwrap());
+    bytes.extend(None::<u64>.to_bytes().unwrap());
+    bytes.extend(10u64.to_bytes().unwrap());
+    bytes.extend(Some(11u64).to_bytes().unwrap());
+    bytes.extend(Some(12u64).to_bytes().unwrap());
+    bytes.extend(Some(13u64).to_bytes().unwrap());
+    bytes.extend(
+        Some((account(1), "Late".to_string(), 12u64))
+            .to_bytes()
+            .unwrap(),
+    );
+    bytes.extend(false.to_bytes().unwrap());
+    bytes.extend(None::<URef>.to_bytes().unwrap());
+    let payer_purse = URef::new([8; 32], AccessRights::NONE);
+    bytes.extend(Some(payer_purse).to_bytes().unwrap());
+
+    let summary = native::EscrowSummary::from_cl_value(&any_value(bytes.clone())).unwrap();
+    assert_eq!(
+        summary,
+        native::EscrowSummary {
+            escrow_id: 7,
+            state: State::Released,
+            issuer: account(1),
+            payer: Key::Hash([9; 32]),
+            arbiter: None,
+            amount: U512::from(100u64),
+            balance: U512::zero(),
+            description: "Logo design".to_string(),
+            due_date: None,
+            created_at: 10,
+            accepted_at: Some(11),
+            funded_at: Some(12),
+            released_at: Some(13),
+            dispute: Some(native::Dispute {
+                raised_by: account(1),
+                reason: "Late".to_string(),
+                raised_at: 12,
+            }),
+            refunded: false,
+            issuer_purse: None,
+            payer_purse: Some(payer_purse),
+        }
+    );
+
+    bytes.push(0);
+    assert!(native::EscrowSummary::from_cl_value(&any_value(bytes)).is_err());
+
+    let balances = CLValue::from_t((U512::from(5u64), U512::from(6u64))).unwrap();
+    assert_eq!(
+        native::decode_purse_balances(&balances),
+        Ok((U512::from(5u64), U512::from(6u64)))
+    );
+}
+
+/// The decoder reads the fields the generated bindings document, in order
+#[test]
+fn test_native_summary_follows_documented_layout() {
+    let purse = URef::new([8; 32], AccessRights::NONE);
+    let mut bytes = Vec::new();
+    for (field, cl_type) in entry_points::NATIVE_SUMMARY_LAYOUT {
+        let value = match *cl_type {
+            "U64" => 3u64.to_bytes(),
+            "U8" => (State::Funded as u8).to_bytes(),
+            "Key" => account(1).to_bytes(),
+            "Option<Key>" => Some(account(2)).to_bytes(),
+            "U512" => U512::from(5u64).to_bytes(),
+            "String" => field.to_string().to_bytes(),
+            "Option<U64>" => Some(4u64).to_bytes(),
+            "Option<(Key, String, U64)>" => Some((account(1), field.to_string(), 4u64)).to_bytes(),
+            "Bool" => true.to_bytes(),
+            "Option<URef>" => Some(purse).to_bytes(),
+            other => panic!("no sample for {} of type {}", field, other),
+        };
+        bytes.extend(value.unwrap());
+    }
+
+    assert_eq!(
+        native::EscrowSummary::from_cl_value(&any_value(bytes)).unwrap(),
+        native::EscrowSummary {
+            escrow_id: 3,
+            state: State::Funded,
+            issuer: account(1),
+            payer: account(1),
+            arbiter: Some(account(2)),
+            amount: U512::from(5u64),
+            balance: U512::from(5u64),
+            description: "description".to_string(),
+            due_date: Some(4),
+            created_at: 3,
+            accepted_at: Some(4),
+            funded_at: Some(4),
+            released_at: Some(4),
+            dispute: Some(native::Dispute {
+                raised_by: account(1),
+                reason: "dispute".to_string(),
+                raised_at: 4,
+            }),
+            refunded: true,
+            issuer_purse: Some(purse),
+            payer_purse: Some(purse),
+        }
+    );
+}
@@ -0,0 +1,47 @@
+use std::{fmt, io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read or written
+    Io(PathBuf, io::Error),
+    /// A profile, spec or manifest doesn't parse
+    Parse(PathBuf, String),
+    /// A key file doesn't hold a usable key
+    Key(PathBuf, String),
+    /// An escrow is rejected before anything is sent
+    InvalidEscrow { id: String, reason: String },
+    /// The manifest records deployments from another network or account
+    ManifestMismatch(PathBuf, String),
+    /// The node couldn't be reached or answered with an error
+    Rpc(String),
+    /// The install deploy executed and failed
+    DeployFailed {
+        id: String,
+        deploy_hash: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            Error::Parse(path, message) => write!(f, "{}: {}", path.display(), message),
+            Error::Key(path, message) => write!(f, "key {}: {}", path.display(), message),
+            Error::InvalidEscrow { id, reason } => write!(f, "escrow {}: {}", id, reason),
+            Error::ManifestMismatch(path, message) => write!(f, "{}: {}", path.display(), message),
+            Error::Rpc(message) => write!(f, "node: {}", message),
+            Error::DeployFailed {
+                id,
+                deploy_hash,
+                message,
+            } => write!(
+                f,
+                "escrow {}: deploy {} failed: {}",
+                id, deploy_hash, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,179 @@
+//! Installs the Odra escrows listed in a spec file and records them in a
+//! deployment manifest for the API
+//!
+//! ```sh
+//! cargo run --manifest-path termina-deploy/Cargo.toml -- \
+//!     --profile termina-deploy/profiles/testnet.toml \
+//!     --spec termina-deploy/specs/demo.toml \
+//!     --manifest deployments/testnet.json
+//! ```
+//!
+//! Every escrow is checked before the first deploy is sent. Escrows already
+//! in the manifest are skipped, so rerunning after a failure resumes. An
+//! install is recorded as soon as it executes; if its package hash can't be
+//! read back then, the run stops and the next one reads it before going on.
+
+mod error;
+mod manifest;
+mod rpc;
+mod spec;
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use casper_types::{bytesrepr::Bytes, Key, PublicKey, Timestamp, U512};
+use clap::Parser;
+use termina_client::{decode_error_message, odra, DeployParams};
+
+use crate::{
+    error::Error,
+    manifest::{DeployedEscrow, Manifest},
+    rpc::RpcClient,
+    spec::{DeploySpec, NetworkProfile},
+};
+
+#[derive(Parser)]
+#[command(about = "Install Termina escrows from a spec file")]
+struct Args {
+    /// Network profile: node, chain name, keys and payment
+    #[arg(long)]
+    profile: PathBuf,
+    /// Escrows to install
+    #[arg(long)]
+    spec: PathBuf,
+    /// Manifest to write; escrows already in it are skipped
+    #[arg(long, default_value = "deployment.json")]
+    manifest: PathBuf,
+    /// Compiled Odra escrow contract
+    #[arg(long, default_value = "contracts/wasm/Escrow.wasm")]
+    wasm: PathBuf,
+    /// Check the spec and print the deploys without sending them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    let profile = NetworkProfile::load(&args.profile)?;
+    let spec = DeploySpec::load(&args.spec)?;
+    let secret_key = profile.secret_key()?;
+    let deployer = PublicKey::from(&secret_key);
+    let issuer = Key::Account(deployer.to_account_hash());
+    let accounts = profile.accounts()?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock is after 1970")
+        .as_millis() as u64;
+    let escrows = spec
+        .escrows
+        .iter()
+        .map(|escrow| {
+            let config = escrow.to_config(issuer, &accounts, now)?;
+            Ok((escrow.resolve(&accounts)?, config))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let wasm = fs::read(&args.wasm).map_err(|error| Error::Io(args.wasm.clone(), error))?;
+    let wasm = Bytes::from(wasm);
+    let mut manifest = Manifest::load_or_new(
+        &args.manifest,
+        Manifest::new(
+            &profile.chain_name,
+            &profile.node_address,
+            issuer.to_formatted_string(),
+        ),
+    )?;
+    let client = RpcClient::new(&profile.node_address);
+
+    for (escrow, config) in escrows {
+        if let Some(deployed) = manifest.get_mut(&escrow.id) {
+            if deployed.package_hash.is_none() {
+                // Nothing was installed since, as the previous run stopped here
+                let package_hash = installed_package(&client, &deployer)?;
+                println!("{}: installed as package {}", escrow.id, package_hash);
+                deployed.package_hash = Some(package_hash);
+                manifest.save(&args.manifest)?;
+            } else {
+                println!("{}: in the manifest, skipping", escrow.id);
+            }
+            continue;
+        }
+        let timestamp = Timestamp::now();
+        let params = DeployParams::new(deployer.clone(), &profile.chain_name)
+            .with_timestamp(timestamp)
+            .with_ttl(profile.ttl())
+            .with_gas_price(profile.gas_price);
+        let payment = escrow.payment.unwrap_or(profile.install_payment);
+        let mut deploy = odra::OdraEscrow::install(wasm.clone(), config)
+            .with_payment(U512::from(payment))
+            .build(&params);
+        deploy.sign(&secret_key);
+        let deploy_hash = hex::encode(deploy.hash().inner().value());
+        if args.dry_run {
+            println!("{}: deploy {} (not sent)", escrow.id, deploy_hash);
+            continue;
+        }
+
+        client.put_deploy(&deploy)?;
+        println!("{}: sent deploy {}", escrow.id, deploy_hash);
+        let execution =
+            client.wait_for_deploy(&deploy_hash, Duration::from_secs(profile.timeout_secs))?;
+        if let Some(message) = execution.error {
+            let message = match decode_error_message(&message) {
+                Some(error) => format!("{} ({})", error.name(), error.description()),
+                None => message,
+            };
+            return Err(Error::DeployFailed {
+                id: escrow.id,
+                deploy_hash,
+                message,
+            });
+        }
+
+        // Record the install before anything else can fail, so a rerun
+        // never pays for it twice
+        let id = escrow.id.clone();
+        manifest.escrows.push(DeployedEscrow {
+            id: id.clone(),
+            package_hash: None,
+            deploy_hash,
+            block_hash: execution.block_hash,
+            deployed_at: timestamp.to_string(),
+            args: escrow,
+        });
+        manifest.save(&args.manifest)?;
+
+        let package_hash = installed_package(&client, &deployer)?;
+        println!("{}: installed as package {}", id, package_hash);
+        manifest.get_mut(&id).expect("just recorded").package_hash = Some(package_hash);
+        manifest.save(&args.manifest)?;
+    }
+    Ok(())
+}
+
+/// Package of the latest install by `deployer`, in hex. The installer
+/// overwrites this key on every install, so it names the package just created.
+fn installed_package(client: &RpcClient, deployer: &PublicKey) -> Result<String, Error> {
+    match client.named_key(deployer, odra::PACKAGE_HASH_KEY_NAME)? {
+        Key::Hash(addr) | Key::SmartContract(addr) => Ok(hex::encode(addr)),
+        other => Err(Error::Rpc(format!(
+            "{} is not a package: {}",
+            odra::PACKAGE_HASH_KEY_NAME,
+            other
+        ))),
+    }
+}
@@ -0,0 +1,158 @@
+//! Deployment manifest: which escrows were installed where. Written after
+//! every install, so a rerun picks up after the last escrow that made it.
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, spec::EscrowSpec};
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub chain_name: String,
+    pub node_address: String,
+    /// Installing account, the issuer of every escrow
+    pub deployer: String,
+    pub escrows: Vec<DeployedEscrow>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeployedEscrow {
+    pub id: String,
+    /// Contract package the escrow is called through, in hex. `None` while
+    /// the install has executed but its package hash is yet to be read.
+    pub package_hash: Option<String>,
+    pub deploy_hash: String,
+    pub block_hash: String,
+    pub deployed_at: String,
+    /// Constructor arguments, with parties as formatted keys
+    pub args: EscrowSpec,
+}
+
+impl Manifest {
+    pub fn new(chain_name: &str, node_address: &str, deployer: String) -> Self {
+        Manifest {
+            chain_name: chain_name.to_string(),
+            node_address: node_address.to_string(),
+            deployer,
+            escrows: Vec::new(),
+        }
+    }
+
+    /// Load the manifest at `path` if there is one. It must be for the same
+    /// chain and deploying account, or resuming would mix deployments.
+    pub fn load_or_new(path: &Path, expected: Manifest) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(expected);
+        }
+        let text =
+            fs::read_to_string(path).map_err(|error| Error::Io(path.to_path_buf(), error))?;
+        let manifest: Manifest = serde_json::from_str(&text)
+            .map_err(|error| Error::Parse(path.to_path_buf(), error.to_string()))?;
+        if manifest.chain_name != expected.chain_name {
+            return Err(Error::ManifestMismatch(
+                path.to_path_buf(),
+                format!("written for chain {}", manifest.chain_name),
+            ));
+        }
+        if manifest.deployer != expected.deployer {
+            return Err(Error::ManifestMismatch(
+                path.to_path_buf(),
+                format!("written for deployer {}", manifest.deployer),
+            ));
+        }
+        Ok(manifest)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut DeployedEscrow> {
+        self.escrows.iter_mut().find(|escrow| escrow.id == id)
+    }
+
+    /// Write through a temporary file, so an interrupted run never leaves a
+    /// truncated manifest
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self).expect("manifest should serialize") + "\n";
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, json).map_err(|error| Error::Io(temporary.clone(), error))?;
+        fs::rename(&temporary, path).map_err(|error| Error::Io(path.to_path_buf(), error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::spec::Amount;
+
+    fn deployed(id: &str) -> DeployedEscrow {
+        DeployedEscrow {
+            id: id.to_string(),
+            package_hash: Some("0d".repeat(32)),
+            deploy_hash: "0e".repeat(32),
+            block_hash: "0f".repeat(32),
+            deployed_at: "2024-01-01T00:00:00.000Z".to_string(),
+            args: EscrowSpec {
+                id: id.to_string(),
+                description: "Logo design".to_string(),
+                amount: Amount::Decimal("5".to_string()),
+                currency: None,
+                payer: format!("account-hash-{}", "02".repeat(32)),
+                arbiter: None,
+                due_date: None,
+                release_conditions: Vec::new(),
+                payment: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_resume_from_saved_manifest() {
+        let dir = env::temp_dir().join(format!("termina-deploy-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("deployment.json");
+        let fresh = || {
+            Manifest::new(
+                "casper-test",
+                "http://localhost/rpc",
+                "account-hash-01".to_string(),
+            )
+        };
+
+        let mut manifest = Manifest::load_or_new(&path, fresh()).unwrap();
+        assert!(manifest.escrows.is_empty());
+        manifest.escrows.push(deployed("INV-1"));
+        // Installed, but its package hash not read back yet
+        let mut pending = deployed("INV-2");
+        pending.package_hash = None;
+        manifest.escrows.push(pending);
+        manifest.save(&path).unwrap();
+
+        let mut loaded = Manifest::load_or_new(&path, fresh()).unwrap();
+        assert_eq!(loaded, manifest);
+        assert!(loaded.get_mut("INV-1").is_some());
+        assert_eq!(loaded.get_mut("INV-2").unwrap().package_hash, None);
+        assert!(loaded.get_mut("INV-3").is_none());
+
+        let other_chain = Manifest::new(
+            "casper",
+            "http://localhost/rpc",
+            "account-hash-01".to_string(),
+        );
+        assert!(matches!(
+            Manifest::load_or_new(&path, other_chain),
+            Err(Error::ManifestMismatch(..))
+        ));
+        let other_deployer = Manifest::new(
+            "casper-test",
+            "http://localhost/rpc",
+            "account-hash-02".to_string(),
+        );
+        assert!(matches!(
+            Manifest::load_or_new(&path, other_deployer),
+            Err(Error::ManifestMismatch(..))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
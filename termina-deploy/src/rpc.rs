@@ -0,0 +1,341 @@
+//! The few node JSON-RPC calls an install needs. Responses of Casper 1.x and
+//! 2.0 nodes are both understood.
+
+use std::{
+    cell::Cell,
+    thread,
+    time::{Duration, Instant},
+};
+
+use casper_types::{AsymmetricType, Deploy, Key, PublicKey};
+use serde_json::{json, Value};
+
+use crate::error::Error;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How an executed deploy ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Execution {
+    pub block_hash: String,
+    /// Error message of a failed deploy
+    pub error: Option<String>,
+}
+
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+    poll_interval: Duration,
+    next_id: Cell<u64>,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        RpcClient {
+            url: url.into(),
+            agent: ureq::agent(),
+            poll_interval: POLL_INTERVAL,
+            next_id: Cell::new(1),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let id = self.next_id.replace(self.next_id.get() + 1);
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let mut response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(request)
+            .map_err(|error| Error::Rpc(format!("{}: {}", method, error)))?
+            .into_json()
+            .map_err(|error| Error::Rpc(format!("{}: {}", method, error)))?;
+        if let Some(error) = response.get("error") {
+            return Err(Error::Rpc(format!("{}: {}", method, error)));
+        }
+        response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| Error::Rpc(format!("{}: response has no result", method)))
+    }
+
+    /// Send a signed deploy; returns its hash as the node reports it
+    pub fn put_deploy(&self, deploy: &Deploy) -> Result<String, Error> {
+        let result = self.call("account_put_deploy", json!({ "deploy": deploy }))?;
+        result["deploy_hash"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::Rpc("account_put_deploy: no deploy hash".to_string()))
+    }
+
+    /// Poll until the deploy has executed. Lookup errors are retried, as a
+    /// fresh deploy may not have reached the node yet.
+    pub fn wait_for_deploy(
+        &self,
+        deploy_hash: &str,
+        timeout: Duration,
+    ) -> Result<Execution, Error> {
+        let started = Instant::now();
+        loop {
+            match self.call("info_get_deploy", json!({ "deploy_hash": deploy_hash })) {
+                Ok(result) => {
+                    if let Some(execution) = execution(&result) {
+                        return Ok(execution);
+                    }
+                }
+                Err(Error::Rpc(_)) if started.elapsed() < timeout => {}
+                Err(error) => return Err(error),
+            }
+            if started.elapsed() >= timeout {
+                return Err(Error::Rpc(format!(
+                    "deploy {} not executed after {}s",
+                    deploy_hash,
+                    timeout.as_secs()
+                )));
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Named key of an account
+    pub fn named_key(&self, account: &PublicKey, name: &str) -> Result<Key, Error> {
+        let result = self.call(
+            "state_get_account_info",
+            json!({ "account_identifier": account.to_hex() }),
+        )?;
+        let named_keys = result["account"]["named_keys"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let key = named_keys
+            .iter()
+            .find(|named_key| named_key["name"] == name)
+            .and_then(|named_key| named_key["key"].as_str())
+            .ok_or_else(|| Error::Rpc(format!("account has no named key {}", name)))?;
+        Key::from_formatted_str(key).map_err(|error| Error::Rpc(format!("{}: {}", name, error)))
+    }
+}
+
+/// Outcome in an `info_get_deploy` result, if the deploy has executed.
+/// 2.0 nodes report `execution_info`, with a versioned result; 1.x nodes
+/// report `execution_results`, with `Success` or `Failure`.
+fn execution(result: &Value) -> Option<Execution> {
+    let (block_hash, outcome) = match result.get("execution_info") {
+        Some(info) if !info.is_null() => (&info["block_hash"], &info["execution_result"]),
+        _ => {
+            let first = result.get("execution_results")?.get(0)?;
+            (&first["block_hash"], &first["result"])
+        }
+    };
+    if outcome.is_null() {
+        return None;
+    }
+    let outcome = outcome
+        .get("Version2")
+        .or_else(|| outcome.get("Version1"))
+        .unwrap_or(outcome);
+    let error = match outcome.get("Failure") {
+        Some(failure) => Some(
+            failure["error_message"]
+                .as_str()
+                .unwrap_or("execution failed")
+                .to_string(),
+        ),
+        None if outcome.get("Success").is_some() => None,
+        None => outcome["error_message"].as_str().map(str::to_string),
+    };
+    Some(Execution {
+        block_hash: block_hash.as_str()?.to_string(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::{TcpListener, TcpStream},
+        thread::JoinHandle,
+    };
+
+    use casper_types::{SecretKey, Timestamp};
+    use termina_client::{odra::OdraEscrow, DeployParams};
+
+    use super::*;
+
+    /// Answer each JSON-RPC request with the next result, one request per
+    /// connection, and hand back the requests once all results are used
+    fn mock_node(results: Vec<Value>) -> (String, JoinHandle<Vec<Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/rpc", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for result in results {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let request = read_request(&mut reader).expect("a JSON-RPC request");
+                let body = match result {
+                    Value::String(message) => json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "error": { "code": -32000, "message": message },
+                    }),
+                    result => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+                }
+                .to_string();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+                requests.push(request);
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Value> {
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse().ok()?;
+                }
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).ok()?;
+        serde_json::from_slice(&body).ok()
+    }
+
+    fn deploy() -> Deploy {
+        let secret_key = SecretKey::ed25519_from_bytes([7; 32]).unwrap();
+        let params = DeployParams::new(PublicKey::from(&secret_key), "casper-test")
+            .with_timestamp(Timestamp::from(1_704_067_200_000));
+        let escrow = OdraEscrow::new([0x0d; 32].into());
+        let mut deploy = escrow.accept().build(&params);
+        deploy.sign(&secret_key);
+        deploy
+    }
+
+    #[test]
+    fn test_install_round_trip() {
+        let package = Key::Hash([0x0d; 32]).to_formatted_string();
+        let (url, node) = mock_node(vec![
+            json!({ "api_version": "2.0.0", "deploy_hash": "aa" }),
+            Value::String("No such deploy".to_string()),
+            json!({ "execution_info": null }),
+            json!({ "execution_info": {
+                "block_hash": "bb",
+                "execution_result": { "Version2": { "error_message": null } },
+            } }),
+            json!({ "account": { "named_keys": [
+                { "name": "other", "key": Key::Hash([1; 32]).to_formatted_string() },
+                { "name": "Escrow_package_hash", "key": package },
+            ] } }),
+        ]);
+        let client = RpcClient::new(url).with_poll_interval(Duration::from_millis(1));
+        let deploy = deploy();
+
+        assert_eq!(client.put_deploy(&deploy).unwrap(), "aa");
+        assert_eq!(
+            client
+                .wait_for_deploy("aa", Duration::from_secs(5))
+                .unwrap(),
+            Execution {
+                block_hash: "bb".to_string(),
+                error: None,
+            }
+        );
+        let account = deploy.header().account().clone();
+        assert_eq!(
+            client.named_key(&account, "Escrow_package_hash").unwrap(),
+            Key::Hash([0x0d; 32])
+        );
+
+        let requests = node.join().unwrap();
+        let methods: Vec<_> = requests
+            .iter()
+            .map(|request| request["method"].clone())
+            .collect();
+        assert_eq!(
+            methods,
+            [
+                "account_put_deploy",
+                "info_get_deploy",
+                "info_get_deploy",
+                "info_get_deploy",
+                "state_get_account_info"
+            ]
+        );
+        let sent: Deploy = serde_json::from_value(requests[0]["params"]["deploy"].clone()).unwrap();
+        assert_eq!(sent, deploy);
+        assert_eq!(
+            requests[4]["params"]["account_identifier"],
+            account.to_hex()
+        );
+    }
+
+    #[test]
+    fn test_node_error() {
+        let (url, node) = mock_node(vec![Value::String("invalid deploy".to_string())]);
+        let client = RpcClient::new(url);
+        match client.put_deploy(&deploy()) {
+            Err(Error::Rpc(message)) => assert!(message.contains("invalid deploy"), "{}", message),
+            other => panic!("expected a node error, got {:?}", other),
+        }
+        node.join().unwrap();
+    }
+
+    #[test]
+    fn test_execution_outcomes() {
+        let v2_failure = json!({ "execution_info": {
+            "block_hash": "bb",
+            "execution_result": { "Version2": { "error_message": "User error: 4" } },
+        } });
+        assert_eq!(
+            execution(&v2_failure),
+            Some(Execution {
+                block_hash: "bb".to_string(),
+                error: Some("User error: 4".to_string()),
+            })
+        );
+
+        let v1_success = json!({ "execution_results": [
+            { "block_hash": "cc", "result": { "Success": { "cost": "1" } } },
+        ] });
+        assert_eq!(
+            execution(&v1_success),
+            Some(Execution {
+                block_hash: "cc".to_string(),
+                error: None,
+            })
+        );
+
+        let v1_failure = json!({ "execution_results": [
+            { "block_hash": "cc", "result": { "Failure": { "error_message": "Out of gas error" } } },
+        ] });
+        assert_eq!(
+            execution(&v1_failure).unwrap().error.as_deref(),
+            Some("Out of gas error")
+        );
+
+        assert_eq!(execution(&json!({ "execution_results": [] })), None);
+    }
+}
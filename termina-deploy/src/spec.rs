@@ -0,0 +1,571 @@
+//! Escrow spec: the escrows to install and the network to install them on.
+//! Both files are TOML or JSON, picked by extension.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use casper_types::{AsymmetricType, Key, PublicKey, SecretKey, TimeDiff, U512};
+use escrow_core::{validate_description, validate_due_date, validate_id, validate_parties};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use termina_client::odra::{ConditionGroup, Currency, EscrowConfig, ReleaseCondition};
+
+use crate::error::Error;
+
+const DEFAULT_INSTALL_PAYMENT: u64 = termina_client::odra::INSTALL_PAYMENT;
+const DEFAULT_GAS_PRICE: u64 = 1;
+const DEFAULT_TTL: &str = "30m";
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Read a TOML or JSON file, by extension
+pub fn read_file<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let text = fs::read_to_string(path).map_err(|error| Error::Io(path.to_path_buf(), error))?;
+    let parsed = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|error| error.to_string()),
+        Some("json") => serde_json::from_str(&text).map_err(|error| error.to_string()),
+        _ => Err("expected a .toml or .json file".to_string()),
+    };
+    parsed.map_err(|message| Error::Parse(path.to_path_buf(), message))
+}
+
+/// Where deploys go and who signs them
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkProfile {
+    /// Node JSON-RPC endpoint, such as `https://node.testnet.casper.network/rpc`
+    pub node_address: String,
+    pub chain_name: String,
+    /// Key of the deploying account, which becomes the issuer of every escrow
+    pub secret_key_path: PathBuf,
+    /// Payment for each install, in motes
+    #[serde(default = "default_install_payment")]
+    pub install_payment: u64,
+    #[serde(default = "default_gas_price")]
+    pub gas_price: u64,
+    /// How long a deploy stays valid, such as `30m`
+    #[serde(default = "default_ttl")]
+    pub ttl: String,
+    /// How long to wait for each deploy to execute
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Parties specs can refer to by name, as public or secret key files
+    #[serde(default)]
+    pub accounts: BTreeMap<String, PathBuf>,
+    /// Directory relative key paths are resolved against
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+fn default_install_payment() -> u64 {
+    DEFAULT_INSTALL_PAYMENT
+}
+
+fn default_gas_price() -> u64 {
+    DEFAULT_GAS_PRICE
+}
+
+fn default_ttl() -> String {
+    DEFAULT_TTL.to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl NetworkProfile {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut profile: NetworkProfile = read_file(path)?;
+        profile.base_dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        profile
+            .ttl
+            .parse::<TimeDiff>()
+            .map_err(|error| Error::Parse(path.to_path_buf(), format!("ttl: {}", error)))?;
+        Ok(profile)
+    }
+
+    pub fn ttl(&self) -> TimeDiff {
+        self.ttl.parse().expect("ttl is checked on load")
+    }
+
+    pub fn secret_key(&self) -> Result<SecretKey, Error> {
+        let path = self.base_dir.join(&self.secret_key_path);
+        SecretKey::from_file(&path).map_err(|error| Error::Key(path, error.to_string()))
+    }
+
+    /// Named parties, as account keys
+    pub fn accounts(&self) -> Result<BTreeMap<String, Key>, Error> {
+        self.accounts
+            .iter()
+            .map(|(name, path)| {
+                let path = self.base_dir.join(path);
+                let public_key = PublicKey::from_file(&path)
+                    .or_else(|_| SecretKey::from_file(&path).map(|key| PublicKey::from(&key)))
+                    .map_err(|error| Error::Key(path, error.to_string()))?;
+                Ok((name.clone(), Key::Account(public_key.to_account_hash())))
+            })
+            .collect()
+    }
+}
+
+/// Escrows to install, in order
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeploySpec {
+    pub escrows: Vec<EscrowSpec>,
+}
+
+impl DeploySpec {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let spec: DeploySpec = read_file(path)?;
+        let mut ids = BTreeSet::new();
+        for escrow in &spec.escrows {
+            if !ids.insert(&escrow.id) {
+                return Err(escrow.invalid("listed more than once"));
+            }
+        }
+        Ok(spec)
+    }
+}
+
+/// One escrow, as written in a spec. Parties are references: the name of a
+/// profile account, a formatted key (`account-hash-…` or `hash-…`), or a
+/// public key in hex.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EscrowSpec {
+    pub id: String,
+    pub description: String,
+    pub amount: Amount,
+    /// Defaults to CSPR
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<CurrencySpec>,
+    pub payer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arbiter: Option<String>,
+    /// Block time in milliseconds after which payment is due
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<u64>,
+    /// Groups of conditions; funds are released once every condition of a
+    /// group holds. Empty falls back to payer approval.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub release_conditions: Vec<Vec<ConditionSpec>>,
+    /// Payment for this install in motes, instead of the profile's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payment: Option<u64>,
+}
+
+/// Amount in the currency's smallest unit. A decimal string carries values
+/// beyond what TOML and JSON integers hold.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Amount {
+    Integer(u64),
+    Decimal(String),
+}
+
+impl Amount {
+    fn to_u512(&self) -> Option<U512> {
+        match self {
+            Amount::Integer(value) => Some(U512::from(*value)),
+            Amount::Decimal(value) => U512::from_dec_str(value).ok(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CurrencySpec {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConditionSpec {
+    PayerApproval,
+    TimeElapsed {
+        after: u64,
+    },
+    AttestorSigned {
+        attestor: String,
+    },
+    Approvals {
+        approvers: Vec<String>,
+        threshold: u32,
+    },
+    /// `hash` is the blake2b-256 hash of the secret, in hex
+    SecretRevealed {
+        hash: String,
+    },
+}
+
+impl EscrowSpec {
+    fn invalid(&self, reason: impl Into<String>) -> Error {
+        Error::InvalidEscrow {
+            id: self.id.clone(),
+            reason: reason.into(),
+        }
+    }
+
+    fn party(&self, reference: &str, accounts: &BTreeMap<String, Key>) -> Result<Key, Error> {
+        resolve_party(reference, accounts)
+            .ok_or_else(|| self.invalid(format!("unknown party `{}`", reference)))
+    }
+
+    /// The escrow with every party as a formatted key and the amount as a
+    /// decimal string, as recorded in the manifest
+    pub fn resolve(&self, accounts: &BTreeMap<String, Key>) -> Result<EscrowSpec, Error> {
+        let party = |reference: &String| {
+            self.party(reference, accounts)
+                .map(Key::to_formatted_string)
+        };
+        let amount = self
+            .amount
+            .to_u512()
+            .ok_or_else(|| self.invalid("amount is not a whole number of motes"))?;
+        let release_conditions = self
+            .release_conditions
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|condition| {
+                        Ok(match condition {
+                            ConditionSpec::AttestorSigned { attestor } => {
+                                ConditionSpec::AttestorSigned {
+                                    attestor: party(attestor)?,
+                                }
+                            }
+                            ConditionSpec::Approvals {
+                                approvers,
+                                threshold,
+                            } => ConditionSpec::Approvals {
+                                approvers: approvers.iter().map(party).collect::<Result<_, _>>()?,
+                                threshold: *threshold,
+                            },
+                            other => other.clone(),
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(EscrowSpec {
+            amount: Amount::Decimal(amount.to_string()),
+            payer: party(&self.payer)?,
+            arbiter: self.arbiter.as_ref().map(party).transpose()?,
+            release_conditions,
+            ..self.clone()
+        })
+    }
+
+    /// Constructor argument for an escrow installed by `issuer` at time `now`,
+    /// checked the way the contract checks it
+    pub fn to_config(
+        &self,
+        issuer: Key,
+        accounts: &BTreeMap<String, Key>,
+        now: u64,
+    ) -> Result<EscrowConfig, Error> {
+        let reject = |error: escrow_core::Error| self.invalid(error.description());
+        validate_id(&self.id).map_err(reject)?;
+        validate_description(&self.description).map_err(reject)?;
+
+        let amount = self
+            .amount
+            .to_u512()
+            .ok_or_else(|| self.invalid("amount is not a whole number of motes"))?;
+        if amount.is_zero() {
+            return Err(reject(escrow_core::Error::ZeroAmount));
+        }
+        let payer = self.party(&self.payer, accounts)?;
+        let arbiter = self
+            .arbiter
+            .as_ref()
+            .map(|arbiter| self.party(arbiter, accounts))
+            .transpose()?;
+        validate_parties(&issuer, &payer, arbiter.as_ref()).map_err(reject)?;
+        validate_due_date(self.due_date, now).map_err(reject)?;
+
+        let mut release_conditions = Vec::new();
+        for group in &self.release_conditions {
+            if group.is_empty() {
+                return Err(reject(escrow_core::Error::InvalidReleaseCondition));
+            }
+            let all_of = group
+                .iter()
+                .map(|condition| self.condition(condition, accounts))
+                .collect::<Result<_, _>>()?;
+            release_conditions.push(ConditionGroup { all_of });
+        }
+
+        Ok(EscrowConfig {
+            id: self.id.clone(),
+            description: self.description.clone(),
+            amount,
+            currency: self
+                .currency
+                .as_ref()
+                .map(|currency| Currency {
+                    symbol: currency.symbol.clone(),
+                    decimals: currency.decimals,
+                })
+                .unwrap_or_else(Currency::cspr),
+            payer,
+            arbiter,
+            due_date: self.due_date,
+            release_conditions,
+        })
+    }
+
+    fn condition(
+        &self,
+        condition: &ConditionSpec,
+        accounts: &BTreeMap<String, Key>,
+    ) -> Result<ReleaseCondition, Error> {
+        let malformed = || self.invalid(escrow_core::Error::InvalidReleaseCondition.description());
+        Ok(match condition {
+            ConditionSpec::PayerApproval => ReleaseCondition::PayerApproval,
+            ConditionSpec::TimeElapsed { after } => ReleaseCondition::TimeElapsed { after: *after },
+            ConditionSpec::AttestorSigned { attestor } => ReleaseCondition::AttestorSigned {
+                attestor: self.party(attestor, accounts)?,
+            },
+            ConditionSpec::Approvals {
+                approvers,
+                threshold,
+            } => {
+                let approvers = approvers
+                    .iter()
+                    .map(|approver| self.party(approver, accounts))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let distinct: BTreeSet<_> = approvers.iter().collect();
+                if distinct.len() != approvers.len()
+                    || *threshold == 0
+                    || *threshold as usize > approvers.len()
+                {
+                    return Err(malformed());
+                }
+                ReleaseCondition::Approvals {
+                    approvers,
+                    threshold: *threshold,
+                }
+            }
+            ConditionSpec::SecretRevealed { hash } => {
+                let mut bytes = [0; 32];
+                hex::decode_to_slice(hash, &mut bytes).map_err(|_| malformed())?;
+                ReleaseCondition::SecretRevealed { hash: bytes }
+            }
+        })
+    }
+}
+
+/// Key of a party reference, if it names an account or a contract package
+pub fn resolve_party(reference: &str, accounts: &BTreeMap<String, Key>) -> Option<Key> {
+    let key = match accounts.get(reference) {
+        Some(key) => *key,
+        None => Key::from_formatted_str(reference).ok().or_else(|| {
+            let public_key = PublicKey::from_hex(reference).ok()?;
+            Some(Key::Account(public_key.to_account_hash()))
+        })?,
+    };
+    matches!(key, Key::Account(_) | Key::Hash(_)).then_some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::account::AccountHash;
+
+    use super::*;
+
+    const NOW: u64 = 1_704_067_200_000;
+
+    fn issuer() -> Key {
+        Key::Account(AccountHash::new([1; 32]))
+    }
+
+    fn accounts() -> BTreeMap<String, Key> {
+        BTreeMap::from([(
+            "demo_payer".to_string(),
+            Key::Account(AccountHash::new([2; 32])),
+        )])
+    }
+
+    fn escrow() -> EscrowSpec {
+        EscrowSpec {
+            id: "INV-1".to_string(),
+            description: "Logo design".to_string(),
+            amount: Amount::Integer(5),
+            currency: None,
+            payer: "demo_payer".to_string(),
+            arbiter: None,
+            due_date: None,
+            release_conditions: Vec::new(),
+            payment: None,
+        }
+    }
+
+    fn reason(result: Result<EscrowConfig, Error>) -> String {
+        match result {
+            Err(Error::InvalidEscrow { reason, .. }) => reason,
+            other => panic!("expected an invalid escrow, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_demo_spec() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("specs/demo.toml");
+        let spec = DeploySpec::load(&path).unwrap();
+        assert_eq!(spec.escrows.len(), 2);
+        for escrow in &spec.escrows {
+            escrow.to_config(issuer(), &accounts(), NOW).unwrap();
+        }
+        let config = spec.escrows[1]
+            .to_config(issuer(), &accounts(), NOW)
+            .unwrap();
+        assert_eq!(config.amount, U512::from(25_000_000_000u64));
+        assert_eq!(config.release_conditions.len(), 2);
+        assert_eq!(config.currency, Currency::cspr());
+    }
+
+    #[test]
+    fn test_json_spec() {
+        let json = r#"{ "escrows": [{
+            "id": "INV-1", "description": "Logo design", "amount": "5",
+            "payer": "demo_payer", "due_date": 1704067300000,
+            "release_conditions": [[
+                { "type": "time_elapsed", "after": 1704067400000 },
+                { "type": "secret_revealed", "hash": "abababababababababababababababababababababababababababababababab" }
+            ]]
+        }] }"#;
+        let spec: DeploySpec = serde_json::from_str(json).unwrap();
+        let config = spec.escrows[0]
+            .to_config(issuer(), &accounts(), NOW)
+            .unwrap();
+        assert_eq!(
+            config.release_conditions[0].all_of[1],
+            ReleaseCondition::SecretRevealed { hash: [0xab; 32] }
+        );
+
+        let unknown = json.replace("\"payer\"", "\"payee\"");
+        assert!(serde_json::from_str::<DeploySpec>(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_resolve_party() {
+        let accounts = accounts();
+        assert_eq!(
+            resolve_party("demo_payer", &accounts),
+            Some(accounts["demo_payer"])
+        );
+
+        let contract = Key::Hash([3; 32]);
+        assert_eq!(
+            resolve_party(&contract.to_formatted_string(), &accounts),
+            Some(contract)
+        );
+
+        let public_key = PublicKey::from(&SecretKey::ed25519_from_bytes([4; 32]).unwrap());
+        assert_eq!(
+            resolve_party(&public_key.to_hex(), &accounts),
+            Some(Key::Account(public_key.to_account_hash()))
+        );
+
+        assert_eq!(resolve_party("someone", &accounts), None);
+        let uref = "uref-0505050505050505050505050505050505050505050505050505050505050505-007";
+        assert_eq!(resolve_party(uref, &accounts), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut spec = escrow();
+        spec.release_conditions = vec![vec![ConditionSpec::AttestorSigned {
+            attestor: "demo_payer".to_string(),
+        }]];
+        let resolved = spec.resolve(&accounts()).unwrap();
+        let payer = accounts()["demo_payer"].to_formatted_string();
+        assert_eq!(resolved.amount, Amount::Decimal("5".to_string()));
+        assert_eq!(resolved.payer, payer);
+        assert_eq!(
+            resolved.release_conditions,
+            vec![vec![ConditionSpec::AttestorSigned { attestor: payer }]]
+        );
+    }
+
+    #[test]
+    fn test_rejected_escrows() {
+        let accounts = accounts();
+        assert_eq!(
+            reason(
+                EscrowSpec {
+                    amount: Amount::Integer(0),
+                    ..escrow()
+                }
+                .to_config(issuer(), &accounts, NOW)
+            ),
+            escrow_core::Error::ZeroAmount.description()
+        );
+        assert_eq!(
+            reason(
+                EscrowSpec {
+                    amount: Amount::Decimal("1.5".to_string()),
+                    ..escrow()
+                }
+                .to_config(issuer(), &accounts, NOW)
+            ),
+            "amount is not a whole number of motes"
+        );
+        assert_eq!(
+            reason(escrow().to_config(accounts["demo_payer"], &accounts, NOW)),
+            escrow_core::Error::PayerIsIssuer.description()
+        );
+        assert_eq!(
+            reason(
+                EscrowSpec {
+                    due_date: Some(NOW),
+                    ..escrow()
+                }
+                .to_config(issuer(), &accounts, NOW)
+            ),
+            escrow_core::Error::DueDateInPast.description()
+        );
+        assert_eq!(
+            reason(
+                EscrowSpec {
+                    payer: "nobody".to_string(),
+                    ..escrow()
+                }
+                .to_config(issuer(), &accounts, NOW)
+            ),
+            "unknown party `nobody`"
+        );
+
+        let approvals = |approvers: &[&str], threshold| EscrowSpec {
+            release_conditions: vec![vec![ConditionSpec::Approvals {
+                approvers: approvers
+                    .iter()
+                    .map(|approver| approver.to_string())
+                    .collect(),
+                threshold,
+            }]],
+            ..escrow()
+        };
+        let malformed = escrow_core::Error::InvalidReleaseCondition.description();
+        assert_eq!(
+            reason(approvals(&["demo_payer"], 2).to_config(issuer(), &accounts, NOW)),
+            malformed
+        );
+        assert_eq!(
+            reason(approvals(&["demo_payer", "demo_payer"], 1).to_config(issuer(), &accounts, NOW)),
+            malformed
+        );
+        let empty_group = EscrowSpec {
+            release_conditions: vec![Vec::new()],
+            ..escrow()
+        };
+        assert_eq!(
+            reason(empty_group.to_config(issuer(), &accounts, NOW)),
+            malformed
+        );
+    }
+}
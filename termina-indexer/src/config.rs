@@ -0,0 +1,191 @@
+//! Indexer config: the node to follow and the contracts to index. TOML or
+//! JSON, picked by extension.
+
+use std::{collections::BTreeSet, fs, path::Path, time::Duration};
+
+use casper_types::{URef, URefAddr};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::error::Error;
+
+const DEFAULT_RETRY_SECS: u64 = 5;
+
+/// Read a TOML or JSON file, by extension
+pub fn read_file<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let text = fs::read_to_string(path).map_err(|error| Error::Io(path.to_path_buf(), error))?;
+    let parsed = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|error| error.to_string()),
+        Some("json") => serde_json::from_str(&text).map_err(|error| error.to_string()),
+        _ => Err("expected a .toml or .json file".to_string()),
+    };
+    parsed.map_err(|message| Error::Parse(path.to_path_buf(), message))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Node event stream: `/events` on Casper 2.0, `/events/main` on 1.x
+    pub events_url: String,
+    /// How long to wait before reconnecting to a stream that broke off
+    #[serde(default = "default_retry_secs")]
+    pub retry_secs: u64,
+    pub contracts: Vec<ContractConfig>,
+}
+
+fn default_retry_secs() -> u64 {
+    DEFAULT_RETRY_SECS
+}
+
+/// A contract whose events are indexed
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractConfig {
+    /// Name the contract's escrows are stored under
+    pub name: String,
+    pub flavour: Flavour,
+    /// The contract's `__events` named key, such as `uref-…-007`. Dictionary
+    /// writes carry it, so it is how events are told apart.
+    pub events_uref: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Flavour {
+    /// One Odra contract per escrow
+    Odra,
+    /// Many escrows in one native contract
+    Native,
+}
+
+impl Flavour {
+    pub fn name(self) -> &'static str {
+        match self {
+            Flavour::Odra => "odra",
+            Flavour::Native => "native",
+        }
+    }
+}
+
+/// A watched contract, with its events dictionary resolved
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contract {
+    pub name: String,
+    pub flavour: Flavour,
+    pub events_uref: URefAddr,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        read_file(path)
+    }
+
+    pub fn retry(&self) -> Duration {
+        Duration::from_secs(self.retry_secs)
+    }
+
+    /// The watched contracts. Names and event dictionaries must be unique.
+    pub fn contracts(&self) -> Result<Vec<Contract>, Error> {
+        if self.contracts.is_empty() {
+            return Err(Error::Config("no contracts to index".to_string()));
+        }
+        let mut names = BTreeSet::new();
+        let mut urefs = BTreeSet::new();
+        self.contracts
+            .iter()
+            .map(|contract| {
+                let uref = URef::from_formatted_str(&contract.events_uref).map_err(|_| {
+                    Error::Config(format!(
+                        "{}: {} is not a uref",
+                        contract.name, contract.events_uref
+                    ))
+                })?;
+                if !names.insert(&contract.name) {
+                    return Err(Error::Config(format!(
+                        "contract {} is listed twice",
+                        contract.name
+                    )));
+                }
+                if !urefs.insert(uref.addr()) {
+                    return Err(Error::Config(format!(
+                        "{}: {} is already indexed",
+                        contract.name, contract.events_uref
+                    )));
+                }
+                Ok(Contract {
+                    name: contract.name.clone(),
+                    flavour: contract.flavour,
+                    events_uref: uref.addr(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UREF: &str = "uref-0909090909090909090909090909090909090909090909090909090909090909-007";
+
+    fn config(text: &str) -> Config {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_contracts() {
+        let config = config(&format!(
+            r#"
+            events_url = "http://localhost:9999/events"
+
+            [[contracts]]
+            name = "invoices"
+            flavour = "native"
+            events_uref = "{}"
+            "#,
+            UREF
+        ));
+        assert_eq!(config.retry(), Duration::from_secs(DEFAULT_RETRY_SECS));
+        assert_eq!(
+            config.contracts().unwrap(),
+            [Contract {
+                name: "invoices".to_string(),
+                flavour: Flavour::Native,
+                events_uref: [9; 32],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rejected_contracts() {
+        let duplicate = config(&format!(
+            r#"
+            events_url = "http://localhost:9999/events"
+
+            [[contracts]]
+            name = "INV-001"
+            flavour = "odra"
+            events_uref = "{uref}"
+
+            [[contracts]]
+            name = "INV-002"
+            flavour = "odra"
+            events_uref = "{uref}"
+            "#,
+            uref = UREF
+        ));
+        assert!(matches!(duplicate.contracts(), Err(Error::Config(_))));
+
+        let not_a_uref = config(
+            r#"
+            events_url = "http://localhost:9999/events"
+
+            [[contracts]]
+            name = "INV-001"
+            flavour = "odra"
+            events_uref = "hash-09"
+            "#,
+        );
+        assert!(matches!(not_a_uref.contracts(), Err(Error::Config(_))));
+        assert!(toml::from_str::<Config>(r#"events_url = "x""#).is_err());
+    }
+}
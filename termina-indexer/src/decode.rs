@@ -0,0 +1,259 @@
+//! Termina events in a node's processed-deploy messages
+//!
+//! Both contracts store each event in their `__events` dictionary, so an
+//! event shows up as a dictionary write among the deploy's effects. The
+//! written value names the dictionary's seed uref, which tells the watched
+//! contracts apart, and the item key, which is the event's index.
+
+use casper_types::{
+    bytesrepr::{self, Bytes, FromBytes},
+    CLValue, Key, U512,
+};
+use serde_json::{json, Value};
+use termina_client::events::{decode_event, EscrowEvent};
+
+use crate::config::Contract;
+
+/// A deploy that executed, with the Termina events it emitted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Processed {
+    pub deploy_hash: String,
+    pub block_hash: String,
+    pub timestamp: String,
+    /// Empty if the deploy failed or touched no watched contract
+    pub events: Vec<Emitted>,
+}
+
+/// An event of a watched contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Emitted {
+    /// Name of the contract in the config
+    pub contract: String,
+    /// Index of the event among all the contract's events
+    pub index: u32,
+    pub event: EscrowEvent,
+}
+
+/// Decode the data of a stream message. Messages other than a processed
+/// deploy or transaction give `None`.
+pub fn decode_message(data: &str, contracts: &[Contract]) -> Result<Option<Processed>, String> {
+    let message: Value =
+        serde_json::from_str(data).map_err(|error| format!("not JSON: {}", error))?;
+    let (processed, deploy_hash) = if let Some(processed) = message.get("DeployProcessed") {
+        (processed, processed.get("deploy_hash"))
+    } else if let Some(processed) = message.get("TransactionProcessed") {
+        // `{"Deploy": hash}` or `{"Version1": hash}`
+        let hash = processed
+            .get("transaction_hash")
+            .and_then(Value::as_object)
+            .and_then(|hash| hash.values().next());
+        (processed, hash)
+    } else {
+        return Ok(None);
+    };
+    let deploy_hash = deploy_hash
+        .and_then(Value::as_str)
+        .ok_or("no deploy hash")?;
+    let field = |name: &str| {
+        processed
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| format!("deploy {}: no {}", deploy_hash, name))
+    };
+
+    let mut events = Vec::new();
+    let execution_result = processed
+        .get("execution_result")
+        .ok_or_else(|| format!("deploy {}: no execution result", deploy_hash))?;
+    for bytes in written_values(execution_result)? {
+        let bytes = hex::decode(bytes)
+            .map_err(|_| format!("deploy {}: written value is not hex", deploy_hash))?;
+        if let Some(emitted) = decode_write(&bytes, contracts)
+            .map_err(|message| format!("deploy {}: {}", deploy_hash, message))?
+        {
+            events.push(emitted);
+        }
+    }
+    Ok(Some(Processed {
+        deploy_hash: deploy_hash.to_string(),
+        block_hash: field("block_hash")?,
+        timestamp: field("timestamp")?,
+        events,
+    }))
+}
+
+/// Hex bytes of every value a successful execution wrote, in order. A failed
+/// execution wrote nothing that stays.
+fn written_values(execution_result: &Value) -> Result<Vec<&str>, String> {
+    if let Some(result) = execution_result.get("Version2") {
+        // Casper 2.0
+        if result
+            .get("error_message")
+            .is_some_and(|error| !error.is_null())
+        {
+            return Ok(Vec::new());
+        }
+        return Ok(written(
+            result.get("effects"),
+            &["kind", "Write", "CLValue"],
+        ));
+    }
+    // Casper 1.x, also wrapped as `Version1` by 2.0 nodes for old blocks
+    let result = execution_result.get("Version1").unwrap_or(execution_result);
+    if let Some(success) = result.get("Success") {
+        let transforms = success
+            .get("effect")
+            .and_then(|effect| effect.get("transforms"));
+        return Ok(written(transforms, &["transform", "WriteCLValue"]));
+    }
+    if result.get("Failure").is_some() {
+        return Ok(Vec::new());
+    }
+    Err("unknown execution result".to_string())
+}
+
+/// Bytes of the CLValue at `path` in each effect that has one
+fn written<'a>(effects: Option<&'a Value>, path: &[&str]) -> Vec<&'a str> {
+    effects
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|effect| {
+            path.iter()
+                .try_fold(effect, |value, field| value.get(field))?
+                .get("bytes")?
+                .as_str()
+        })
+        .collect()
+}
+
+/// The event in a written value, if it is an item of a watched events
+/// dictionary
+fn decode_write(bytes: &[u8], contracts: &[Contract]) -> Result<Option<Emitted>, String> {
+    let Some((value, seed_uref, item_key)) = dictionary_value(bytes) else {
+        return Ok(None);
+    };
+    let Some(contract) = contracts
+        .iter()
+        .find(|contract| contract.events_uref[..] == seed_uref[..])
+    else {
+        return Ok(None);
+    };
+    let index = std::str::from_utf8(&item_key)
+        .ok()
+        .and_then(|key| key.parse().ok())
+        .ok_or_else(|| format!("{}: {:?} is not an event index", contract.name, item_key))?;
+    let event = bytesrepr::deserialize_from_slice::<_, Bytes>(value.inner_bytes())
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| decode_event(&bytes).map_err(|error| error.to_string()))
+        .map_err(|message| format!("{} event {}: {}", contract.name, index, message))?;
+    Ok(Some(Emitted {
+        contract: contract.name.clone(),
+        index,
+        event,
+    }))
+}
+
+/// Split a stored dictionary item into its value, seed uref and item key.
+/// Anything else written doesn't parse as one.
+fn dictionary_value(bytes: &[u8]) -> Option<(CLValue, Bytes, Bytes)> {
+    let (value, rest) = CLValue::from_bytes(bytes).ok()?;
+    let (seed_uref, rest) = Bytes::from_bytes(rest).ok()?;
+    let (item_key, rest) = Bytes::from_bytes(rest).ok()?;
+    (rest.is_empty() && seed_uref.len() == 32).then_some((value, seed_uref, item_key))
+}
+
+/// Fields of an event as JSON: parties as formatted keys, amounts as
+/// decimal strings
+pub fn event_json(event: &EscrowEvent) -> Value {
+    let key = |key: &Key| key.to_formatted_string();
+    let amount = |amount: &U512| amount.to_string();
+    match event {
+        EscrowEvent::EscrowCreated {
+            escrow_id,
+            issuer,
+            payer,
+            amount: value,
+            currency_symbol,
+            currency_decimals,
+        } => json!({
+            "escrow_id": escrow_id,
+            "issuer": key(issuer),
+            "payer": key(payer),
+            "amount": amount(value),
+            "currency_symbol": currency_symbol,
+            "currency_decimals": currency_decimals,
+        }),
+        EscrowEvent::EscrowAccepted { escrow_id, payer } => json!({
+            "escrow_id": escrow_id,
+            "payer": key(payer),
+        }),
+        EscrowEvent::FundsDeposited {
+            escrow_id,
+            payer,
+            amount: value,
+        } => json!({
+            "escrow_id": escrow_id,
+            "payer": key(payer),
+            "amount": amount(value),
+        }),
+        EscrowEvent::FundsReleased {
+            escrow_id,
+            receiver,
+            amount: value,
+        } => json!({
+            "escrow_id": escrow_id,
+            "receiver": key(receiver),
+            "amount": amount(value),
+        }),
+        EscrowEvent::EscrowCancelled {
+            escrow_id,
+            cancelled_by,
+        } => json!({
+            "escrow_id": escrow_id,
+            "cancelled_by": key(cancelled_by),
+        }),
+        EscrowEvent::DisputeRaised {
+            escrow_id,
+            raised_by,
+            reason,
+        } => json!({
+            "escrow_id": escrow_id,
+            "raised_by": key(raised_by),
+            "reason": reason,
+        }),
+        EscrowEvent::DisputeResolved {
+            escrow_id,
+            resolved_by,
+            release_to_receiver,
+        } => json!({
+            "escrow_id": escrow_id,
+            "resolved_by": key(resolved_by),
+            "release_to_receiver": release_to_receiver,
+        }),
+        EscrowEvent::ReleaseApproved {
+            escrow_id,
+            approver,
+        } => json!({
+            "escrow_id": escrow_id,
+            "approver": key(approver),
+        }),
+        EscrowEvent::SecretRevealed {
+            escrow_id,
+            revealed_by,
+        } => json!({
+            "escrow_id": escrow_id,
+            "revealed_by": key(revealed_by),
+        }),
+        EscrowEvent::FundsSwept {
+            escrow_id,
+            recipient,
+            amount: value,
+        } => json!({
+            "escrow_id": escrow_id,
+            "recipient": key(recipient),
+            "amount": amount(value),
+        }),
+    }
+}
@@ -0,0 +1,39 @@
+use std::{fmt, io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read
+    Io(PathBuf, io::Error),
+    /// A config file doesn't parse
+    Parse(PathBuf, String),
+    /// The config names contracts the indexer can't follow
+    Config(String),
+    /// The event stream couldn't be opened or broke off; worth retrying
+    Stream(String),
+    /// A message from a watched contract doesn't decode. The cursor stays
+    /// before it, so the indexer stops rather than skip history.
+    Decode { id: u64, message: String },
+    /// The database rejected a read or write
+    Database(rusqlite::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            Error::Parse(path, message) => write!(f, "{}: {}", path.display(), message),
+            Error::Config(message) => write!(f, "config: {}", message),
+            Error::Stream(message) => write!(f, "event stream: {}", message),
+            Error::Decode { id, message } => write!(f, "message {}: {}", id, message),
+            Error::Database(error) => write!(f, "database: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Error::Database(error)
+    }
+}
@@ -0,0 +1,84 @@
+//! Materializes Termina escrow history from a Casper node's event stream
+//! into SQLite
+//!
+//! The indexer follows the node's SSE stream, picks the events of the
+//! watched contracts out of every processed deploy, and writes escrows,
+//! their transitions and their parties to the [`store`]. Both contract
+//! flavours emit the same events, decoded by [`termina_client::events`].
+//! Progress is kept per stream, so a restarted indexer asks the node to
+//! replay from where it stopped.
+
+pub mod config;
+pub mod decode;
+mod error;
+pub mod sse;
+pub mod store;
+
+use std::{io::BufRead, thread, time::Duration};
+
+pub use error::Error;
+
+use crate::{config::Contract, decode::decode_message, sse::Reader, store::Store};
+
+/// What one pass over a stream indexed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Messages past the cursor, whether or not they held events
+    pub messages: u64,
+    /// Events not indexed before
+    pub events: usize,
+}
+
+/// Index a stream until it ends. Messages at or before the stream's cursor
+/// are skipped, so a replayed stream only adds what is new.
+pub fn index<R: BufRead>(
+    store: &mut Store,
+    contracts: &[Contract],
+    stream: &str,
+    reader: R,
+) -> Result<Progress, Error> {
+    let mut progress = Progress::default();
+    let mut cursor = store.cursor(stream)?;
+    for message in Reader::new(reader) {
+        let message = message.map_err(|error| Error::Stream(error.to_string()))?;
+        // Only the opening `ApiVersion` message comes without an id, and it
+        // holds nothing to index
+        let Some(id) = message.id else {
+            continue;
+        };
+        if cursor.is_some_and(|cursor| id <= cursor) {
+            continue;
+        }
+        let processed = decode_message(&message.data, contracts)
+            .map_err(|message| Error::Decode { id, message })?;
+        progress.events += store.apply(stream, id, processed.as_ref())?;
+        progress.messages += 1;
+        cursor = Some(id);
+    }
+    Ok(progress)
+}
+
+/// Follow a node's event stream for good, reconnecting `retry` after it
+/// breaks off. The node replays from the cursor, which is skipped as
+/// indexed. Returns only on an error that reconnecting won't fix.
+pub fn follow(
+    store: &mut Store,
+    contracts: &[Contract],
+    events_url: &str,
+    retry: Duration,
+) -> Result<(), Error> {
+    loop {
+        let cursor = store.cursor(events_url)?;
+        let result = sse::connect(events_url, cursor)
+            .and_then(|reader| index(store, contracts, events_url, reader));
+        match result {
+            Ok(progress) => println!(
+                "{}: stream ended after {} messages, {} new events",
+                events_url, progress.messages, progress.events
+            ),
+            Err(Error::Stream(message)) => eprintln!("{}: {}", events_url, message),
+            Err(error) => return Err(error),
+        }
+        thread::sleep(retry);
+    }
+}
@@ -0,0 +1,60 @@
+//! Follows a node's event stream and indexes Termina escrows into SQLite
+//!
+//! ```sh
+//! cargo run --manifest-path termina-indexer/Cargo.toml -- \
+//!     --config termina-indexer/config/example.toml \
+//!     --database termina-index.sqlite
+//! ```
+//!
+//! With `--fixture`, a recorded stream is indexed from a file instead and
+//! the indexer exits at its end. Otherwise it reconnects whenever the stream
+//! breaks off, resuming from the last message it indexed.
+
+use std::{fs::File, io::BufReader, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use termina_indexer::{config::Config, follow, index, store::Store, Error};
+
+#[derive(Parser)]
+#[command(about = "Index Termina escrow events into SQLite")]
+struct Args {
+    /// Node event stream and contracts to index
+    #[arg(long)]
+    config: PathBuf,
+    /// SQLite database, created if missing
+    #[arg(long, default_value = "termina-index.sqlite")]
+    database: PathBuf,
+    /// Index a recorded event stream instead of following the node
+    #[arg(long)]
+    fixture: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    let config = Config::load(&args.config)?;
+    let contracts = config.contracts()?;
+    let mut store = Store::open(&args.database)?;
+    store.register(&contracts)?;
+
+    if let Some(path) = args.fixture {
+        let file = File::open(&path).map_err(|error| Error::Io(path.clone(), error))?;
+        let stream = path.display().to_string();
+        let progress = index(&mut store, &contracts, &stream, BufReader::new(file))?;
+        println!(
+            "{}: {} messages, {} new events",
+            stream, progress.messages, progress.events
+        );
+        return Ok(());
+    }
+
+    follow(&mut store, &contracts, &config.events_url, config.retry())
+}
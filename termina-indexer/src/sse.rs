@@ -0,0 +1,124 @@
+//! Server-sent events, as a Casper node streams them
+//!
+//! Each message is a `data:` line, preceded by an `id:` line for everything
+//! but the opening `ApiVersion` message, and ended by a blank line. A node
+//! replays its buffered messages from an id given as `start_from`.
+
+use std::io::{self, BufRead, BufReader};
+
+use crate::error::Error;
+
+/// One message of the stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub id: Option<u64>,
+    pub data: String,
+}
+
+/// Messages read from a stream, until it ends
+pub struct Reader<R> {
+    reader: R,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(reader: R) -> Self {
+        Reader { reader }
+    }
+
+    fn next_message(&mut self) -> io::Result<Option<Message>> {
+        let mut id = None;
+        let mut data: Option<String> = None;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                // A message cut off by the end of the stream is dropped, as
+                // the spec has it
+                return Ok(None);
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                match data.take() {
+                    Some(data) => return Ok(Some(Message { id, data })),
+                    None => {
+                        id = None;
+                        continue;
+                    }
+                }
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "data" => match &mut data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => data = Some(value.to_string()),
+                },
+                "id" => id = value.parse().ok(),
+                // Comments (keep-alives) and fields the node doesn't send
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = io::Result<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_message().transpose()
+    }
+}
+
+/// Open a node's event stream, replaying from `start_from` if given
+pub fn connect(events_url: &str, start_from: Option<u64>) -> Result<impl BufRead, Error> {
+    let mut request = ureq::get(events_url).set("Accept", "text/event-stream");
+    if let Some(id) = start_from {
+        request = request.query("start_from", &id.to_string());
+    }
+    let response = request
+        .call()
+        .map_err(|error| Error::Stream(format!("{}: {}", events_url, error)))?;
+    Ok(BufReader::new(response.into_reader()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_messages() {
+        let stream = "data:{\"ApiVersion\":\"2.0.0\"}\n\n\
+            : keep-alive\n\
+            id:7\n\
+            data:{\"BlockAdded\":{}}\n\n\
+            \n\
+            id: 8\r\n\
+            data: first\r\n\
+            data: second\r\n\r\n\
+            id:9\n\
+            data:cut off";
+        let messages = Reader::new(stream.as_bytes())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            messages,
+            [
+                Message {
+                    id: None,
+                    data: "{\"ApiVersion\":\"2.0.0\"}".to_string(),
+                },
+                Message {
+                    id: Some(7),
+                    data: "{\"BlockAdded\":{}}".to_string(),
+                },
+                Message {
+                    id: Some(8),
+                    data: "first\nsecond".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+//! SQLite tables the indexer materializes
+//!
+//! - `events`: every indexed event, once, with its deploy and JSON fields
+//! - `escrows`: each escrow's current state, amount and currency
+//! - `transitions`: the state changes, one per event that made one
+//! - `parties`: who took part in each escrow, and as what
+//! - `cursors`: the last stream message indexed, per stream
+//!
+//! A stream message is applied in one transaction together with its cursor,
+//! so an indexer stopped at any point resumes after the last message it
+//! fully applied. Replayed events are recognized and skipped.
+
+use std::path::Path;
+
+use casper_types::{AccessRights, Key, URef, URefAddr};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Transaction};
+use termina_client::{events::EscrowEvent, State};
+
+use crate::{
+    config::Contract,
+    decode::{event_json, Emitted, Processed},
+    error::Error,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS contracts (
+    name TEXT PRIMARY KEY,
+    flavour TEXT NOT NULL,
+    events_uref TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY,
+    contract TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    escrow_id TEXT NOT NULL,
+    fields TEXT NOT NULL,
+    deploy_hash TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    UNIQUE (contract, event_index)
+);
+CREATE TABLE IF NOT EXISTS escrows (
+    contract TEXT NOT NULL,
+    escrow_id TEXT NOT NULL,
+    state TEXT NOT NULL,
+    amount TEXT,
+    currency_symbol TEXT,
+    currency_decimals INTEGER,
+    created_at TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (contract, escrow_id)
+);
+CREATE TABLE IF NOT EXISTS transitions (
+    event_id INTEGER PRIMARY KEY REFERENCES events (id),
+    contract TEXT NOT NULL,
+    escrow_id TEXT NOT NULL,
+    from_state TEXT,
+    to_state TEXT NOT NULL,
+    deploy_hash TEXT NOT NULL,
+    timestamp TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS parties (
+    contract TEXT NOT NULL,
+    escrow_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    party TEXT NOT NULL,
+    PRIMARY KEY (contract, escrow_id, role, party)
+);
+CREATE TABLE IF NOT EXISTS cursors (
+    stream TEXT PRIMARY KEY,
+    message_id INTEGER NOT NULL
+);
+";
+
+/// An escrow as the events so far leave it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowRow {
+    pub contract: String,
+    pub escrow_id: String,
+    pub state: String,
+    /// Unknown for an escrow created before the stream was followed
+    pub amount: Option<String>,
+    pub currency_symbol: Option<String>,
+    pub updated_at: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransitionRow {
+    pub from_state: Option<String>,
+    pub to_state: String,
+    pub deploy_hash: String,
+}
+
+/// An indexed event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventRow {
+    /// Row id, increasing in the order events were indexed
+    pub id: i64,
+    pub contract: String,
+    /// Address of the contract's events dictionary, as registered
+    pub events_uref: URefAddr,
+    /// Index among the contract's events
+    pub event_index: u32,
+    pub name: String,
+    pub escrow_id: String,
+    /// The event's fields as JSON
+    pub fields: String,
+    /// State the event moved the escrow to, if it changed the state
+    pub state: Option<String>,
+    pub deploy_hash: String,
+    pub block_hash: String,
+    pub timestamp: String,
+}
+
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Store::new(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Store::new(Connection::open_in_memory()?)
+    }
+
+    fn new(connection: Connection) -> Result<Self, Error> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Store { connection })
+    }
+
+    /// Record the watched contracts, so the tables can be read without the
+    /// config
+    pub fn register(&self, contracts: &[Contract]) -> Result<(), Error> {
+        for contract in contracts {
+            self.connection.execute(
+                "INSERT INTO contracts (name, flavour, events_uref) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (name) DO UPDATE SET flavour = ?2, events_uref = ?3",
+                params![
+                    contract.name,
+                    contract.flavour.name(),
+                    URef::new(contract.events_uref, AccessRights::READ_ADD_WRITE)
+                        .to_formatted_string(),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Id of the last message indexed from a stream
+    pub fn cursor(&self, stream: &str) -> Result<Option<u64>, Error> {
+        let id = self
+            .connection
+            .query_row(
+                "SELECT message_id FROM cursors WHERE stream = ?1",
+                [stream],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        Ok(id.map(|id| id as u64))
+    }
+
+    /// Apply a stream message and move the stream's cursor to it. Returns the
+    /// number of events not indexed before.
+    pub fn apply(
+        &mut self,
+        stream: &str,
+        message_id: u64,
+        processed: Option<&Processed>,
+    ) -> Result<usize, Error> {
+        let transaction = self.connection.transaction()?;
+        let mut indexed = 0;
+        if let Some(processed) = processed {
+            for emitted in &processed.events {
+                if apply_event(&transaction, processed, emitted)? {
+                    indexed += 1;
+                }
+            }
+        }
+        transaction.execute(
+            "INSERT INTO cursors (stream, message_id) VALUES (?1, ?2)
+             ON CONFLICT (stream) DO UPDATE SET message_id = ?2",
+            params![stream, message_id as i64],
+        )?;
+        transaction.commit()?;
+        Ok(indexed)
+    }
+
+    pub fn escrow(&self, contract: &str, escrow_id: &str) -> Result<Option<EscrowRow>, Error> {
+        let escrow = self
+            .connection
+            .query_row(
+                "SELECT state, amount, currency_symbol, updated_at FROM escrows
+                 WHERE contract = ?1 AND escrow_id = ?2",
+                [contract, escrow_id],
+                |row| {
+                    Ok(EscrowRow {
+                        contract: contract.to_string(),
+                        escrow_id: escrow_id.to_string(),
+                        state: row.get(0)?,
+                        amount: row.get(1)?,
+                        currency_symbol: row.get(2)?,
+                        updated_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(escrow)
+    }
+
+    /// State changes of an escrow, oldest first
+    pub fn transitions(
+        &self,
+        contract: &str,
+        escrow_id: &str,
+    ) -> Result<Vec<TransitionRow>, Error> {
+        let mut statement = self.connection.prepare(
+            "SELECT from_state, to_state, deploy_hash FROM transitions
+             WHERE contract = ?1 AND escrow_id = ?2 ORDER BY event_id",
+        )?;
+        let rows = statement.query_map([contract, escrow_id], |row| {
+            Ok(TransitionRow {
+                from_state: row.get(0)?,
+                to_state: row.get(1)?,
+                deploy_hash: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Parties of an escrow as (role, formatted key) pairs
+    pub fn parties(&self, contract: &str, escrow_id: &str) -> Result<Vec<(String, String)>, Error> {
+        let mut statement = self.connection.prepare(
+            "SELECT role, party FROM parties
+             WHERE contract = ?1 AND escrow_id = ?2 ORDER BY role, party",
+        )?;
+        let rows =
+            statement.query_map([contract, escrow_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Events indexed after the row id `after`, oldest first. Only events of
+    /// registered contracts are returned.
+    pub fn events_after(&self, after: i64, limit: usize) -> Result<Vec<EventRow>, Error> {
+        let mut statement = self.connection.prepare(
+            "SELECT events.id, events.contract, event_index, events.name, events.escrow_id, fields,
+                    to_state, events.deploy_hash, block_hash, events.timestamp, events_uref
+             FROM events
+             JOIN contracts ON contracts.name = events.contract
+             LEFT JOIN transitions ON transitions.event_id = events.id
+             WHERE events.id > ?1 ORDER BY events.id LIMIT ?2",
+        )?;
+        let rows = statement.query_map(params![after, limit as i64], |row| {
+            let events_uref =
+                URef::from_formatted_str(&row.get::<_, String>(10)?).map_err(|error| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        10,
+                        Type::Text,
+                        error.to_string().into(),
+                    )
+                })?;
+            Ok(EventRow {
+                id: row.get(0)?,
+                contract: row.get(1)?,
+                events_uref: events_uref.addr(),
+                event_index: row.get(2)?,
+                name: row.get(3)?,
+                escrow_id: row.get(4)?,
+                fields: row.get(5)?,
+                state: row.get(6)?,
+                deploy_hash: row.get(7)?,
+                block_hash: row.get(8)?,
+                timestamp: row.get(9)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+/// Index one event, unless it already is. Returns whether it was new.
+fn apply_event(
+    transaction: &Transaction,
+    processed: &Processed,
+    emitted: &Emitted,
+) -> Result<bool, Error> {
+    let event = &emitted.event;
+    let contract = emitted.contract.as_str();
+    let escrow_id = event.escrow_id();
+    let inserted = transaction.execute(
+        "INSERT OR IGNORE INTO events
+             (contract, event_index, name, escrow_id, fields, deploy_hash, block_hash, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            contract,
+            emitted.index,
+            event.name(),
+            escrow_id,
+            event_json(event).to_string(),
+            processed.deploy_hash,
+            processed.block_hash,
+            processed.timestamp,
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(false);
+    }
+    let event_id = transaction.last_insert_rowid();
+
+    let current = transaction
+        .query_row(
+            "SELECT state FROM escrows WHERE contract = ?1 AND escrow_id = ?2",
+            [contract, escrow_id],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?
+        .and_then(|name| State::ALL.into_iter().find(|state| state.name() == name));
+    if let Some(next) = event.next_state(current) {
+        transaction.execute(
+            "INSERT INTO escrows (contract, escrow_id, state, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (contract, escrow_id) DO UPDATE SET state = ?3, updated_at = ?4",
+            params![contract, escrow_id, next.name(), processed.timestamp],
+        )?;
+        transaction.execute(
+            "INSERT INTO transitions
+                 (event_id, contract, escrow_id, from_state, to_state, deploy_hash, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                event_id,
+                contract,
+                escrow_id,
+                current.map(State::name),
+                next.name(),
+                processed.deploy_hash,
+                processed.timestamp,
+            ],
+        )?;
+    }
+    if let EscrowEvent::EscrowCreated {
+        amount,
+        currency_symbol,
+        currency_decimals,
+        ..
+    } = event
+    {
+        transaction.execute(
+            "UPDATE escrows SET amount = ?3, currency_symbol = ?4, currency_decimals = ?5,
+                 created_at = ?6
+             WHERE contract = ?1 AND escrow_id = ?2",
+            params![
+                contract,
+                escrow_id,
+                amount.to_string(),
+                currency_symbol,
+                currency_decimals,
+                processed.timestamp,
+            ],
+        )?;
+    }
+    for (role, party) in parties(event) {
+        transaction.execute(
+            "INSERT OR IGNORE INTO parties (contract, escrow_id, role, party)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![contract, escrow_id, role, party.to_formatted_string()],
+        )?;
+    }
+    Ok(true)
+}
+
+/// Parties an event names, by role. Whoever resolves a dispute is the
+/// arbiter; the other actors are already recorded or not parties.
+fn parties(event: &EscrowEvent) -> Vec<(&'static str, &Key)> {
+    match event {
+        EscrowEvent::EscrowCreated { issuer, payer, .. } => {
+            vec![("issuer", issuer), ("payer", payer)]
+        }
+        EscrowEvent::EscrowAccepted { payer, .. } | EscrowEvent::FundsDeposited { payer, .. } => {
+            vec![("payer", payer)]
+        }
+        EscrowEvent::DisputeResolved { resolved_by, .. } => vec![("arbiter", resolved_by)],
+        EscrowEvent::ReleaseApproved { approver, .. } => vec![("approver", approver)],
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,516 @@
+//! Indexing of recorded node event streams. The fixtures are what a 1.x and
+//! a 2.0 node stream for an Odra and a native escrow; regenerate them after
+//! an intended change with `UPDATE_FIXTURES=1 cargo test`.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::PathBuf,
+    thread,
+};
+
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{Bytes, ToBytes},
+    AccessRights, CLValue, Key, URef, U512,
+};
+use serde_json::{json, Value};
+use termina_client::events::EscrowEvent;
+use termina_indexer::{
+    config::{Contract, Flavour},
+    index, sse,
+    store::{Store, TransitionRow},
+    Progress,
+};
+
+const ODRA_EVENTS: [u8; 32] = [1; 32];
+const NATIVE_EVENTS: [u8; 32] = [2; 32];
+/// Events dictionary of a contract nobody watches
+const OTHER_EVENTS: [u8; 32] = [3; 32];
+const TIMESTAMP: &str = "2024-01-01T00:00:00.000Z";
+
+fn contracts() -> Vec<Contract> {
+    vec![
+        Contract {
+            name: "INV-001".to_string(),
+            flavour: Flavour::Odra,
+            events_uref: ODRA_EVENTS,
+        },
+        Contract {
+            name: "invoices".to_string(),
+            flavour: Flavour::Native,
+            events_uref: NATIVE_EVENTS,
+        },
+    ]
+}
+
+fn account(seed: u8) -> Key {
+    Key::Account(AccountHash::new([seed; 32]))
+}
+
+fn issuer() -> Key {
+    account(1)
+}
+
+fn payer() -> Key {
+    account(2)
+}
+
+fn arbiter() -> Key {
+    account(3)
+}
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn read_fixture(name: &str) -> String {
+    let path = fixture_path(name);
+    fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing {}, run with UPDATE_FIXTURES=1", path.display()))
+}
+
+/// A write of event `index` to an events dictionary, as its key and the hex
+/// bytes of the stored dictionary value
+fn event_write(seed: [u8; 32], index: u32, event: &EscrowEvent) -> (String, String) {
+    let item_key = index.to_string();
+    let key = Key::dictionary(
+        URef::new(seed, AccessRights::READ_ADD_WRITE),
+        item_key.as_bytes(),
+    );
+    let mut bytes = CLValue::from_t(Bytes::from(event.to_bytes().unwrap()))
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    bytes.extend(Bytes::from(seed.to_vec()).to_bytes().unwrap());
+    bytes.extend(Bytes::from(item_key.into_bytes()).to_bytes().unwrap());
+    (key.to_formatted_string(), hex::encode(bytes))
+}
+
+/// A write of the events length next to each event
+fn length_write(length: u32) -> (String, String) {
+    (
+        URef::new([4; 32], AccessRights::READ_ADD_WRITE).to_formatted_string(),
+        hex::encode(CLValue::from_t(length).unwrap().inner_bytes()),
+    )
+}
+
+fn created(escrow_id: &str) -> EscrowEvent {
+    EscrowEvent::EscrowCreated {
+        escrow_id: escrow_id.to_string(),
+        issuer: issuer(),
+        payer: payer(),
+        amount: U512::from(5_000_000_000u64),
+        currency_symbol: "CSPR".to_string(),
+        currency_decimals: 9,
+    }
+}
+
+fn accepted(escrow_id: &str) -> EscrowEvent {
+    EscrowEvent::EscrowAccepted {
+        escrow_id: escrow_id.to_string(),
+        payer: payer(),
+    }
+}
+
+fn deposited(escrow_id: &str) -> EscrowEvent {
+    EscrowEvent::FundsDeposited {
+        escrow_id: escrow_id.to_string(),
+        payer: payer(),
+        amount: U512::from(5_000_000_000u64),
+    }
+}
+
+fn released(escrow_id: &str, receiver: Key) -> EscrowEvent {
+    EscrowEvent::FundsReleased {
+        escrow_id: escrow_id.to_string(),
+        receiver,
+        amount: U512::from(5_000_000_000u64),
+    }
+}
+
+fn hash(seed: u8) -> String {
+    hex::encode([seed; 32])
+}
+
+/// A deploy as a 1.x node streams it, with the transforms it made
+fn deploy_processed(seed: u8, writes: &[(String, String)], error: Option<&str>) -> Value {
+    let transforms: Vec<Value> = writes
+        .iter()
+        .map(|(key, bytes)| {
+            json!({
+                "key": key,
+                "transform": { "WriteCLValue": { "cl_type": "Any", "bytes": bytes, "parsed": null } },
+            })
+        })
+        .chain([json!({ "key": Key::Hash([5; 32]).to_formatted_string(), "transform": "Identity" })])
+        .collect();
+    let effect = json!({ "operations": [], "transforms": transforms });
+    let execution_result = match error {
+        None => json!({ "Success": { "effect": effect, "transfers": [], "cost": "2500000000" } }),
+        Some(message) => json!({
+            "Failure": { "effect": effect, "transfers": [], "cost": "2500000000", "error_message": message },
+        }),
+    };
+    json!({
+        "DeployProcessed": {
+            "deploy_hash": hash(seed),
+            "account": "01".to_string() + &hash(7),
+            "timestamp": TIMESTAMP,
+            "ttl": "30m",
+            "dependencies": [],
+            "block_hash": hash(seed + 100),
+            "execution_result": execution_result,
+        }
+    })
+}
+
+/// A deploy as a 2.0 node streams it, with the effects it had
+fn transaction_processed(seed: u8, writes: &[(String, String)], error: Option<&str>) -> Value {
+    let effects: Vec<Value> = writes
+        .iter()
+        .map(|(key, bytes)| {
+            json!({
+                "key": key,
+                "kind": { "Write": { "CLValue": { "cl_type": "Any", "bytes": bytes, "parsed": null } } },
+            })
+        })
+        .chain([json!({ "key": Key::Hash([5; 32]).to_formatted_string(), "kind": "Identity" })])
+        .collect();
+    json!({
+        "TransactionProcessed": {
+            "transaction_hash": { "Deploy": hash(seed) },
+            "initiator_addr": { "PublicKey": "01".to_string() + &hash(7) },
+            "timestamp": TIMESTAMP,
+            "ttl": "30m",
+            "block_hash": hash(seed + 100),
+            "execution_result": {
+                "Version2": {
+                    "initiator": { "PublicKey": "01".to_string() + &hash(7) },
+                    "error_message": error,
+                    "current_price": 1,
+                    "limit": "2500000000",
+                    "consumed": "1000000000",
+                    "cost": "2500000000",
+                    "refund": "0",
+                    "transfers": [],
+                    "size_estimate": 200,
+                    "effects": effects,
+                }
+            },
+            "messages": [],
+        }
+    })
+}
+
+fn sse(api_version: &str, messages: Vec<Value>) -> String {
+    let mut text = format!("data:{}\n\n", json!({ "ApiVersion": api_version }));
+    for (id, message) in messages.into_iter().enumerate() {
+        text.push_str(&format!("id:{}\ndata:{}\n\n", id + 1, message));
+    }
+    text
+}
+
+/// An Odra escrow from install to release on a 1.x node, with a failed
+/// release before the approval and an event of an unwatched contract
+fn odra_stream() -> String {
+    let id = "INV-001";
+    let write = |index, event: EscrowEvent| event_write(ODRA_EVENTS, index, &event);
+    sse(
+        "1.5.8",
+        vec![
+            json!({ "BlockAdded": { "block_hash": hash(99) } }),
+            deploy_processed(1, &[write(0, created(id)), length_write(1)], None),
+            deploy_processed(2, &[write(1, accepted(id)), length_write(2)], None),
+            deploy_processed(3, &[write(2, deposited(id)), length_write(3)], None),
+            deploy_processed(4, &[], Some("User error: 9")),
+            deploy_processed(
+                5,
+                &[
+                    write(
+                        3,
+                        EscrowEvent::ReleaseApproved {
+                            escrow_id: id.to_string(),
+                            approver: payer(),
+                        },
+                    ),
+                    event_write(OTHER_EVENTS, 0, &created("INV-999")),
+                ],
+                None,
+            ),
+            deploy_processed(6, &[write(4, released(id, issuer()))], None),
+        ],
+    )
+}
+
+/// A native escrow on a 2.0 node, disputed and resolved for the payer after
+/// a failed resolution by someone else
+fn native_stream() -> String {
+    let id = "1";
+    let write = |index, event: EscrowEvent| event_write(NATIVE_EVENTS, index, &event);
+    sse(
+        "2.0.0",
+        vec![
+            transaction_processed(11, &[write(0, created(id))], None),
+            json!({ "BlockAdded": { "block_hash": hash(98) } }),
+            transaction_processed(12, &[write(1, accepted(id))], None),
+            transaction_processed(13, &[write(2, deposited(id))], None),
+            transaction_processed(
+                14,
+                &[write(
+                    3,
+                    EscrowEvent::DisputeRaised {
+                        escrow_id: id.to_string(),
+                        raised_by: issuer(),
+                        reason: "Late delivery".to_string(),
+                    },
+                )],
+                None,
+            ),
+            transaction_processed(15, &[], Some("User error: 2")),
+            transaction_processed(
+                16,
+                &[
+                    write(
+                        4,
+                        EscrowEvent::DisputeResolved {
+                            escrow_id: id.to_string(),
+                            resolved_by: arbiter(),
+                            release_to_receiver: false,
+                        },
+                    ),
+                    write(5, released(id, payer())),
+                ],
+                None,
+            ),
+        ],
+    )
+}
+
+fn assert_fixture(name: &str, text: String) {
+    if std::env::var_os("UPDATE_FIXTURES").is_some() {
+        fs::write(fixture_path(name), &text).unwrap();
+        return;
+    }
+    assert_eq!(text, read_fixture(name), "{} differs from its stream", name);
+}
+
+#[test]
+fn test_fixtures_are_current() {
+    assert_fixture("odra-1.x.sse", odra_stream());
+    assert_fixture("native-2.0.sse", native_stream());
+}
+
+fn transition(from: Option<&str>, to: &str, deploy: u8) -> TransitionRow {
+    TransitionRow {
+        from_state: from.map(str::to_string),
+        to_state: to.to_string(),
+        deploy_hash: hash(deploy),
+    }
+}
+
+fn party(role: &str, key: Key) -> (String, String) {
+    (role.to_string(), key.to_formatted_string())
+}
+
+fn assert_odra_indexed(store: &Store) {
+    let escrow = store.escrow("INV-001", "INV-001").unwrap().unwrap();
+    assert_eq!(escrow.state, "Released");
+    assert_eq!(escrow.amount.as_deref(), Some("5000000000"));
+    assert_eq!(escrow.currency_symbol.as_deref(), Some("CSPR"));
+    assert_eq!(
+        store.transitions("INV-001", "INV-001").unwrap(),
+        [
+            transition(None, "Draft", 1),
+            transition(Some("Draft"), "Accepted", 2),
+            transition(Some("Accepted"), "Funded", 3),
+            transition(Some("Funded"), "Released", 6),
+        ]
+    );
+    assert_eq!(
+        store.parties("INV-001", "INV-001").unwrap(),
+        [
+            party("approver", payer()),
+            party("issuer", issuer()),
+            party("payer", payer()),
+        ]
+    );
+    assert_eq!(store.escrow("INV-001", "INV-999").unwrap(), None);
+}
+
+fn assert_native_indexed(store: &Store) {
+    let escrow = store.escrow("invoices", "1").unwrap().unwrap();
+    assert_eq!(escrow.state, "Released");
+    // The payout after the resolution changes no state
+    assert_eq!(
+        store.transitions("invoices", "1").unwrap(),
+        [
+            transition(None, "Draft", 11),
+            transition(Some("Draft"), "Accepted", 12),
+            transition(Some("Accepted"), "Funded", 13),
+            transition(Some("Funded"), "Disputed", 14),
+            transition(Some("Disputed"), "Released", 16),
+        ]
+    );
+    assert_eq!(
+        store.parties("invoices", "1").unwrap(),
+        [
+            party("arbiter", arbiter()),
+            party("issuer", issuer()),
+            party("payer", payer()),
+        ]
+    );
+}
+
+#[test]
+fn test_index_fixtures() {
+    let mut store = Store::open_in_memory().unwrap();
+    let contracts = contracts();
+    store.register(&contracts).unwrap();
+
+    let odra = read_fixture("odra-1.x.sse");
+    let progress = index(&mut store, &contracts, "odra", odra.as_bytes()).unwrap();
+    assert_eq!(
+        progress,
+        Progress {
+            messages: 7,
+            events: 5
+        }
+    );
+    assert_eq!(store.cursor("odra").unwrap(), Some(7));
+    assert_odra_indexed(&store);
+
+    let native = read_fixture("native-2.0.sse");
+    let progress = index(&mut store, &contracts, "native", native.as_bytes()).unwrap();
+    assert_eq!(progress.events, 6);
+    assert_native_indexed(&store);
+
+    let events = store.events_after(0, 100).unwrap();
+    assert_eq!(events.len(), 11);
+    let created = &events[0];
+    assert_eq!(
+        (created.name.as_str(), created.event_index),
+        ("EscrowCreated", 0)
+    );
+    assert_eq!(created.events_uref, ODRA_EVENTS);
+    assert_eq!(created.state.as_deref(), Some("Draft"));
+    let fields: Value = serde_json::from_str(&created.fields).unwrap();
+    assert_eq!(fields["issuer"], json!(issuer().to_formatted_string()));
+    assert_eq!(fields["amount"], json!("5000000000"));
+    // The resolution and its payout share a deploy; only the first changes
+    // the state
+    let payout = &events[10];
+    assert_eq!(payout.name, "FundsReleased");
+    assert_eq!(payout.events_uref, NATIVE_EVENTS);
+    assert_eq!(payout.deploy_hash, hash(16));
+    assert_eq!(events[9].deploy_hash, hash(16));
+    assert_eq!(payout.state, None);
+    assert_eq!(events[9].state.as_deref(), Some("Released"));
+
+    // Indexed once, however often the stream is replayed
+    let progress = index(&mut store, &contracts, "odra", odra.as_bytes()).unwrap();
+    assert_eq!(progress, Progress::default());
+    let progress = index(&mut store, &contracts, "odra-again", odra.as_bytes()).unwrap();
+    assert_eq!((progress.messages, progress.events), (7, 0));
+    assert_eq!(store.events_after(0, 100).unwrap().len(), 11);
+    assert_odra_indexed(&store);
+}
+
+#[test]
+fn test_undecodable_event_stops_indexing() {
+    let mut store = Store::open_in_memory().unwrap();
+    let contracts = contracts();
+    let (key, _) = event_write(NATIVE_EVENTS, 0, &created("1"));
+    let mut bytes = CLValue::from_t(Bytes::from(b"event_Unknown".to_vec()))
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    bytes.extend(Bytes::from(NATIVE_EVENTS.to_vec()).to_bytes().unwrap());
+    bytes.extend(Bytes::from(b"0".to_vec()).to_bytes().unwrap());
+    let stream = sse(
+        "2.0.0",
+        vec![
+            json!({ "BlockAdded": {} }),
+            transaction_processed(11, &[(key, hex::encode(bytes))], None),
+        ],
+    );
+    let error = index(&mut store, &contracts, "native", stream.as_bytes()).unwrap_err();
+    assert!(
+        matches!(error, termina_indexer::Error::Decode { id: 2, .. }),
+        "{}",
+        error
+    );
+    assert_eq!(store.cursor("native").unwrap(), Some(1));
+}
+
+/// Serve the stream to each connection in turn, cut after `cut` messages on
+/// the first, and return the request lines received
+fn mock_stream(stream: String, cut: usize) -> (String, thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/events", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for connection in 0..2 {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(socket.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            requests.push(request_line.trim_end().to_string());
+            let body = if connection == 0 {
+                stream.split_inclusive("\n\n").take(cut + 1).collect()
+            } else {
+                stream.clone()
+            };
+            write!(
+                socket,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n{}",
+                body
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+#[test]
+fn test_resume_from_cursor() {
+    let mut store = Store::open_in_memory().unwrap();
+    let contracts = contracts();
+    let (url, handle) = mock_stream(native_stream(), 3);
+
+    let reader = sse::connect(&url, store.cursor(&url).unwrap()).unwrap();
+    let progress = index(&mut store, &contracts, &url, reader).unwrap();
+    assert_eq!(progress.events, 2);
+    assert_eq!(
+        store.escrow("invoices", "1").unwrap().unwrap().state,
+        "Accepted"
+    );
+    assert_eq!(store.cursor(&url).unwrap(), Some(3));
+
+    // The node replays from the cursor; only what follows it is applied
+    let reader = sse::connect(&url, store.cursor(&url).unwrap()).unwrap();
+    let progress = index(&mut store, &contracts, &url, reader).unwrap();
+    assert_eq!(
+        progress,
+        Progress {
+            messages: 4,
+            events: 4
+        }
+    );
+    assert_native_indexed(&store);
+
+    let requests = handle.join().unwrap();
+    assert_eq!(requests[0], "GET /events HTTP/1.1");
+    assert_eq!(requests[1], "GET /events?start_from=3 HTTP/1.1");
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+/// A request the node can't serve, with the JSON-RPC error code a Casper
+/// node reports for it
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The method isn't one the stand-in implements
+    MethodNotFound(String),
+    /// The params don't match the method
+    InvalidParams(String),
+    /// The deploy or transaction is rejected before execution
+    InvalidDeploy(String),
+    /// No deploy with this hash was received
+    NoSuchDeploy(String),
+    /// The account has never sent a deploy
+    NoSuchAccount(String),
+    /// The key, path or dictionary item doesn't name a value
+    QueryFailed(String),
+}
+
+impl Error {
+    pub fn code(&self) -> i64 {
+        match self {
+            Error::MethodNotFound(_) => -32601,
+            Error::InvalidParams(_) => -32602,
+            Error::NoSuchDeploy(_) => -32000,
+            Error::QueryFailed(_) => -32003,
+            Error::InvalidDeploy(_) => -32008,
+            Error::NoSuchAccount(_) => -32009,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MethodNotFound(method) => write!(f, "method not found: {}", method),
+            Error::InvalidParams(message) => write!(f, "invalid params: {}", message),
+            Error::InvalidDeploy(message) => write!(f, "invalid deploy: {}", message),
+            Error::NoSuchDeploy(hash) => write!(f, "no such deploy: {}", hash),
+            Error::NoSuchAccount(account) => write!(f, "no such account: {}", account),
+            Error::QueryFailed(message) => write!(f, "query failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
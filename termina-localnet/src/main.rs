@@ -0,0 +1,51 @@
+//! Local stand-in for a Casper node: hosts Termina Odra escrows in OdraVM
+//! behind the node JSON-RPC calls the API, dashboard and deploy tool make
+//!
+//! ```sh
+//! cargo run --manifest-path termina-localnet/Cargo.toml -- --port 11101
+//! ```
+//!
+//! Deploys must be signed and name the chain given here. An installer deploy
+//! creates an escrow from its `config` argument without running the module
+//! bytes, so any WASM will do. State lives in memory and is gone on exit.
+
+mod error;
+mod node;
+mod rpc;
+
+use std::process::ExitCode;
+
+use clap::Parser;
+use tiny_http::Server;
+
+use crate::node::LocalNode;
+
+#[derive(Parser)]
+#[command(about = "Serve Termina escrows over Casper node JSON-RPC, in memory")]
+struct Args {
+    /// Port to listen on, on localhost
+    #[arg(long, default_value_t = 11101)]
+    port: u16,
+    /// Chain name deploys must be built for
+    #[arg(long, default_value = "termina-local")]
+    chain_name: String,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let server = match Server::http(("127.0.0.1", args.port)) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("error: port {}: {}", args.port, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut node = LocalNode::new(odra_test::env(), &args.chain_name);
+    println!(
+        "serving chain {} at http://127.0.0.1:{}/rpc",
+        node.chain_name(),
+        args.port
+    );
+    rpc::serve(&mut node, &server);
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,319 @@
+//! Odra escrows hosted in OdraVM, with just enough chain around them to
+//! answer node queries. Every deploy executes as it arrives, in a block of
+//! its own, and its payment is ignored.
+
+use std::collections::BTreeMap;
+
+use odra::casper_types::{
+    account::AccountHash, bytesrepr::FromBytes, CLTyped, CLValue, Deploy, DeployHash, Digest,
+    ExecutableDeployItem, Key, RuntimeArgs,
+};
+use odra::host::{Deployer, HostEnv};
+use odra::prelude::{Address, Addressable};
+use odra::{CallDef, OdraError};
+use termina_contracts::escrow::{EscrowHostRef, EscrowInitArgs};
+use termina_contracts::{Escrow, EscrowConfig};
+
+use crate::error::Error;
+
+/// Installer argument naming the account key the package hash is stored under
+const ARG_PACKAGE_HASH_KEY_NAME: &str = "odra_cfg_package_hash_key_name";
+const DEFAULT_PACKAGE_HASH_KEY_NAME: &str = "Escrow_package_hash";
+
+/// Dictionary of an Odra contract holding its events by index
+pub const EVENTS_DICTIONARY: &str = "__events";
+/// Dictionary of an escrow holding its transitions by index
+pub const HISTORY_DICTIONARY: &str = "history";
+
+/// A deploy and how its execution ended
+pub struct Executed {
+    pub deploy: Deploy,
+    pub block_hash: Digest,
+    pub block_height: u64,
+    /// Error message of a failed deploy, as a node words it
+    pub error: Option<String>,
+}
+
+pub struct LocalNode {
+    env: HostEnv,
+    chain_name: String,
+    /// Installed escrows by package hash, which stands in for the contract
+    /// hash too
+    escrows: BTreeMap<[u8; 32], EscrowHostRef>,
+    named_keys: BTreeMap<AccountHash, BTreeMap<String, Key>>,
+    deploys: BTreeMap<DeployHash, Executed>,
+    block_height: u64,
+}
+
+impl LocalNode {
+    pub fn new(env: HostEnv, chain_name: impl Into<String>) -> Self {
+        LocalNode {
+            env,
+            chain_name: chain_name.into(),
+            escrows: BTreeMap::new(),
+            named_keys: BTreeMap::new(),
+            deploys: BTreeMap::new(),
+            block_height: 0,
+        }
+    }
+
+    pub fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
+    /// Check and execute a deploy. A deploy that fails in execution is still
+    /// accepted; its error is recorded with it.
+    pub fn put_deploy(&mut self, deploy: Deploy) -> Result<DeployHash, Error> {
+        let deploy_hash = *deploy.hash();
+        if self.deploys.contains_key(&deploy_hash) {
+            return Ok(deploy_hash);
+        }
+        deploy
+            .is_valid()
+            .map_err(|error| Error::InvalidDeploy(error.to_string()))?;
+        if deploy.header().chain_name() != self.chain_name {
+            return Err(Error::InvalidDeploy(format!(
+                "chain name {} is not {}",
+                deploy.header().chain_name(),
+                self.chain_name
+            )));
+        }
+
+        // Blocks carry the deploy's timestamp, so due dates and time
+        // conditions follow the client's clock
+        let timestamp = deploy.header().timestamp().millis();
+        if timestamp > self.env.block_time() {
+            self.env
+                .advance_block_time(timestamp - self.env.block_time());
+        }
+        let account = deploy.header().account().to_account_hash();
+        let error = self.execute(account, deploy.session()).err();
+
+        self.block_height += 1;
+        let mut preimage = self.block_height.to_le_bytes().to_vec();
+        preimage.extend(deploy_hash.inner().value());
+        self.deploys.insert(
+            deploy_hash,
+            Executed {
+                deploy,
+                block_hash: Digest::hash(preimage),
+                block_height: self.block_height,
+                error,
+            },
+        );
+        Ok(deploy_hash)
+    }
+
+    pub fn deploy(&self, deploy_hash: &DeployHash) -> Option<&Executed> {
+        self.deploys.get(deploy_hash)
+    }
+
+    /// Changes with every block; queries always read the latest state
+    pub fn state_root_hash(&self) -> Digest {
+        Digest::hash(self.block_height.to_le_bytes())
+    }
+
+    /// Named keys of an account that has sent a deploy
+    pub fn named_keys(&self, account: &AccountHash) -> Option<&BTreeMap<String, Key>> {
+        self.named_keys.get(account)
+    }
+
+    /// Value of an escrow view, such as `state` or `summary`, by the escrow's
+    /// package hash or the account named key it is stored under
+    pub fn query(&self, key: &Key, path: &[String]) -> Result<CLValue, Error> {
+        let (view, escrow) = match (key, path) {
+            (Key::Hash(package_hash), [view]) => (view, self.escrow(package_hash)?),
+            (Key::Account(account), [name, view]) => {
+                let package_hash = self
+                    .named_keys
+                    .get(account)
+                    .and_then(|named_keys| named_keys.get(name))
+                    .and_then(|key| key.into_hash_addr())
+                    .ok_or_else(|| Error::QueryFailed(format!("no named key {}", name)))?;
+                (view, self.escrow(&package_hash)?)
+            }
+            _ => {
+                return Err(Error::QueryFailed(format!(
+                    "no value at {} / {}",
+                    key.to_formatted_string(),
+                    path.join("/")
+                )))
+            }
+        };
+        let value = match view.as_str() {
+            "state" => CLValue::from_t(escrow.get_state()),
+            "invoice" => CLValue::from_t(escrow.get_invoice()),
+            "balance" => CLValue::from_t(escrow.get_balance()),
+            "summary" => CLValue::from_t(escrow.get_summary()),
+            "history_len" => CLValue::from_t(escrow.get_history_len()),
+            _ => return Err(Error::QueryFailed(format!("no escrow view {}", view))),
+        };
+        Ok(value.expect("escrow views serialize"))
+    }
+
+    /// Item of an escrow's event or history dictionary, with the dictionary
+    /// key it is stored under. Item keys are indices.
+    pub fn dictionary_item(
+        &self,
+        key: &Key,
+        dictionary_name: &str,
+        item_key: &str,
+    ) -> Result<(Key, CLValue), Error> {
+        let missing = || {
+            Error::QueryFailed(format!(
+                "no item {} in dictionary {}",
+                item_key, dictionary_name
+            ))
+        };
+        let package_hash = key
+            .into_hash_addr()
+            .ok_or_else(|| Error::QueryFailed(format!("{} is not a contract", key)))?;
+        let escrow = self.escrow(&package_hash)?;
+        let index: u32 = item_key.parse().map_err(|_| missing())?;
+        let value = match dictionary_name {
+            EVENTS_DICTIONARY => {
+                let event = self
+                    .env
+                    .get_event_bytes(&escrow.address(), index)
+                    .map_err(|_| missing())?;
+                CLValue::from_t(event)
+            }
+            HISTORY_DICTIONARY => {
+                let transition = escrow
+                    .get_history(index, 1)
+                    .into_iter()
+                    .next()
+                    .ok_or_else(missing)?;
+                CLValue::from_t(transition)
+            }
+            _ => return Err(missing()),
+        }
+        .expect("dictionary items serialize");
+
+        let mut preimage = package_hash.to_vec();
+        preimage.extend(dictionary_name.as_bytes());
+        preimage.extend(item_key.as_bytes());
+        Ok((Key::Dictionary(Digest::hash(preimage).value()), value))
+    }
+
+    fn escrow(&self, package_hash: &[u8; 32]) -> Result<&EscrowHostRef, Error> {
+        self.escrows.get(package_hash).ok_or_else(|| {
+            Error::QueryFailed(format!("no escrow at hash-{}", hex::encode(package_hash)))
+        })
+    }
+
+    fn execute(
+        &mut self,
+        account: AccountHash,
+        session: &ExecutableDeployItem,
+    ) -> Result<(), String> {
+        self.env.set_caller(Address::Account(account));
+        match session {
+            ExecutableDeployItem::ModuleBytes { args, .. } => self.install(account, args),
+            ExecutableDeployItem::StoredContractByHash {
+                hash,
+                entry_point,
+                args,
+            } => self.call(&hash.value(), entry_point, args),
+            ExecutableDeployItem::StoredVersionedContractByHash {
+                hash,
+                entry_point,
+                args,
+                ..
+            } => self.call(&hash.value(), entry_point, args),
+            ExecutableDeployItem::StoredContractByName {
+                name,
+                entry_point,
+                args,
+            }
+            | ExecutableDeployItem::StoredVersionedContractByName {
+                name,
+                entry_point,
+                args,
+                ..
+            } => {
+                let package_hash = self
+                    .named_keys
+                    .get(&account)
+                    .and_then(|named_keys| named_keys.get(name))
+                    .and_then(|key| key.into_hash_addr())
+                    .ok_or_else(|| format!("no named key {}", name))?;
+                self.call(&package_hash, entry_point, args)
+            }
+            ExecutableDeployItem::Transfer { .. } => Err("transfers are not supported".to_string()),
+        }
+    }
+
+    /// Stand in for the Odra installer: the module bytes aren't run, the
+    /// escrow is deployed from the `config` argument
+    fn install(&mut self, account: AccountHash, args: &RuntimeArgs) -> Result<(), String> {
+        let config: EscrowConfig = arg(args, "config")?;
+        let key_name = match args.get(ARG_PACKAGE_HASH_KEY_NAME) {
+            Some(_) => arg(args, ARG_PACKAGE_HASH_KEY_NAME)?,
+            None => DEFAULT_PACKAGE_HASH_KEY_NAME.to_string(),
+        };
+        let escrow = Escrow::try_deploy(&self.env, EscrowInitArgs { config }).map_err(failure)?;
+        let package_hash = escrow
+            .address()
+            .as_contract_package_hash()
+            .expect("an installed escrow is a contract")
+            .value();
+        self.named_keys
+            .entry(account)
+            .or_default()
+            .insert(key_name, Key::Hash(package_hash));
+        self.escrows.insert(package_hash, escrow);
+        Ok(())
+    }
+
+    fn call(
+        &mut self,
+        package_hash: &[u8; 32],
+        entry_point: &str,
+        args: &RuntimeArgs,
+    ) -> Result<(), String> {
+        let escrow = self
+            .escrows
+            .get(package_hash)
+            .ok_or_else(|| format!("no escrow at hash-{}", hex::encode(package_hash)))?;
+        let call_def = CallDef::new(entry_point, true, args.clone());
+        self.env
+            .call_contract::<()>(escrow.address(), call_def)
+            .map_err(failure)
+    }
+}
+
+fn arg<T: CLTyped + FromBytes>(args: &RuntimeArgs, name: &str) -> Result<T, String> {
+    args.get(name)
+        .ok_or_else(|| format!("missing argument {}", name))?
+        .to_t()
+        .map_err(|error| format!("invalid argument {}: {}", name, error))
+}
+
+/// Execution error message, worded as a node reports a revert
+fn failure(error: OdraError) -> String {
+    match error {
+        OdraError::VmError(error) => format!("{:?}", error),
+        error => format!("User error: {}", error.code()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_escrow() {
+        let node = LocalNode::new(odra_test::env(), "termina-local");
+        let key = Key::Hash([9; 32]);
+        assert!(matches!(
+            node.query(&key, &["state".to_string()]),
+            Err(Error::QueryFailed(_))
+        ));
+        assert!(matches!(
+            node.dictionary_item(&key, HISTORY_DICTIONARY, "0"),
+            Err(Error::QueryFailed(_))
+        ));
+        assert_eq!(node.state_root_hash(), Digest::hash(0u64.to_le_bytes()));
+    }
+}
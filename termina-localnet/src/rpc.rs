@@ -0,0 +1,495 @@
+//! The subset of the Casper node JSON-RPC the stand-in answers. Results have
+//! the shape a node gives; `info_get_deploy` reports the outcome both the 1.x
+//! way and the 2.0 way, so clients of either generation can read it.
+
+use std::io::Read;
+
+use odra::casper_types::{
+    account::AccountHash, AsymmetricType, Deploy, DeployHash, Key, PublicKey, Transaction,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use tiny_http::{Header, Response, Server};
+
+use crate::{error::Error, node::LocalNode};
+
+const API_VERSION: &str = "2.0.0";
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+
+/// Answer every request on `server`, one at a time
+pub fn serve(node: &mut LocalNode, server: &Server) {
+    let content_type: Header = "Content-Type: application/json"
+        .parse()
+        .expect("header is well formed");
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => respond(node, &body),
+            Err(error) => envelope(&Value::Null, Err((PARSE_ERROR, error.to_string()))),
+        };
+        let response =
+            Response::from_string(response.to_string()).with_header(content_type.clone());
+        if let Err(error) = request.respond(response) {
+            eprintln!("failed to answer a request: {}", error);
+        }
+    }
+}
+
+/// Answer one JSON-RPC request body
+pub fn respond(node: &mut LocalNode, body: &str) -> Value {
+    let request: Value = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(error) => return envelope(&Value::Null, Err((PARSE_ERROR, error.to_string()))),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return envelope(&id, Err((INVALID_REQUEST, "no method".to_string())));
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let result = dispatch(node, method, params).map_err(|error| (error.code(), error.to_string()));
+    envelope(&id, result)
+}
+
+fn envelope(id: &Value, result: Result<Value, (i64, String)>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }),
+    }
+}
+
+fn dispatch(node: &mut LocalNode, method: &str, params: Value) -> Result<Value, Error> {
+    match method {
+        "account_put_deploy" => {
+            let PutDeploy { deploy } = params_of(params)?;
+            let deploy_hash = node.put_deploy(deploy)?;
+            Ok(json!({ "api_version": API_VERSION, "deploy_hash": deploy_hash }))
+        }
+        "account_put_transaction" => {
+            let PutTransaction { transaction } = params_of(params)?;
+            let Transaction::Deploy(deploy) = transaction else {
+                return Err(Error::InvalidDeploy(
+                    "only deploy transactions are supported".to_string(),
+                ));
+            };
+            let deploy_hash = node.put_deploy(deploy)?;
+            Ok(json!({
+                "api_version": API_VERSION,
+                "transaction_hash": { "Deploy": deploy_hash },
+            }))
+        }
+        "info_get_deploy" => {
+            let GetDeploy { deploy_hash } = params_of(params)?;
+            deploy_info(node, &deploy_hash)
+        }
+        "chain_get_state_root_hash" => Ok(json!({
+            "api_version": API_VERSION,
+            "state_root_hash": hex::encode(node.state_root_hash().value()),
+        })),
+        "query_global_state" | "state_get_item" => {
+            let Query { key, path } = params_of(params)?;
+            let key = parse_key(&key)?;
+            let stored_value = match (key, path.as_slice()) {
+                (Key::Account(account), []) => account_json(node, &account)?,
+                _ => json!({ "CLValue": node.query(&key, &path)? }),
+            };
+            Ok(json!({
+                "api_version": API_VERSION,
+                "block_header": null,
+                "stored_value": stored_value,
+                "merkle_proof": "",
+            }))
+        }
+        "state_get_dictionary_item" => {
+            let GetDictionaryItem {
+                dictionary_identifier,
+            } = params_of(params)?;
+            let DictionaryIdentifier::ContractNamedKey {
+                key,
+                dictionary_name,
+                dictionary_item_key,
+            } = dictionary_identifier;
+            let (dictionary_key, value) =
+                node.dictionary_item(&parse_key(&key)?, &dictionary_name, &dictionary_item_key)?;
+            Ok(json!({
+                "api_version": API_VERSION,
+                "dictionary_key": dictionary_key.to_formatted_string(),
+                "stored_value": { "CLValue": value },
+                "merkle_proof": "",
+            }))
+        }
+        "state_get_account_info" => {
+            let GetAccountInfo {
+                public_key,
+                account_identifier,
+            } = params_of(params)?;
+            let identifier = account_identifier
+                .or(public_key)
+                .ok_or_else(|| Error::InvalidParams("no account identifier".to_string()))?;
+            let account = PublicKey::from_hex(&identifier)
+                .map(|public_key| public_key.to_account_hash())
+                .or_else(|_| AccountHash::from_formatted_str(&identifier))
+                .map_err(|_| Error::InvalidParams(format!("not an account: {}", identifier)))?;
+            Ok(json!({
+                "api_version": API_VERSION,
+                "account": account_json(node, &account)?["Account"],
+                "merkle_proof": "",
+            }))
+        }
+        _ => Err(Error::MethodNotFound(method.to_string())),
+    }
+}
+
+fn params_of<T: DeserializeOwned>(params: Value) -> Result<T, Error> {
+    serde_json::from_value(params).map_err(|error| Error::InvalidParams(error.to_string()))
+}
+
+fn parse_key(key: &str) -> Result<Key, Error> {
+    Key::from_formatted_str(key)
+        .map_err(|error| Error::InvalidParams(format!("{}: {}", key, error)))
+}
+
+#[derive(Deserialize)]
+struct PutDeploy {
+    deploy: Deploy,
+}
+
+#[derive(Deserialize)]
+struct PutTransaction {
+    transaction: Transaction,
+}
+
+#[derive(Deserialize)]
+struct GetDeploy {
+    deploy_hash: DeployHash,
+}
+
+#[derive(Deserialize)]
+struct Query {
+    key: String,
+    #[serde(default)]
+    path: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct GetDictionaryItem {
+    dictionary_identifier: DictionaryIdentifier,
+}
+
+#[derive(Deserialize)]
+enum DictionaryIdentifier {
+    ContractNamedKey {
+        key: String,
+        dictionary_name: String,
+        dictionary_item_key: String,
+    },
+}
+
+/// 1.x nodes take a `public_key`, 2.0 nodes an `account_identifier`
+#[derive(Deserialize)]
+struct GetAccountInfo {
+    public_key: Option<String>,
+    account_identifier: Option<String>,
+}
+
+fn deploy_info(node: &LocalNode, deploy_hash: &DeployHash) -> Result<Value, Error> {
+    let executed = node
+        .deploy(deploy_hash)
+        .ok_or_else(|| Error::NoSuchDeploy(hex::encode(deploy_hash.inner().value())))?;
+    let block_hash = hex::encode(executed.block_hash.value());
+    let effect = json!({ "operations": [], "transforms": [] });
+    let result_v1 = match &executed.error {
+        None => json!({ "Success": { "effect": effect, "transfers": [], "cost": "0" } }),
+        Some(message) => json!({ "Failure": {
+            "effect": effect,
+            "transfers": [],
+            "cost": "0",
+            "error_message": message,
+        } }),
+    };
+    Ok(json!({
+        "api_version": API_VERSION,
+        "deploy": executed.deploy,
+        "execution_results": [{ "block_hash": block_hash, "result": result_v1 }],
+        "execution_info": {
+            "block_hash": block_hash,
+            "block_height": executed.block_height,
+            "execution_result": { "Version2": {
+                "initiator": { "PublicKey": executed.deploy.header().account() },
+                "error_message": executed.error,
+                "limit": "0",
+                "consumed": "0",
+                "cost": "0",
+                "transfers": [],
+                "size_estimate": 0,
+                "effects": [],
+            } },
+        },
+    }))
+}
+
+/// An account as a stored value, with the package hashes it has installed
+/// escrows under as named keys
+fn account_json(node: &LocalNode, account: &AccountHash) -> Result<Value, Error> {
+    let named_keys: Vec<Value> = node
+        .named_keys(account)
+        .ok_or_else(|| Error::NoSuchAccount(account.to_formatted_string()))?
+        .iter()
+        .map(|(name, key)| json!({ "name": name, "key": key.to_formatted_string() }))
+        .collect();
+    Ok(json!({ "Account": {
+        "account_hash": account.to_formatted_string(),
+        "named_keys": named_keys,
+        "main_purse": format!("uref-{}-007", hex::encode([0; 32])),
+        "associated_keys": [{ "account_hash": account.to_formatted_string(), "weight": 1 }],
+        "action_thresholds": { "deployment": 1, "key_management": 1 },
+    } }))
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{bytesrepr::Bytes, SecretKey, Timestamp, U512};
+    use termina_client::{
+        decode_state,
+        odra::{Currency, EscrowConfig, EscrowSummary, OdraEscrow},
+        DeployParams, EscrowTransaction, State,
+    };
+
+    use super::*;
+
+    const CHAIN_NAME: &str = "termina-local";
+
+    struct Party {
+        secret_key: SecretKey,
+        public_key: casper_types::PublicKey,
+    }
+
+    impl Party {
+        fn new(seed: u8) -> Self {
+            let secret_key = SecretKey::ed25519_from_bytes([seed; 32]).unwrap();
+            let public_key = casper_types::PublicKey::from(&secret_key);
+            Party {
+                secret_key,
+                public_key,
+            }
+        }
+
+        fn key(&self) -> casper_types::Key {
+            casper_types::Key::Account(self.public_key.to_account_hash())
+        }
+
+        /// Signed deploy JSON, sent a second after `at`
+        fn sign(&self, transaction: EscrowTransaction, at: u64) -> Value {
+            let params = DeployParams::new(self.public_key.clone(), CHAIN_NAME)
+                .with_timestamp(Timestamp::from(at + 1000));
+            let mut deploy = transaction.build(&params);
+            deploy.sign(&self.secret_key);
+            serde_json::to_value(deploy).unwrap()
+        }
+    }
+
+    fn call(node: &mut LocalNode, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = respond(node, &request.to_string());
+        assert_eq!(response["id"], 1);
+        response
+    }
+
+    fn result(node: &mut LocalNode, method: &str, params: Value) -> Value {
+        let response = call(node, method, params);
+        assert!(response.get("error").is_none(), "{}", response);
+        response["result"].clone()
+    }
+
+    /// Send a deploy and return its 1.x execution result
+    fn execute(node: &mut LocalNode, deploy: Value) -> Value {
+        let sent = result(node, "account_put_deploy", json!({ "deploy": deploy }));
+        let info = result(
+            node,
+            "info_get_deploy",
+            json!({ "deploy_hash": sent["deploy_hash"] }),
+        );
+        assert_eq!(
+            info["execution_info"]["block_hash"],
+            info["execution_results"][0]["block_hash"]
+        );
+        info["execution_results"][0]["result"].clone()
+    }
+
+    fn cl_value(stored_value: &Value) -> casper_types::CLValue {
+        serde_json::from_value(stored_value["CLValue"].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_escrow_lifecycle_over_rpc() {
+        let mut node = LocalNode::new(odra_test::env(), CHAIN_NAME);
+        let issuer = Party::new(1);
+        let payer = Party::new(2);
+        let now = 1_704_067_200_000;
+
+        let config = EscrowConfig {
+            id: "INV-1".to_string(),
+            description: "Logo design".to_string(),
+            amount: U512::from(5_000),
+            currency: Currency::cspr(),
+            payer: payer.key(),
+            arbiter: None,
+            due_date: None,
+            release_conditions: Vec::new(),
+        };
+        let install = OdraEscrow::install(Bytes::from(vec![0]), config);
+        assert!(execute(&mut node, issuer.sign(install, now))
+            .get("Success")
+            .is_some());
+
+        // The installer's named key leads to the package
+        let account = result(
+            &mut node,
+            "state_get_account_info",
+            json!({ "public_key": issuer.public_key.to_hex() }),
+        );
+        let package = account["account"]["named_keys"][0]["key"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            account["account"]["named_keys"][0]["name"],
+            termina_client::odra::PACKAGE_HASH_KEY_NAME
+        );
+        let package_hash = casper_types::Key::from_formatted_str(&package)
+            .unwrap()
+            .into_hash_addr()
+            .unwrap();
+        let escrow = OdraEscrow::new(package_hash.into());
+
+        // Only the payer may accept
+        let failure = execute(&mut node, issuer.sign(escrow.accept(), now + 1));
+        assert_eq!(
+            failure["Failure"]["error_message"],
+            format!("User error: {}", termina_client::Error::Unauthorized.code())
+        );
+        for transaction in [
+            escrow.accept(),
+            escrow.fund(U512::from(5_000)),
+            escrow.approve(),
+        ] {
+            assert!(execute(&mut node, payer.sign(transaction, now + 2))
+                .get("Success")
+                .is_some());
+        }
+
+        let state = result(
+            &mut node,
+            "query_global_state",
+            json!({ "key": package, "path": ["state"] }),
+        );
+        assert_eq!(
+            decode_state(&cl_value(&state["stored_value"])),
+            Ok(State::Funded)
+        );
+        let summary = result(
+            &mut node,
+            "state_get_item",
+            json!({ "state_root_hash": "00", "key": package, "path": ["summary"] }),
+        );
+        let summary = EscrowSummary::from_cl_value(&cl_value(&summary["stored_value"])).unwrap();
+        assert_eq!(summary.balance, U512::from(5_000));
+        assert_eq!(summary.invoice.issuer, issuer.key());
+        assert!(summary.invoice.funded_at.unwrap() > now);
+
+        // Creation, acceptance and funding are in the history and the events
+        for (dictionary, items) in [("history", 3), ("__events", 4)] {
+            let identifier = |item: u32| {
+                json!({ "dictionary_identifier": { "ContractNamedKey": {
+                    "key": package,
+                    "dictionary_name": dictionary,
+                    "dictionary_item_key": item.to_string(),
+                } } })
+            };
+            let item = result(
+                &mut node,
+                "state_get_dictionary_item",
+                identifier(items - 1),
+            );
+            assert!(item["dictionary_key"]
+                .as_str()
+                .unwrap()
+                .starts_with("dictionary-"));
+            let missing = call(&mut node, "state_get_dictionary_item", identifier(items));
+            assert_eq!(missing["error"]["code"], -32003);
+        }
+
+        // By named key, as for a contract the account installed
+        let account_key = issuer.key().to_formatted_string();
+        let balance = result(
+            &mut node,
+            "query_global_state",
+            json!({ "key": account_key, "path": [termina_client::odra::PACKAGE_HASH_KEY_NAME, "balance"] }),
+        );
+        assert_eq!(
+            cl_value(&balance["stored_value"]).into_t::<U512>().unwrap(),
+            U512::from(5_000)
+        );
+    }
+
+    #[test]
+    fn test_rejected_requests() {
+        let mut node = LocalNode::new(odra_test::env(), CHAIN_NAME);
+        let payer = Party::new(2);
+        let escrow = OdraEscrow::new([7; 32].into());
+
+        assert_eq!(
+            call(&mut node, "info_get_block", json!({}))["error"]["code"],
+            -32601
+        );
+        assert_eq!(
+            call(&mut node, "account_put_deploy", json!({ "deploy": 1 }))["error"]["code"],
+            -32602
+        );
+        assert_eq!(respond(&mut node, "{")["error"]["code"], PARSE_ERROR);
+
+        // A deploy for another chain, and one with its signature tampered with
+        let params = DeployParams::new(payer.public_key.clone(), "casper-test");
+        let mut deploy = escrow.accept().build(&params);
+        deploy.sign(&payer.secret_key);
+        let other_chain = call(&mut node, "account_put_deploy", json!({ "deploy": deploy }));
+        assert_eq!(other_chain["error"]["code"], -32008);
+        let mut tampered = payer.sign(escrow.accept(), 0);
+        tampered["header"]["timestamp"] = json!("2024-01-01T00:00:00.000Z");
+        let tampered = call(
+            &mut node,
+            "account_put_deploy",
+            json!({ "deploy": tampered }),
+        );
+        assert_eq!(tampered["error"]["code"], -32008);
+
+        // Calls to a package that doesn't exist fail in execution
+        let missing = execute(&mut node, payer.sign(escrow.accept(), 0));
+        assert!(missing["Failure"]["error_message"]
+            .as_str()
+            .unwrap()
+            .contains("no escrow"));
+
+        let unknown = hex::encode([3; 32]);
+        assert_eq!(
+            call(
+                &mut node,
+                "info_get_deploy",
+                json!({ "deploy_hash": unknown })
+            )["error"]["code"],
+            -32000
+        );
+        assert_eq!(
+            call(
+                &mut node,
+                "state_get_account_info",
+                json!({ "account_identifier": Party::new(9).public_key.to_hex() })
+            )["error"]["code"],
+            -32009
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Notifier config: where events come from, the webhooks to deliver them
+//! to and how to retry. TOML or JSON, picked by extension.
+
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use termina_client::{events::EscrowEvent, State};
+use termina_indexer::store::EventRow;
+
+use crate::error::Error;
+
+const DEFAULT_POLL_SECS: u64 = 2;
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+const DEFAULT_BASE_DELAY_SECS: u64 = 10;
+const DEFAULT_MAX_DELAY_SECS: u64 = 3600;
+
+/// Read a TOML or JSON file, by extension
+pub fn read_file<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let text = fs::read_to_string(path).map_err(|error| Error::Io(path.to_path_buf(), error))?;
+    let parsed = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|error| error.to_string()),
+        Some("json") => serde_json::from_str(&text).map_err(|error| error.to_string()),
+        _ => Err("expected a .toml or .json file".to_string()),
+    };
+    parsed.map_err(|message| Error::Parse(path.to_path_buf(), message))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub source: Source,
+    /// How often to look for new events and deliveries due
+    #[serde(default = "default_poll_secs")]
+    pub poll_secs: u64,
+    /// How long a webhook has to answer
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+/// Where events come from. Relative paths are resolved against the config
+/// file's directory.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Source {
+    /// The database of a `termina-indexer` running next to the notifier
+    Database(PathBuf),
+    /// A `termina-indexer` config: the notifier follows the node's stream
+    /// itself, indexing into its own database
+    Stream(PathBuf),
+}
+
+/// How failed deliveries are retried: after `base_delay_secs`, doubling
+/// with every attempt up to `max_delay_secs`. A delivery that fails
+/// `max_attempts` times becomes a dead letter.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay_secs: DEFAULT_BASE_DELAY_SECS,
+            max_delay_secs: DEFAULT_MAX_DELAY_SECS,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the next attempt, after `attempts` failed ones
+    pub fn delay(&self, attempts: u32) -> Duration {
+        let doublings = attempts.saturating_sub(1).min(32);
+        let delay = self.base_delay_secs.saturating_mul(1 << doublings);
+        Duration::from_secs(delay.min(self.max_delay_secs))
+    }
+}
+
+/// A webhook as configured
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Name deliveries and dead letters are recorded under
+    pub name: String,
+    pub url: String,
+    /// Environment variable holding the signing secret
+    pub secret_env: String,
+    /// Event names to deliver, such as `FundsDeposited`
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Deliver the events that move an escrow to one of these states, such
+    /// as `Funded`
+    #[serde(default)]
+    pub states: Vec<String>,
+}
+
+/// A webhook ready to deliver to. With neither events nor states given, it
+/// gets every event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Webhook {
+    pub name: String,
+    pub url: String,
+    pub secret: Vec<u8>,
+    pub events: Vec<String>,
+    pub states: Vec<String>,
+}
+
+impl Webhook {
+    pub fn matches(&self, event: &EventRow) -> bool {
+        (self.events.is_empty() && self.states.is_empty())
+            || self.events.contains(&event.name)
+            || event
+                .state
+                .as_ref()
+                .is_some_and(|state| self.states.contains(state))
+    }
+}
+
+fn default_poll_secs() -> u64 {
+    DEFAULT_POLL_SECS
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut config: Config = read_file(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        match &mut config.source {
+            Source::Database(path) | Source::Stream(path) => *path = base_dir.join(&*path),
+        }
+        Ok(config)
+    }
+
+    pub fn poll(&self) -> Duration {
+        Duration::from_secs(self.poll_secs)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// The webhooks, with their secrets read from the environment
+    pub fn webhooks(&self) -> Result<Vec<Webhook>, Error> {
+        if self.webhooks.is_empty() {
+            return Err(Error::Config("no webhooks to deliver to".to_string()));
+        }
+        if self.retry.max_attempts == 0 {
+            return Err(Error::Config(
+                "retry.max_attempts must be at least 1".to_string(),
+            ));
+        }
+        let mut names = BTreeSet::new();
+        self.webhooks
+            .iter()
+            .map(|webhook| {
+                let invalid =
+                    |reason: String| Error::Config(format!("{}: {}", webhook.name, reason));
+                if !names.insert(&webhook.name) {
+                    return Err(invalid("webhook is listed twice".to_string()));
+                }
+                if !(webhook.url.starts_with("http://") || webhook.url.starts_with("https://")) {
+                    return Err(invalid(format!("{} is not an http(s) URL", webhook.url)));
+                }
+                if let Some(event) = webhook
+                    .events
+                    .iter()
+                    .find(|event| !EscrowEvent::NAMES.contains(&event.as_str()))
+                {
+                    return Err(invalid(format!("no event {}", event)));
+                }
+                if let Some(state) = webhook
+                    .states
+                    .iter()
+                    .find(|state| !State::ALL.iter().any(|known| known.name() == *state))
+                {
+                    return Err(invalid(format!("no state {}", state)));
+                }
+                let secret = env::var(&webhook.secret_env)
+                    .ok()
+                    .filter(|secret| !secret.is_empty())
+                    .ok_or_else(|| invalid(format!("{} is not set", webhook.secret_env)))?;
+                Ok(Webhook {
+                    name: webhook.name.clone(),
+                    url: webhook.url.clone(),
+                    secret: secret.into_bytes(),
+                    events: webhook.events.clone(),
+                    states: webhook.states.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(webhook: &str) -> Config {
+        toml::from_str(&format!(
+            r#"
+            [source]
+            database = "termina-index.sqlite"
+
+            [[webhooks]]
+            name = "erp"
+            url = "https://erp.example.com/hooks/termina"
+            {}
+            "#,
+            webhook
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_webhooks() {
+        env::set_var("TERMINA_TEST_SECRET", "whsec_test");
+        let config = parse(
+            r#"
+            secret_env = "TERMINA_TEST_SECRET"
+            states = ["Funded", "Released"]
+            "#,
+        );
+        assert!(
+            matches!(&config.source, Source::Database(path) if path.ends_with("termina-index.sqlite"))
+        );
+        assert_eq!(config.retry, RetryPolicy::default());
+        let webhooks = config.webhooks().unwrap();
+        assert_eq!(webhooks[0].secret, b"whsec_test");
+        assert_eq!(webhooks[0].states, ["Funded", "Released"]);
+
+        for webhook in [
+            r#"secret_env = "TERMINA_TEST_SECRET"
+               states = ["Paid"]"#,
+            r#"secret_env = "TERMINA_TEST_SECRET"
+               events = ["FundsPaid"]"#,
+            r#"secret_env = "TERMINA_TEST_MISSING_SECRET""#,
+        ] {
+            assert!(matches!(parse(webhook).webhooks(), Err(Error::Config(_))));
+        }
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 8,
+            base_delay_secs: 10,
+            max_delay_secs: 60,
+        };
+        let delays: Vec<_> = (1..=5)
+            .map(|attempts| policy.delay(attempts).as_secs())
+            .collect();
+        assert_eq!(delays, [10, 20, 40, 60, 60]);
+        assert_eq!(policy.delay(u32::MAX).as_secs(), 60);
+    }
+}
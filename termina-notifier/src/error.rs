@@ -0,0 +1,41 @@
+use std::{fmt, io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read
+    Io(PathBuf, io::Error),
+    /// A config file doesn't parse
+    Parse(PathBuf, String),
+    /// The config names webhooks the notifier can't serve
+    Config(String),
+    /// The outbox rejected a read or write
+    Database(rusqlite::Error),
+    /// Events couldn't be read or indexed
+    Index(termina_indexer::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            Error::Parse(path, message) => write!(f, "{}: {}", path.display(), message),
+            Error::Config(message) => write!(f, "config: {}", message),
+            Error::Database(error) => write!(f, "outbox: {}", error),
+            Error::Index(error) => write!(f, "events: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Error::Database(error)
+    }
+}
+
+impl From<termina_indexer::Error> for Error {
+    fn from(error: termina_indexer::Error) -> Self {
+        Error::Index(error)
+    }
+}
@@ -0,0 +1,125 @@
+//! Signed webhooks for Termina escrow lifecycle events
+//!
+//! The notifier reads the events a `termina-indexer` database holds, picks
+//! those each webhook subscribes to, and queues a delivery for each in its
+//! [`outbox`]. Deliveries are POSTed as JSON with an HMAC
+//! [`signature`] and an idempotency key, retried with exponential backoff,
+//! and kept as dead letters once out of attempts. Each webhook has its own
+//! cursor over the events, so a restarted notifier queues nothing twice.
+
+pub mod config;
+mod error;
+pub mod outbox;
+pub mod signature;
+pub mod webhook;
+
+use std::time::Duration;
+
+use termina_indexer::store::Store;
+
+pub use error::Error;
+
+use crate::{
+    config::{RetryPolicy, Webhook},
+    outbox::Outbox,
+};
+
+/// Events read from the database at a time
+const EVENT_BATCH: usize = 100;
+/// Deliveries attempted per pass
+const DELIVERY_BATCH: usize = 100;
+
+/// What one delivery pass did
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub delivered: usize,
+    /// Failed attempts that will be retried
+    pub retried: usize,
+    /// Deliveries that failed their last attempt
+    pub dead: usize,
+}
+
+pub struct Notifier {
+    outbox: Outbox,
+    webhooks: Vec<Webhook>,
+    retry: RetryPolicy,
+    agent: ureq::Agent,
+}
+
+impl Notifier {
+    /// A notifier whose webhooks each get `timeout` to answer
+    pub fn new(
+        outbox: Outbox,
+        webhooks: Vec<Webhook>,
+        retry: RetryPolicy,
+        timeout: Duration,
+    ) -> Self {
+        Notifier {
+            outbox,
+            webhooks,
+            retry,
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    pub fn outbox(&self) -> &Outbox {
+        &self.outbox
+    }
+
+    pub fn outbox_mut(&mut self) -> &mut Outbox {
+        &mut self.outbox
+    }
+
+    /// Queue the events indexed since each webhook's cursor that it
+    /// subscribes to, due at `now` in unix milliseconds. Returns the number
+    /// of deliveries queued.
+    pub fn enqueue(&mut self, events: &Store, now: u64) -> Result<usize, Error> {
+        let mut queued = 0;
+        for webhook in &self.webhooks {
+            loop {
+                let cursor = self.outbox.cursor(&webhook.name)?;
+                let batch = events.events_after(cursor, EVENT_BATCH)?;
+                let Some(last) = batch.last() else {
+                    break;
+                };
+                let deliveries: Vec<_> = batch
+                    .iter()
+                    .filter(|event| webhook.matches(event))
+                    .map(webhook::new_delivery)
+                    .collect();
+                queued += self
+                    .outbox
+                    .enqueue(&webhook.name, &deliveries, last.id, now)?;
+            }
+        }
+        Ok(queued)
+    }
+
+    /// Attempt the deliveries due at `now`, in unix milliseconds.
+    /// Deliveries to a webhook no longer configured wait until it is again.
+    pub fn deliver_due(&mut self, now: u64) -> Result<Report, Error> {
+        let mut report = Report::default();
+        for webhook in &self.webhooks {
+            for delivery in self.outbox.due(&webhook.name, now, DELIVERY_BATCH)? {
+                match webhook::post(&self.agent, webhook, &delivery, now / 1000) {
+                    Ok(()) => {
+                        self.outbox.delivered(&delivery, now)?;
+                        report.delivered += 1;
+                    }
+                    Err(error) => {
+                        eprintln!(
+                            "{}: delivery {} failed: {}",
+                            webhook.name, delivery.idempotency_key, error
+                        );
+                        if self.outbox.failed(&delivery, &error, now, &self.retry)? {
+                            report.dead += 1;
+                        } else {
+                            report.retried += 1;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+}
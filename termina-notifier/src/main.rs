@@ -0,0 +1,109 @@
+//! Delivers signed webhooks for Termina escrow events
+//!
+//! ```sh
+//! ERP_WEBHOOK_SECRET=... cargo run --manifest-path termina-notifier/Cargo.toml -- \
+//!     --config termina-notifier/config/example.toml \
+//!     --database termina-notifier.sqlite
+//! ```
+//!
+//! Events come from a `termina-indexer` database, or from the node's stream
+//! when the config names an indexer config instead; the notifier then
+//! indexes into its own database. The outbox lives in that database too.
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::Parser;
+use termina_indexer::store::Store;
+use termina_notifier::{
+    config::{Config, Source},
+    outbox::Outbox,
+    Error, Notifier,
+};
+
+#[derive(Parser)]
+#[command(about = "Deliver signed webhooks for Termina escrow events")]
+struct Args {
+    /// Event source, webhooks and retry policy
+    #[arg(long)]
+    config: PathBuf,
+    /// SQLite database for the outbox, and for events followed from a stream
+    #[arg(long, default_value = "termina-notifier.sqlite")]
+    database: PathBuf,
+    /// Queue the dead letters again before delivering
+    #[arg(long)]
+    requeue_dead_letters: bool,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock is after 1970")
+        .as_millis() as u64
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    let config = Config::load(&args.config)?;
+    let webhooks = config.webhooks()?;
+    let mut outbox = Outbox::open(&args.database)?;
+    if args.requeue_dead_letters {
+        let requeued = outbox.requeue_dead_letters(now())?;
+        println!("requeued {} dead letters", requeued);
+    }
+
+    let (events, mut indexer) = match &config.source {
+        Source::Database(path) => (Store::open(path)?, None),
+        Source::Stream(path) => {
+            let handle = follow(path, &args.database)?;
+            (Store::open(&args.database)?, Some(handle))
+        }
+    };
+    let mut notifier = Notifier::new(outbox, webhooks, config.retry.clone(), config.timeout());
+    loop {
+        if indexer.as_ref().is_some_and(JoinHandle::is_finished) {
+            let handle = indexer.take().expect("checked above");
+            return handle
+                .join()
+                .expect("the indexer doesn't panic")
+                .map_err(Error::Index);
+        }
+        let queued = notifier.enqueue(&events, now())?;
+        let report = notifier.deliver_due(now())?;
+        if queued > 0 || report != Default::default() {
+            println!(
+                "queued {}, delivered {}, retrying {}, dead {}",
+                queued, report.delivered, report.retried, report.dead
+            );
+        }
+        thread::sleep(config.poll());
+    }
+}
+
+/// Index the stream an indexer config names into `database`, in the
+/// background. The thread ends only on an error reconnecting won't fix.
+fn follow(
+    indexer_config: &Path,
+    database: &Path,
+) -> Result<JoinHandle<Result<(), termina_indexer::Error>>, Error> {
+    let config = termina_indexer::config::Config::load(indexer_config)?;
+    let contracts = config.contracts()?;
+    let mut store = Store::open(database)?;
+    store.register(&contracts)?;
+    Ok(thread::spawn(move || {
+        termina_indexer::follow(&mut store, &contracts, &config.events_url, config.retry())
+    }))
+}
@@ -0,0 +1,247 @@
+//! SQLite outbox of webhook deliveries
+//!
+//! - `deliveries`: one row per event and webhook, pending until delivered
+//! - `dead_letters`: deliveries that failed every attempt
+//! - `webhook_cursors`: the last event each webhook was offered
+//!
+//! A delivery is queued with its payload, so every attempt sends the same
+//! body, and is unique per webhook and idempotency key.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{config::RetryPolicy, error::Error};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS webhook_cursors (
+    webhook TEXT PRIMARY KEY,
+    event_id INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS deliveries (
+    id INTEGER PRIMARY KEY,
+    webhook TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT,
+    delivered_at INTEGER,
+    UNIQUE (webhook, idempotency_key)
+);
+CREATE TABLE IF NOT EXISTS dead_letters (
+    id INTEGER PRIMARY KEY,
+    webhook TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    failed_at INTEGER NOT NULL,
+    UNIQUE (webhook, idempotency_key)
+);
+";
+
+/// A delivery to queue
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewDelivery {
+    pub idempotency_key: String,
+    pub event: String,
+    pub payload: String,
+}
+
+/// A queued delivery
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delivery {
+    pub id: i64,
+    pub webhook: String,
+    pub idempotency_key: String,
+    pub event: String,
+    pub payload: String,
+    /// Failed attempts so far
+    pub attempts: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadLetter {
+    pub webhook: String,
+    pub idempotency_key: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: u32,
+    pub last_error: String,
+    /// Unix milliseconds
+    pub failed_at: u64,
+}
+
+pub struct Outbox {
+    connection: Connection,
+}
+
+impl Outbox {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Outbox::new(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Outbox::new(Connection::open_in_memory()?)
+    }
+
+    fn new(connection: Connection) -> Result<Self, Error> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Outbox { connection })
+    }
+
+    /// Row id of the last event offered to a webhook, 0 before the first
+    pub fn cursor(&self, webhook: &str) -> Result<i64, Error> {
+        let cursor = self
+            .connection
+            .query_row(
+                "SELECT event_id FROM webhook_cursors WHERE webhook = ?1",
+                [webhook],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(cursor.unwrap_or_default())
+    }
+
+    /// Queue deliveries due at `now` and move the webhook's cursor to the
+    /// last event they were picked from, together. Returns the number of
+    /// deliveries not queued before.
+    pub fn enqueue(
+        &mut self,
+        webhook: &str,
+        deliveries: &[NewDelivery],
+        cursor: i64,
+        now: u64,
+    ) -> Result<usize, Error> {
+        let transaction = self.connection.transaction()?;
+        let mut queued = 0;
+        for delivery in deliveries {
+            queued += transaction.execute(
+                "INSERT OR IGNORE INTO deliveries
+                     (webhook, idempotency_key, event, payload, next_attempt_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    webhook,
+                    delivery.idempotency_key,
+                    delivery.event,
+                    delivery.payload,
+                    now as i64,
+                ],
+            )?;
+        }
+        transaction.execute(
+            "INSERT INTO webhook_cursors (webhook, event_id) VALUES (?1, ?2)
+             ON CONFLICT (webhook) DO UPDATE SET event_id = ?2",
+            params![webhook, cursor],
+        )?;
+        transaction.commit()?;
+        Ok(queued)
+    }
+
+    /// Pending deliveries to a webhook whose next attempt is due at `now`,
+    /// oldest first
+    pub fn due(&self, webhook: &str, now: u64, limit: usize) -> Result<Vec<Delivery>, Error> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, webhook, idempotency_key, event, payload, attempts FROM deliveries
+             WHERE webhook = ?1 AND delivered_at IS NULL AND next_attempt_at <= ?2
+             ORDER BY id LIMIT ?3",
+        )?;
+        let rows = statement.query_map(params![webhook, now as i64, limit as i64], |row| {
+            Ok(Delivery {
+                id: row.get(0)?,
+                webhook: row.get(1)?,
+                idempotency_key: row.get(2)?,
+                event: row.get(3)?,
+                payload: row.get(4)?,
+                attempts: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn delivered(&self, delivery: &Delivery, now: u64) -> Result<(), Error> {
+        self.connection.execute(
+            "UPDATE deliveries SET delivered_at = ?2 WHERE id = ?1",
+            params![delivery.id, now as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Record a failed attempt. The delivery is retried after the policy's
+    /// delay, or becomes a dead letter once out of attempts; returns whether
+    /// it did.
+    pub fn failed(
+        &mut self,
+        delivery: &Delivery,
+        error: &str,
+        now: u64,
+        policy: &RetryPolicy,
+    ) -> Result<bool, Error> {
+        let attempts = delivery.attempts + 1;
+        let transaction = self.connection.transaction()?;
+        let dead = attempts >= policy.max_attempts;
+        if dead {
+            transaction.execute(
+                "INSERT OR REPLACE INTO dead_letters
+                     (webhook, idempotency_key, event, payload, attempts, last_error, failed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    delivery.webhook,
+                    delivery.idempotency_key,
+                    delivery.event,
+                    delivery.payload,
+                    attempts,
+                    error,
+                    now as i64,
+                ],
+            )?;
+            transaction.execute("DELETE FROM deliveries WHERE id = ?1", [delivery.id])?;
+        } else {
+            let next_attempt_at = now + policy.delay(attempts).as_millis() as u64;
+            transaction.execute(
+                "UPDATE deliveries SET attempts = ?2, next_attempt_at = ?3, last_error = ?4
+                 WHERE id = ?1",
+                params![delivery.id, attempts, next_attempt_at as i64, error],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(dead)
+    }
+
+    pub fn dead_letters(&self) -> Result<Vec<DeadLetter>, Error> {
+        let mut statement = self.connection.prepare(
+            "SELECT webhook, idempotency_key, event, payload, attempts, last_error, failed_at
+             FROM dead_letters ORDER BY id",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok(DeadLetter {
+                webhook: row.get(0)?,
+                idempotency_key: row.get(1)?,
+                event: row.get(2)?,
+                payload: row.get(3)?,
+                attempts: row.get(4)?,
+                last_error: row.get(5)?,
+                failed_at: row.get::<_, i64>(6)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Queue the dead letters again, with fresh attempts, due at `now`.
+    /// Returns how many were queued.
+    pub fn requeue_dead_letters(&mut self, now: u64) -> Result<usize, Error> {
+        let transaction = self.connection.transaction()?;
+        let requeued = transaction.execute(
+            "INSERT OR IGNORE INTO deliveries
+                 (webhook, idempotency_key, event, payload, next_attempt_at)
+             SELECT webhook, idempotency_key, event, payload, ?1 FROM dead_letters",
+            [now as i64],
+        )?;
+        transaction.execute("DELETE FROM dead_letters", [])?;
+        transaction.commit()?;
+        Ok(requeued)
+    }
+}
@@ -0,0 +1,85 @@
+//! Webhook payloads and their delivery over HTTP
+//!
+//! A delivery is a JSON `POST` with these headers:
+//!
+//! - `Idempotency-Key`: the payload's `id`, the same on every attempt
+//! - `X-Termina-Event`: the event name
+//! - `X-Termina-Delivery-Attempt`: 1 on the first attempt
+//! - `X-Termina-Signature`: see [`signature`](crate::signature)
+//!
+//! Any 2xx answer counts as delivered.
+
+use serde_json::{json, Value};
+use termina_indexer::store::EventRow;
+
+use crate::{
+    config::Webhook,
+    outbox::{Delivery, NewDelivery},
+    signature::{sign, SIGNATURE_HEADER},
+};
+
+/// Key a receiver can drop repeated deliveries by: the deploy hash, the
+/// address of the contract's events dictionary and the event's CES index in
+/// it. All three come from the chain, so the key stays the same when events
+/// are indexed again, whatever name the config gives the contract.
+pub fn idempotency_key(event: &EventRow) -> String {
+    format!(
+        "{}-{}-{}",
+        event.deploy_hash,
+        hex::encode(event.events_uref),
+        event.event_index
+    )
+}
+
+/// Body of the webhook for an event. `state` is the escrow's state after
+/// the event, or null if the event didn't change it.
+pub fn payload(event: &EventRow) -> Value {
+    let fields: Value = serde_json::from_str(&event.fields).unwrap_or(Value::Null);
+    json!({
+        "id": idempotency_key(event),
+        "event": event.name,
+        "contract": event.contract,
+        "escrow_id": event.escrow_id,
+        "state": event.state,
+        "event_index": event.event_index,
+        "deploy_hash": event.deploy_hash,
+        "block_hash": event.block_hash,
+        "timestamp": event.timestamp,
+        "fields": fields,
+    })
+}
+
+pub fn new_delivery(event: &EventRow) -> NewDelivery {
+    NewDelivery {
+        idempotency_key: idempotency_key(event),
+        event: event.name.clone(),
+        payload: payload(event).to_string(),
+    }
+}
+
+/// Send a delivery, signed at `now` in unix seconds. An error says why the
+/// attempt failed.
+pub fn post(
+    agent: &ureq::Agent,
+    webhook: &Webhook,
+    delivery: &Delivery,
+    now: u64,
+) -> Result<(), String> {
+    let signature = sign(&webhook.secret, now, delivery.payload.as_bytes());
+    let result = agent
+        .post(&webhook.url)
+        .set("Content-Type", "application/json")
+        .set("Idempotency-Key", &delivery.idempotency_key)
+        .set("X-Termina-Event", &delivery.event)
+        .set(
+            "X-Termina-Delivery-Attempt",
+            &(delivery.attempts + 1).to_string(),
+        )
+        .set(SIGNATURE_HEADER, &signature)
+        .send_string(&delivery.payload);
+    match result {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(status, _)) => Err(format!("HTTP {}", status)),
+        Err(ureq::Error::Transport(error)) => Err(error.to_string()),
+    }
+}
@@ -0,0 +1,268 @@
+//! Delivery of the events of a recorded node stream to a local HTTP
+//! receiver
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::PathBuf,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use serde_json::Value;
+use termina_indexer::{
+    config::{Contract, Flavour},
+    index,
+    store::Store,
+};
+use termina_notifier::{
+    config::{RetryPolicy, Webhook},
+    outbox::Outbox,
+    signature::verify,
+    Notifier, Report,
+};
+
+const SECRET: &[u8] = b"whsec_test";
+/// 2024-01-01T00:00:00Z
+const NOW: u64 = 1_704_067_200_000;
+
+/// Events of the native escrow in the indexer's 2.0 fixture: created,
+/// accepted, funded, disputed, resolved and paid out
+fn indexed_events() -> Store {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../termina-indexer/tests/fixtures/native-2.0.sse");
+    let stream = std::fs::read_to_string(path).unwrap();
+    let contracts = [Contract {
+        name: "invoices".to_string(),
+        flavour: Flavour::Native,
+        events_uref: [2; 32],
+    }];
+    let mut store = Store::open_in_memory().unwrap();
+    store.register(&contracts).unwrap();
+    index(&mut store, &contracts, "native", stream.as_bytes()).unwrap();
+    store
+}
+
+struct Request {
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+/// Answer each request with the next status, then stop
+fn receiver(statuses: Vec<u16>) -> (String, JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hooks/termina", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for status in statuses {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(socket.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line.trim_end(), "POST /hooks/termina HTTP/1.1");
+            let mut headers = BTreeMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                match line.trim_end().split_once(": ") {
+                    Some((name, value)) => {
+                        headers.insert(name.to_ascii_lowercase(), value.to_string())
+                    }
+                    None => break,
+                };
+            }
+            let length = headers["content-length"].parse().unwrap();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            write!(
+                socket,
+                "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+            requests.push(Request {
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            });
+        }
+        requests
+    });
+    (url, handle)
+}
+
+fn notifier(url: &str, states: &[&str], retry: RetryPolicy) -> Notifier {
+    let webhook = Webhook {
+        name: "erp".to_string(),
+        url: url.to_string(),
+        secret: SECRET.to_vec(),
+        events: Vec::new(),
+        states: states.iter().map(|state| state.to_string()).collect(),
+    };
+    Notifier::new(
+        Outbox::open_in_memory().unwrap(),
+        vec![webhook],
+        retry,
+        Duration::from_secs(5),
+    )
+}
+
+fn hash(seed: u8) -> String {
+    hex::encode([seed; 32])
+}
+
+#[test]
+fn test_deliver_signed_events() {
+    let events = indexed_events();
+    let (url, receiver) = receiver(vec![200, 204]);
+    let mut notifier = notifier(&url, &["Funded", "Released"], RetryPolicy::default());
+
+    assert_eq!(notifier.enqueue(&events, NOW).unwrap(), 2);
+    // Queued once, however often the events are read
+    assert_eq!(notifier.enqueue(&events, NOW).unwrap(), 0);
+    assert_eq!(
+        notifier.deliver_due(NOW).unwrap(),
+        Report {
+            delivered: 2,
+            ..Report::default()
+        }
+    );
+    assert_eq!(notifier.deliver_due(NOW).unwrap(), Report::default());
+
+    let requests = receiver.join().unwrap();
+    let [funded, released] = &requests[..] else {
+        panic!("expected two deliveries");
+    };
+    for request in [funded, released] {
+        let signature = &request.headers["x-termina-signature"];
+        assert!(verify(
+            SECRET,
+            signature,
+            request.body.as_bytes(),
+            NOW / 1000,
+            300
+        ));
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.headers["x-termina-delivery-attempt"], "1");
+    }
+
+    let body: Value = serde_json::from_str(&funded.body).unwrap();
+    let key = format!("{}-{}-2", hash(13), hex::encode([2; 32]));
+    assert_eq!(funded.headers["idempotency-key"], key);
+    assert_eq!(funded.headers["x-termina-event"], "FundsDeposited");
+    assert_eq!(body["id"], key.as_str());
+    assert_eq!(body["event"], "FundsDeposited");
+    assert_eq!(body["contract"], "invoices");
+    assert_eq!(body["escrow_id"], "1");
+    assert_eq!(body["state"], "Funded");
+    assert_eq!(body["event_index"], 2);
+    assert_eq!(body["fields"]["amount"], "5000000000");
+
+    // The payout in the same deploy changes no state, so only the
+    // resolution is delivered
+    let body: Value = serde_json::from_str(&released.body).unwrap();
+    assert_eq!(
+        body["id"],
+        format!("{}-{}-4", hash(16), hex::encode([2; 32]))
+    );
+    assert_eq!(body["event"], "DisputeResolved");
+    assert_eq!(body["state"], "Released");
+}
+
+#[test]
+fn test_retry_with_backoff() {
+    let events = indexed_events();
+    let (url, receiver) = receiver(vec![503, 500, 200]);
+    let mut notifier = notifier(
+        &url,
+        &["Funded"],
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay_secs: 10,
+            max_delay_secs: 60,
+        },
+    );
+    notifier.enqueue(&events, NOW).unwrap();
+
+    let retried = Report {
+        retried: 1,
+        ..Report::default()
+    };
+    assert_eq!(notifier.deliver_due(NOW).unwrap(), retried);
+    // Not due again until 10 s later, then 20 s after that
+    assert_eq!(
+        notifier.deliver_due(NOW + 9_999).unwrap(),
+        Report::default()
+    );
+    assert_eq!(notifier.deliver_due(NOW + 10_000).unwrap(), retried);
+    assert_eq!(
+        notifier.deliver_due(NOW + 29_999).unwrap(),
+        Report::default()
+    );
+    assert_eq!(notifier.deliver_due(NOW + 30_000).unwrap().delivered, 1);
+
+    let requests = receiver.join().unwrap();
+    let attempts: Vec<_> = requests
+        .iter()
+        .map(|request| request.headers["x-termina-delivery-attempt"].as_str())
+        .collect();
+    assert_eq!(attempts, ["1", "2", "3"]);
+    // Every attempt carries the same body and key, signed when it is sent
+    assert!(requests
+        .iter()
+        .all(|request| request.body == requests[0].body
+            && request.headers["idempotency-key"] == requests[0].headers["idempotency-key"]));
+    assert!(verify(
+        SECRET,
+        &requests[2].headers["x-termina-signature"],
+        requests[2].body.as_bytes(),
+        (NOW + 30_000) / 1000,
+        0
+    ));
+    assert!(notifier.outbox().dead_letters().unwrap().is_empty());
+}
+
+#[test]
+fn test_dead_letters() {
+    let events = indexed_events();
+    let (url, receiver) = receiver(vec![500, 500, 200]);
+    let mut notifier = notifier(
+        &url,
+        &["Funded"],
+        RetryPolicy {
+            max_attempts: 2,
+            base_delay_secs: 1,
+            max_delay_secs: 1,
+        },
+    );
+    notifier.enqueue(&events, NOW).unwrap();
+    assert_eq!(notifier.deliver_due(NOW).unwrap().retried, 1);
+    assert_eq!(notifier.deliver_due(NOW + 1_000).unwrap().dead, 1);
+    assert_eq!(
+        notifier.deliver_due(NOW + 60_000).unwrap(),
+        Report::default()
+    );
+
+    let dead_letters = notifier.outbox().dead_letters().unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    let dead_letter = &dead_letters[0];
+    assert_eq!(dead_letter.webhook, "erp");
+    assert_eq!(
+        dead_letter.idempotency_key,
+        format!("{}-{}-2", hash(13), hex::encode([2; 32]))
+    );
+    assert_eq!(dead_letter.attempts, 2);
+    assert_eq!(dead_letter.last_error, "HTTP 500");
+    assert_eq!(dead_letter.failed_at, NOW + 1_000);
+
+    // Requeued, it starts over and goes through
+    let later = NOW + 120_000;
+    assert_eq!(
+        notifier.outbox_mut().requeue_dead_letters(later).unwrap(),
+        1
+    );
+    assert_eq!(notifier.deliver_due(later).unwrap().delivered, 1);
+    assert!(notifier.outbox().dead_letters().unwrap().is_empty());
+    let requests = receiver.join().unwrap();
+    assert_eq!(requests[2].headers["x-termina-delivery-attempt"], "1");
+}